@@ -0,0 +1,490 @@
+// AI 提取待办：调用 OpenAI 兼容的 /chat/completions 接口，
+// 模型返回的 JSON 在这里校验和修正后再交给界面，不直接信任
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::ser::SerializeStruct;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use std::fmt;
+use tauri::State;
+use crate::config::{load_ai_settings_from_db_internal, AISettings};
+use crate::priority::Priority;
+use crate::recurrence::{local_to_timestamp, normalize_rule};
+use crate::todo::AddTodoParams;
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+pub const EXTRACTION_PROMPT: &str = r#"你的任务是根据给定的一段话，提炼出一个todo事项。该事项需要包含事项名称、起止时间、重复周期、备注信息和重要程度，并且只返回一个json对象，不要有额外说明。
+
+在提炼todo事项时，请关注段落中提及的具体任务内容、时间相关信息（包括开始时间、结束时间、重复情况）、额外的备注说明以及重要程度。事项名称应更合理，例如能准确概括任务核心。
+对于重复周期，遵循以下规则填写：
+1. 若输入中明确了每年、每月等周期字段，则按照输入里的来；
+2. 如果输入没有明确，则进行判断，给出合适的周期，如生日为每年、女性生理期为每月等；
+3. 如果输入没有明确且也不能进行判断合适的周期，则默认为“one”。
+周期只能是 one、day、workday、week、month、year 之一。
+
+如果段落中没有明确提及某一项信息（如起止时间、备注信息、重要程度），请将该项信息标记如下：
+- 开始时间和结束时间：若未提及开始时间，标记为“无”；若未提及结束时间，标记为空。时间的格式需严格统一为YYYY-MM-DD HH:mm，不能出现中文表述。
+- 备注信息：若未提及，必须严格标记为“无”，不得自行添加其他内容，只能依据段落中明确提及的内容填写备注信息。
+- 重要程度：用整数表示，可选值为0（重要不紧急）、1（重要且紧急）、2（不重要不紧急）、3（不重要但紧急）。若不能判断，默认标记为0。
+
+以下是一个示例json格式：
+{
+        "title": "示例事项",
+        "start_time": "开始时间",
+        "end_time":   "结束时间",
+        "cycle": "day",
+        "notes": "这是一个示例备注",
+        "level": 0
+}
+"#;
+
+// 模型用来表示“没有”的写法
+const PLACEHOLDERS: &[&str] = &["", "无", "空", "暂无", "未提及", "null", "none", "n/a"];
+
+// 模型可能给出的时间格式，后面的只有日期
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y/%m/%d %H:%M",
+    "%Y年%m月%d日 %H:%M",
+    "%Y年%m月%d日%H:%M",
+];
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y年%m月%d日"];
+
+const CHINESE_CYCLES: &[(&str, &str)] = &[
+    ("仅一次", "one"),
+    ("一次", "one"),
+    ("不重复", "one"),
+    ("每天", "day"),
+    ("每日", "day"),
+    ("工作日", "workday"),
+    ("每周", "week"),
+    ("每月", "month"),
+    ("每年", "year"),
+    ("daily", "day"),
+    ("weekly", "week"),
+    ("monthly", "month"),
+    ("yearly", "year"),
+];
+
+const WEEKDAY_NAMES: [&str; 7] = ["星期一", "星期二", "星期三", "星期四", "星期五", "星期六", "星期日"];
+
+// AI 提取失败的原因，前端按 code 区分，message 用于提示
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiError {
+    NotConfigured,
+    EmptyInput,
+    Database(String),
+    Network(String),
+    Http { status: u16, body: String },
+    // 接口返回的内容不是预期的 chat completion 格式
+    BadResponse(String),
+    // 模型给出的待办无法修正
+    InvalidTodo(String),
+}
+
+impl AiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AiError::NotConfigured => "NOT_CONFIGURED",
+            AiError::EmptyInput => "EMPTY_INPUT",
+            AiError::Database(_) => "DATABASE_ERROR",
+            AiError::Network(_) => "NETWORK_ERROR",
+            AiError::Http { .. } => "HTTP_ERROR",
+            AiError::BadResponse(_) => "BAD_RESPONSE",
+            AiError::InvalidTodo(_) => "INVALID_TODO",
+        }
+    }
+}
+
+impl fmt::Display for AiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiError::NotConfigured => write!(f, "尚未配置 AI 的 API Key"),
+            AiError::EmptyInput => write!(f, "内容不能为空"),
+            AiError::Database(e) => write!(f, "读取 AI 配置失败: {}", e),
+            AiError::Network(e) => write!(f, "网络请求失败: {}", e),
+            AiError::Http { status, body } => write!(f, "AI 接口返回错误 {}: {}", status, body),
+            AiError::BadResponse(e) => write!(f, "AI 接口返回的内容无法解析: {}", e),
+            AiError::InvalidTodo(e) => write!(f, "AI 提取的待办无效: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AiError {}
+
+// 序列化为 { code, message }，与前端 api.call 读取错误的方式一致
+impl serde::Serialize for AiError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AiError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(serde::Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(serde::Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+// 调用 /chat/completions，返回第一条回复的内容
+async fn chat_completion(settings: &AISettings, system: &str, user: &str) -> Result<String, AiError> {
+    let url = format!("{}/chat/completions", settings.base_url.trim().trim_end_matches('/'));
+    let body = json!({
+        "model": settings.model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": user },
+        ],
+        "temperature": 0.1,
+        "stream": false,
+        "response_format": { "type": "json_object" },
+    });
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&url)
+        .bearer_auth(settings.api_key.trim())
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| AiError::Network(e.to_string()))?;
+
+    let status = response.status();
+    let text = response.text().await.map_err(|e| AiError::Network(e.to_string()))?;
+    if !status.is_success() {
+        let body: String = text.chars().take(500).collect();
+        return Err(AiError::Http { status: status.as_u16(), body });
+    }
+
+    let completion: ChatCompletion =
+        serde_json::from_str(&text).map_err(|e| AiError::BadResponse(e.to_string()))?;
+    completion
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|choice| choice.message.content)
+        .filter(|content| !content.trim().is_empty())
+        .ok_or_else(|| AiError::BadResponse("回复内容为空".to_string()))
+}
+
+// 从回复中取出 JSON 对象，兼容 ```json 代码块和前后的说明文字
+fn extract_json(content: &str) -> Result<Value, AiError> {
+    let start = content.find('{');
+    let end = content.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => return Err(AiError::InvalidTodo("回复中没有 JSON 对象".to_string())),
+    };
+    let value: Value = serde_json::from_str(json).map_err(|e| AiError::InvalidTodo(format!("JSON 格式错误: {}", e)))?;
+    match value {
+        Value::Object(_) => Ok(value),
+        _ => Err(AiError::InvalidTodo("回复不是 JSON 对象".to_string())),
+    }
+}
+
+// 按别名依次取字段
+fn field<'a>(todo: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| todo.get(name)).filter(|v| !v.is_null())
+}
+
+fn is_placeholder(text: &str) -> bool {
+    let text = text.trim();
+    PLACEHOLDERS.iter().any(|p| p.eq_ignore_ascii_case(text))
+}
+
+// 字段的文本内容，占位符视为没有
+fn text_field(todo: &Value, names: &[&str]) -> Option<String> {
+    match field(todo, names)? {
+        Value::String(s) if is_placeholder(s) => None,
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// 解析时间，返回时间戳和是否只有日期
+fn parse_time<Tz: TimeZone>(tz: &Tz, todo: &Value, names: &[&str], label: &str) -> Result<Option<(i64, bool)>, AiError> {
+    let value = match field(todo, names) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let text = match value {
+        // 时间戳，超过秒级范围的按毫秒处理
+        Value::Number(n) => {
+            let ts = n.as_i64().ok_or_else(|| AiError::InvalidTodo(format!("{}无效: {}", label, n)))?;
+            return Ok(Some((if ts > 100_000_000_000 { ts / 1000 } else { ts }, false)));
+        }
+        Value::String(s) if is_placeholder(s) => return Ok(None),
+        Value::String(s) => s.trim(),
+        other => return Err(AiError::InvalidTodo(format!("{}无效: {}", label, other))),
+    };
+
+    let parsed = DATETIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+        .map(|t| (t, false))
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(text, f).ok())
+                .map(|d| (d.and_time(NaiveTime::MIN), true))
+        });
+    let (local, date_only) =
+        parsed.ok_or_else(|| AiError::InvalidTodo(format!("{}格式无效: {}", label, text)))?;
+    let ts = local_to_timestamp(tz, local).ok_or_else(|| AiError::InvalidTodo(format!("{}无效: {}", label, text)))?;
+    Ok(Some((ts, date_only)))
+}
+
+// 重复周期：兼容旧版 cycle、中文写法和 RRULE
+fn parse_cycle(todo: &Value) -> Result<Option<String>, AiError> {
+    let cycle = match text_field(todo, &["cycle", "rrule", "repeat"]) {
+        Some(cycle) => cycle,
+        None => return Ok(None),
+    };
+    let lower = cycle.to_ascii_lowercase();
+    let cycle = CHINESE_CYCLES
+        .iter()
+        .find(|(name, _)| *name == lower)
+        .map_or(lower.as_str(), |(_, legacy)| legacy);
+    let cycle = if cycle.starts_with("freq=") { cycle.to_ascii_uppercase() } else { cycle.to_string() };
+    normalize_rule(Some(&cycle)).map_err(|e| AiError::InvalidTodo(format!("重复周期无效: {}", e)))
+}
+
+// 优先级：数字、数字字符串或象限名称，无法识别时取默认的重要不紧急
+fn parse_level(todo: &Value) -> Priority {
+    let level = match field(todo, &["level", "priority"]) {
+        Some(Value::Number(n)) => n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64),
+        Some(Value::String(s)) => s.trim().parse::<i64>().ok().or_else(|| {
+            (0..4).find(|l| Priority::try_from(*l).is_ok_and(|p| p.label() == s.trim()))
+        }),
+        _ => None,
+    };
+    level
+        .and_then(|l| Priority::try_from(l).ok())
+        .unwrap_or(Priority::ImportantNotUrgent)
+}
+
+// 校验并修正模型返回的待办
+pub fn repair_todo<Tz: TimeZone>(tz: &Tz, todo: &Value) -> Result<AddTodoParams, AiError> {
+    let title = text_field(todo, &["title", "name"])
+        .ok_or_else(|| AiError::InvalidTodo("缺少事项名称".to_string()))?;
+    let start = parse_time(tz, todo, &["start_time", "startTime"], "开始时间")?;
+    let mut end = parse_time(tz, todo, &["end_time", "endTime"], "结束时间")?;
+    // 只有日期的时间按全天处理，结束时间带时刻时不算全天
+    let all_day = start.is_some_and(|(_, date_only)| date_only) && end.is_none_or(|(_, date_only)| date_only);
+    // 结束时间早于开始时间时丢弃
+    if let (Some((start, _)), Some((end_ts, _))) = (start, end) {
+        if end_ts < start || (end_ts == start && !all_day) {
+            end = None;
+        }
+    }
+    Ok(AddTodoParams::parsed(
+        title,
+        start.map(|(ts, _)| ts),
+        end.map(|(ts, _)| ts),
+        text_field(todo, &["notes", "note", "remark"]),
+        parse_level(todo),
+        parse_cycle(todo)?,
+        all_day,
+    ))
+}
+
+// 按时区 tz 提取，now 为当前时间戳（秒），用于让模型理解“明天”等相对时间
+pub async fn extract_todo_in<Tz: TimeZone>(
+    tz: &Tz,
+    settings: &AISettings,
+    text: &str,
+    now: i64,
+) -> Result<AddTodoParams, AiError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AiError::EmptyInput);
+    }
+    if settings.api_key.trim().is_empty() || settings.base_url.trim().is_empty() {
+        return Err(AiError::NotConfigured);
+    }
+    let now = tz
+        .timestamp_opt(now, 0)
+        .single()
+        .ok_or_else(|| AiError::InvalidTodo("无效的当前时间".to_string()))?
+        .naive_local();
+    let prompt = format!(
+        "现在是{} {}; 要提取的文本是：{}",
+        now.format("%Y-%m-%d %H:%M"),
+        WEEKDAY_NAMES[now.weekday().num_days_from_monday() as usize],
+        text
+    );
+    let content = chat_completion(settings, EXTRACTION_PROMPT, &prompt).await?;
+    repair_todo(tz, &extract_json(&content)?)
+}
+
+// 用保存的 AI 配置从一段话中提取待办，返回新建待办的参数供界面确认
+#[tauri::command]
+pub async fn ai_extract_todo(pool: State<'_, SqlitePool>, text: String) -> Result<AddTodoParams, AiError> {
+    let settings = load_ai_settings_from_db_internal(pool.inner())
+        .await
+        .map_err(|e| AiError::Database(e.to_string()))?;
+    extract_todo_in(&Local, &settings, &text, Utc::now().timestamp()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Asia::Shanghai;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    fn local(s: &str) -> i64 {
+        let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        Shanghai.from_local_datetime(&naive).earliest().unwrap().timestamp()
+    }
+
+    // 只应答一次请求的本地 HTTP 服务，返回服务地址和收到的请求体
+    async fn mock_server(status: u16, body: String) -> (String, JoinHandle<(String, Value)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            let (head, body_start, length) = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(pos) = text.find("\r\n\r\n") {
+                    let head = text[..pos].to_string();
+                    let length = head
+                        .lines()
+                        .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    break (head, pos + 4, length);
+                }
+            };
+            while request.len() < body_start + length {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let request_body = serde_json::from_slice(&request[body_start..body_start + length]).unwrap();
+            (head, request_body)
+        });
+        (url, handle)
+    }
+
+    fn completion(content: &str) -> String {
+        json!({ "choices": [{ "index": 0, "message": { "role": "assistant", "content": content } }] }).to_string()
+    }
+
+    fn settings(base_url: &str) -> AISettings {
+        AISettings {
+            api_key: "test-key".to_string(),
+            base_url: base_url.to_string(),
+            model: "test-model".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn extracts_and_repairs_todo_from_mock_server() {
+        let content = "```json\n{\"title\": \"交周报\", \"start_time\": \"2026-10-15 15:00\", \"end_time\": \"\", \"cycle\": \"每周\", \"notes\": \"无\", \"level\": \"1\"}\n```";
+        let (url, server) = mock_server(200, completion(content)).await;
+
+        let todo = extract_todo_in(&Shanghai, &settings(&url), "明天下午三点交周报", local("2026-10-14 10:00"))
+            .await
+            .unwrap();
+        let todo = serde_json::to_value(todo).unwrap();
+        assert_eq!(todo["title"], "交周报");
+        assert_eq!(todo["startTime"], local("2026-10-15 15:00"));
+        assert_eq!(todo["endTime"], Value::Null);
+        assert_eq!(todo["notes"], Value::Null);
+        assert_eq!(todo["rrule"], "FREQ=WEEKLY");
+        assert_eq!(todo["level"], 1);
+        assert_eq!(todo["allDay"], false);
+
+        let (head, request) = server.await.unwrap();
+        assert!(head.starts_with("POST /v1/chat/completions"));
+        assert!(head.to_ascii_lowercase().contains("authorization: bearer test-key"));
+        assert_eq!(request["model"], "test-model");
+        assert_eq!(request["response_format"]["type"], "json_object");
+        assert_eq!(request["messages"][0]["content"], EXTRACTION_PROMPT);
+        assert_eq!(request["messages"][1]["content"], "现在是2026-10-14 10:00 星期三; 要提取的文本是：明天下午三点交周报");
+    }
+
+    #[tokio::test]
+    async fn http_and_content_errors_are_typed() {
+        let (url, _server) = mock_server(401, "{\"error\":\"invalid key\"}".to_string()).await;
+        let err = extract_todo_in(&Shanghai, &settings(&url), "交周报", 0).await.unwrap_err();
+        assert_eq!(err, AiError::Http { status: 401, body: "{\"error\":\"invalid key\"}".to_string() });
+        assert_eq!(
+            serde_json::to_value(&err).unwrap()["code"],
+            "HTTP_ERROR"
+        );
+
+        let (url, _server) = mock_server(200, "{\"unexpected\": true}".to_string()).await;
+        let err = extract_todo_in(&Shanghai, &settings(&url), "交周报", 0).await.unwrap_err();
+        assert_eq!(err.code(), "BAD_RESPONSE");
+
+        let (url, _server) = mock_server(200, completion("好的，已经为你记下了")).await;
+        let err = extract_todo_in(&Shanghai, &settings(&url), "交周报", 0).await.unwrap_err();
+        assert_eq!(err.code(), "INVALID_TODO");
+
+        let err = extract_todo_in(&Shanghai, &settings(""), "交周报", 0).await.unwrap_err();
+        assert_eq!(err, AiError::NotConfigured);
+        let err = extract_todo_in(&Shanghai, &settings("http://127.0.0.1:1"), "  ", 0).await.unwrap_err();
+        assert_eq!(err, AiError::EmptyInput);
+    }
+
+    #[test]
+    fn repair_rejects_or_fixes_fields() {
+        let repair = |todo: Value| repair_todo(&Shanghai, &todo).map(|t| serde_json::to_value(t).unwrap());
+
+        // 只有日期时为全天，越界的优先级取默认值，早于开始的结束时间被丢弃
+        let todo = repair(json!({
+            "title": "生日", "start_time": "2026-11-02", "end_time": "2026-11-01",
+            "cycle": "year", "notes": "买蛋糕", "level": 7
+        }))
+        .unwrap();
+        assert_eq!(todo["startTime"], local("2026-11-02 00:00"));
+        assert_eq!(todo["endTime"], Value::Null);
+        assert_eq!(todo["allDay"], true);
+        assert_eq!(todo["rrule"], "FREQ=YEARLY");
+        assert_eq!(todo["notes"], "买蛋糕");
+        assert_eq!(todo["level"], 0);
+
+        let todo = repair(json!({ "title": "整理房间", "start_time": "无", "cycle": "one", "level": "不重要但紧急" })).unwrap();
+        assert_eq!(todo["startTime"], Value::Null);
+        assert_eq!(todo["rrule"], Value::Null);
+        assert_eq!(todo["level"], 3);
+
+        assert_eq!(repair(json!({ "title": "无" })).unwrap_err().code(), "INVALID_TODO");
+        assert!(matches!(
+            repair(json!({ "title": "开会", "start_time": "明天下午3点" })),
+            Err(AiError::InvalidTodo(_))
+        ));
+        assert!(matches!(
+            repair(json!({ "title": "开会", "cycle": "fortnightly" })),
+            Err(AiError::InvalidTodo(_))
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, State};
+use crate::dependency::{blocked_todo_ids, emit_todos_unblocked, unblocked_since};
+use crate::history::{diff_advanced_series, record_changes, ChangeSource, FieldChange};
+use crate::holiday::{load_workday_calendar, WorkdayCalendar};
+use crate::priority::Priority;
+use crate::subtask::{set_status, sync_parent_completion};
+use crate::todo::{advance_series, emit_todos_changed, local_day_start, move_to_trash, next_occurrence, shifted_end, Todo, TODO_SELECT};
+use crate::trash::restore_from_trash;
+
+// 批量操作中每一条待办的结果，失败的待办不影响其他待办
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkResult {
+    pub id: i64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// 改期方式：整体偏移若干分钟，或保持时刻不变移到指定日期
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum Reschedule {
+    #[serde(rename = "offset")]
+    Offset { minutes: i64 },
+    #[serde(rename = "date")]
+    Date { date: NaiveDate },
+}
+
+enum BulkAction {
+    Complete,
+    Uncomplete,
+    Delete,
+    Restore,
+    Reschedule(Reschedule),
+    SetLevel(i64),
+}
+
+fn rescheduled_start(start_time: i64, reschedule: &Reschedule) -> Result<i64, String> {
+    match reschedule {
+        Reschedule::Offset { minutes } => Ok(start_time + minutes * 60),
+        Reschedule::Date { date } => {
+            let current = Local.timestamp_opt(start_time, 0).single().ok_or("无效的开始时间")?;
+            Local
+                .from_local_datetime(&date.and_time(current.time()))
+                .earliest()
+                .map(|dt| dt.timestamp())
+                .ok_or_else(|| "无效的日期".to_string())
+        }
+    }
+}
+
+async fn apply_one(
+    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
+    id: i64,
+    action: &BulkAction,
+    calendar: &WorkdayCalendar,
+) -> Result<(), String> {
+    if let BulkAction::Restore = action {
+        return restore_from_trash(pool, conn, id).await;
+    }
+
+    let mut todo = sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ? AND status != 2", TODO_SELECT))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Todo not found")?;
+
+    match action {
+        BulkAction::Complete => {
+            if todo.status == 1 {
+                return Ok(());
+            }
+            // 循环任务只完成当前这一次
+            if todo.rrule.is_some() {
+                let current = todo.clone();
+                advance_series(conn, &mut todo, "done", calendar).await?;
+                record_changes(conn, id, &diff_advanced_series(&current, &todo), ChangeSource::Ui).await?;
+            } else {
+                set_status(conn, id, 1).await?;
+                record_changes(conn, id, &[FieldChange::new("status", todo.status, 1)], ChangeSource::Ui).await?;
+            }
+            if let Some(parent_id) = todo.parent_id {
+                sync_parent_completion(pool, conn, parent_id).await?;
+            }
+        }
+        BulkAction::Uncomplete => {
+            if todo.status == 0 {
+                return Ok(());
+            }
+            set_status(conn, id, 0).await?;
+            record_changes(conn, id, &[FieldChange::new("status", todo.status, 0)], ChangeSource::Ui).await?;
+            if let Some(parent_id) = todo.parent_id {
+                sync_parent_completion(pool, conn, parent_id).await?;
+            }
+        }
+        BulkAction::Delete => move_to_trash(pool, conn, id, todo.parent_id).await?,
+        BulkAction::Restore => {}
+        BulkAction::Reschedule(reschedule) => {
+            let Some(old_start) = todo.start_time else {
+                return Err("尚未安排时间的待办不能改期".to_string());
+            };
+            let mut start_time = rescheduled_start(old_start, reschedule)?;
+            if todo.all_day {
+                start_time = local_day_start(start_time, 0);
+            }
+            // 循环任务按推迟当前这一次处理，不改变系列的规则
+            if todo.rrule.is_some() {
+                if let Some(next) = next_occurrence(&todo, calendar) {
+                    if start_time >= next {
+                        return Err("推迟时间不能晚于下一次的开始时间".to_string());
+                    }
+                }
+                todo.occurrence_start = todo.occurrence_start.or(todo.start_time);
+            }
+            let old_end = todo.end_time;
+            todo.end_time = shifted_end(&todo, start_time);
+            todo.start_time = Some(start_time);
+
+            sqlx::query("UPDATE todos SET start_time = ?, end_time = ?, occurrence_start = ?, updated_at = datetime('now') WHERE id = ?")
+                .bind(todo.start_time)
+                .bind(todo.end_time)
+                .bind(todo.occurrence_start)
+                .bind(id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let mut changes = Vec::new();
+            if todo.start_time != Some(old_start) {
+                changes.push(FieldChange::new("startTime", old_start, todo.start_time));
+            }
+            if old_end != todo.end_time {
+                changes.push(FieldChange::new("endTime", old_end, todo.end_time));
+            }
+            record_changes(conn, id, &changes, ChangeSource::Ui).await?;
+        }
+        BulkAction::SetLevel(level) => {
+            if todo.level == *level {
+                return Ok(());
+            }
+            sqlx::query("UPDATE todos SET level = ?, updated_at = datetime('now') WHERE id = ?")
+                .bind(level)
+                .bind(id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+            record_changes(conn, id, &[FieldChange::new("level", todo.level, *level)], ChangeSource::Ui).await?;
+        }
+    }
+    Ok(())
+}
+
+// 在一个事务中处理所有待办，每条待办使用单独的保存点，失败时只回滚这一条；
+// 返回每条待办的结果和因此不再被阻塞的待办
+async fn apply_bulk(pool: &SqlitePool, ids: Vec<i64>, action: BulkAction) -> Result<(Vec<BulkResult>, Vec<i64>), String> {
+    let calendar = load_workday_calendar(pool).await?;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut results: Vec<BulkResult> = Vec::with_capacity(ids.len());
+    let blocked_before = match action {
+        BulkAction::Complete => Some(blocked_todo_ids(&mut tx).await?),
+        _ => None,
+    };
+
+    for id in ids {
+        if results.iter().any(|r| r.id == id) {
+            continue;
+        }
+        let mut savepoint = tx.begin().await.map_err(|e| e.to_string())?;
+        match apply_one(pool, &mut savepoint, id, &action, &calendar).await {
+            Ok(()) => {
+                savepoint.commit().await.map_err(|e| e.to_string())?;
+                results.push(BulkResult { id, success: true, error: None });
+            }
+            Err(e) => {
+                savepoint.rollback().await.map_err(|e| e.to_string())?;
+                results.push(BulkResult { id, success: false, error: Some(e) });
+            }
+        }
+    }
+    let unblocked = match blocked_before {
+        Some(before) => unblocked_since(&mut tx, &before).await?,
+        None => Vec::new(),
+    };
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok((results, unblocked))
+}
+
+async fn run_bulk(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    ids: Vec<i64>,
+    action: BulkAction,
+) -> Result<Vec<BulkResult>, String> {
+    let (results, unblocked) = apply_bulk(pool, ids, action).await?;
+    let changed = results.iter().filter(|r| r.success).map(|r| r.id).collect::<Vec<_>>();
+    if !changed.is_empty() {
+        emit_todos_changed(app_handle, "bulk", &changed);
+    }
+    emit_todos_unblocked(app_handle, &changed, unblocked);
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn bulk_complete_todos(app_handle: AppHandle, pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<Vec<BulkResult>, String> {
+    run_bulk(&app_handle, pool.inner(), ids, BulkAction::Complete).await
+}
+
+#[tauri::command]
+pub async fn bulk_uncomplete_todos(app_handle: AppHandle, pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<Vec<BulkResult>, String> {
+    run_bulk(&app_handle, pool.inner(), ids, BulkAction::Uncomplete).await
+}
+
+// 批量移入回收站
+#[tauri::command]
+pub async fn bulk_delete_todos(app_handle: AppHandle, pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<Vec<BulkResult>, String> {
+    run_bulk(&app_handle, pool.inner(), ids, BulkAction::Delete).await
+}
+
+// 批量从回收站恢复
+#[tauri::command]
+pub async fn bulk_restore_todos(app_handle: AppHandle, pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<Vec<BulkResult>, String> {
+    run_bulk(&app_handle, pool.inner(), ids, BulkAction::Restore).await
+}
+
+// 批量改期，截止时间随开始时间一起移动
+#[tauri::command]
+pub async fn bulk_reschedule_todos(
+    app_handle: AppHandle,
+    pool: State<'_, SqlitePool>,
+    ids: Vec<i64>,
+    reschedule: Reschedule,
+) -> Result<Vec<BulkResult>, String> {
+    run_bulk(&app_handle, pool.inner(), ids, BulkAction::Reschedule(reschedule)).await
+}
+
+#[tauri::command]
+pub async fn bulk_set_todo_level(
+    app_handle: AppHandle,
+    pool: State<'_, SqlitePool>,
+    ids: Vec<i64>,
+    level: Priority,
+) -> Result<Vec<BulkResult>, String> {
+    run_bulk(&app_handle, pool.inner(), ids, BulkAction::SetLevel(level.level())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_todo(pool: &SqlitePool, title: &str) -> i64 {
+        sqlx::query("INSERT INTO todos (title, start_time) VALUES (?, 1000)")
+            .bind(title)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn statuses(pool: &SqlitePool) -> Vec<(String, i64)> {
+        sqlx::query_as::<_, (String, i64)>("SELECT title, status FROM todos ORDER BY id").fetch_all(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_id_rolls_back_only_itself() {
+        let pool = pool().await;
+        let first = add_todo(&pool, "A").await;
+        let failing = add_todo(&pool, "B").await;
+        let last = add_todo(&pool, "C").await;
+        // B 的状态已经改为完成，写修改历史时才失败，这一步之前的修改也要回滚
+        sqlx::query(&format!(
+            "CREATE TRIGGER fail_history BEFORE INSERT ON todo_history WHEN NEW.todo_id = {}
+             BEGIN SELECT RAISE(ABORT, 'history is read-only'); END",
+            failing
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (results, unblocked) = apply_bulk(&pool, vec![first, failing, 999, last, first], BulkAction::Complete).await.unwrap();
+        assert!(unblocked.is_empty());
+        let outcome = results.iter().map(|r| (r.id, r.success)).collect::<Vec<_>>();
+        assert_eq!(outcome, [(first, true), (failing, false), (999, false), (last, true)]);
+        assert!(results[1].error.as_deref().unwrap().contains("history is read-only"));
+        assert_eq!(results[2].error.as_deref(), Some("Todo not found"));
+
+        assert_eq!(
+            statuses(&pool).await,
+            [("A".to_string(), 1), ("B".to_string(), 0), ("C".to_string(), 1)]
+        );
+        let recorded = sqlx::query_scalar::<_, i64>("SELECT todo_id FROM todo_history ORDER BY todo_id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(recorded, [first, last]);
+    }
+
+    #[tokio::test]
+    async fn bulk_completing_recurring_todo_is_recorded() {
+        let pool = pool().await;
+        let id = sqlx::query("INSERT INTO todos (title, start_time, rrule, series_start, occurrence_start) VALUES ('晨跑', 1000, 'FREQ=DAILY', 1000, 1000)")
+            .execute(&pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+
+        let (results, _) = apply_bulk(&pool, vec![id], BulkAction::Complete).await.unwrap();
+        assert!(results[0].success);
+        let history = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(
+            "SELECT field, old_value, new_value FROM todo_history WHERE todo_id = ?"
+        )
+        .bind(id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(history, [("occurrenceStart".to_string(), Some("1000".to_string()), Some("87400".to_string()))]);
+    }
+}
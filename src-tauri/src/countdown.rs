@@ -1,8 +1,12 @@
 use crate::database::{CountdownConfig, save_countdown_record};
+use crate::pomodoro::{fire_pomodoro_phase, tick_pomodoro, PomodoroState};
+use crate::reminder::SnoozePreset;
 use chrono::{ Local, NaiveTime, TimeZone};
 use serde::Serialize;
 use sqlx::SqlitePool;
-use tauri::{AppHandle, Emitter, State};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
 use tokio::time::{interval, Duration};
 
 #[derive(Debug, Clone, Serialize)]
@@ -13,6 +17,13 @@ pub struct CountdownData {
     pub status: String, // "running", "finished", "reset"
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct CountdownAlert {
+    #[serde(rename = "alertDate")]
+    pub alert_date: String,
+    pub repeat: bool, // 稍后提醒再次弹出
+}
+
 #[tauri::command]
 pub async fn update_countdown_config(
     pool: State<'_, SqlitePool>,
@@ -150,24 +161,56 @@ pub async fn calculate_countdown_timestamp(pool: &SqlitePool) -> CountdownData {
     }
 }
 
+// 计时循环是否已经启动；应用启动时和各计时窗口都会调用 start_countdown_timer，循环只能有一个
+#[derive(Default)]
+pub struct CountdownTimerState(AtomicBool);
+
+impl CountdownTimerState {
+    // 只有第一次调用返回 true
+    fn try_start(&self) -> bool {
+        !self.0.swap(true, Ordering::SeqCst)
+    }
+}
+
 #[tauri::command]
 pub async fn start_countdown_timer(
     app_handle: AppHandle,
     pool: State<'_, SqlitePool>,
+    timer: State<'_, CountdownTimerState>,
 ) -> Result<(), String> {
+    if !timer.try_start() {
+        return Ok(());
+    }
     let pool_clone = pool.inner().clone();
     
     tokio::spawn(async move {
         let mut interval = interval(Duration::from_secs(1));
+        // 已经写入过下班提醒的日期，避免下班后每秒都写数据库
+        let mut alerted_date: Option<String> = None;
 
         loop {
             interval.tick().await;
 
+            // 推进番茄钟并发送剩余时间
+            let pomodoro = app_handle.state::<PomodoroState>();
+            let now = Local::now().timestamp();
+            match tick_pomodoro(&pool_clone, pomodoro.inner(), now).await {
+                Ok(Some(event)) => fire_pomodoro_phase(&app_handle, event),
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to advance pomodoro: {}", e),
+            }
+            if let Ok(Some(status)) = pomodoro.inner().status(now) {
+                if let Err(e) = app_handle.emit("pomodoro-update", status) {
+                    eprintln!("Failed to emit pomodoro-update event: {}", e);
+                }
+            }
+
             // 获取当前配置
             let config = crate::config::load_countdown_config_from_db_internal(&pool_clone).await
                 .unwrap_or_else(|_| crate::config::get_default_countdown_config());
             
             // 如果启用了下班倒计时，就发送倒计时数据
+            let mut status = None;
             if config.enable_work_end_countdown {
                 let countdown_data = if let Some(countdown) = calculate_work_end_countdown(&pool_clone).await {
                     countdown
@@ -180,17 +223,124 @@ pub async fn start_countdown_timer(
                     }
                 };
                 
+                status = Some(countdown_data.status.clone());
+
                 // 发送倒计时更新事件
                 if let Err(e) = app_handle.emit("countdown-update", countdown_data) {
                     eprintln!("Failed to emit countdown-update event: {}", e);
                 }
             }
+
+            let today = Local::now().format("%Y-%m-%d").to_string();
+            match check_work_end_alerts(&pool_clone, status.as_deref(), &today, now, &mut alerted_date).await {
+                Ok(alerts) => alerts.into_iter().for_each(|alert| fire_work_end_alert(&app_handle, alert)),
+                Err(e) => eprintln!("Failed to check work end alert: {}", e),
+            }
         }
     });
 
     Ok(())
 }
 
+// 每秒检查需要弹出的下班提醒，status 为下班倒计时的状态，未启用时为 None
+// 到时间的稍后提醒不论倒计时处于什么状态都要弹出；当天的第一次提醒只在倒计时结束后创建，
+// alerted_date 记住已经创建过提醒的日期，避免下班后每秒都写数据库
+pub(crate) async fn check_work_end_alerts(
+    pool: &SqlitePool,
+    status: Option<&str>,
+    today: &str,
+    now: i64,
+    alerted_date: &mut Option<String>,
+) -> Result<Vec<CountdownAlert>, String> {
+    let mut alerts = take_snoozed_work_end_alerts(pool, now).await?;
+    if status == Some("finished") && alerted_date.as_deref() != Some(today) {
+        if let Some(alert) = create_work_end_alert(pool, today, now).await? {
+            alerts.push(alert);
+        }
+        *alerted_date = Some(today.to_string());
+    }
+    Ok(alerts)
+}
+
+// 下班提醒的状态保存在 countdown_alerts 表中，重启后稍后提醒仍然有效
+// 取出到时间的稍后提醒并清除稍后时间，now 为当前时间戳（秒）
+// 计时循环可能同时运行多个，用一条 UPDATE 认领，同一条提醒只会被一个循环取出
+pub(crate) async fn take_snoozed_work_end_alerts(pool: &SqlitePool, now: i64) -> Result<Vec<CountdownAlert>, String> {
+    let alert_dates = sqlx::query_scalar::<_, String>(
+        "UPDATE countdown_alerts SET fired_at = ?1, snoozed_until = NULL
+         WHERE acknowledged_at IS NULL AND snoozed_until IS NOT NULL AND snoozed_until <= ?1
+         RETURNING alert_date"
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(alert_dates.into_iter().map(|alert_date| CountdownAlert { alert_date, repeat: true }).collect())
+}
+
+// 记录当天的下班提醒，当天已经提醒过（包括重启前）时返回 None
+pub(crate) async fn create_work_end_alert(pool: &SqlitePool, alert_date: &str, now: i64) -> Result<Option<CountdownAlert>, String> {
+    let inserted = sqlx::query("INSERT OR IGNORE INTO countdown_alerts (alert_date, fired_at) VALUES (?, ?)")
+        .bind(alert_date)
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok((inserted.rows_affected() > 0).then(|| CountdownAlert { alert_date: alert_date.to_string(), repeat: false }))
+}
+
+fn fire_work_end_alert(app_handle: &AppHandle, alert: CountdownAlert) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("下班时间到了")
+        .body(if alert.repeat { "稍后提醒：该下班了" } else { "今天辛苦了" })
+        .show()
+    {
+        eprintln!("Failed to show work end notification: {}", e);
+    }
+
+    if let Err(e) = app_handle.emit("countdown-alert", alert) {
+        eprintln!("Failed to emit countdown-alert event: {}", e);
+    }
+}
+
+// 稍后再提醒下班，返回再次提醒的时间戳（秒）
+#[tauri::command]
+pub async fn snooze_countdown_alert(
+    pool: State<'_, SqlitePool>,
+    alert_date: String,
+    preset: SnoozePreset,
+) -> Result<i64, String> {
+    let until = preset.until(Local::now());
+    let result = sqlx::query("UPDATE countdown_alerts SET snoozed_until = ?, acknowledged_at = NULL WHERE alert_date = ?")
+        .bind(until)
+        .bind(alert_date)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("下班提醒不存在".to_string());
+    }
+    Ok(until)
+}
+
+#[tauri::command]
+pub async fn acknowledge_countdown_alert(
+    pool: State<'_, SqlitePool>,
+    alert_date: String,
+) -> Result<(), String> {
+    sqlx::query("UPDATE countdown_alerts SET acknowledged_at = ?, snoozed_until = NULL WHERE alert_date = ?")
+        .bind(Local::now().timestamp())
+        .bind(alert_date)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 // 重置下班倒计时到下一天
 #[tauri::command]
 pub async fn reset_work_end_countdown_to_next_day(
@@ -213,3 +363,92 @@ pub async fn reset_work_end_countdown_to_next_day(
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn snooze(pool: &SqlitePool, alert_date: &str, until: i64) {
+        sqlx::query("UPDATE countdown_alerts SET snoozed_until = ?, acknowledged_at = NULL WHERE alert_date = ?")
+            .bind(until)
+            .bind(alert_date)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn first_alert_only_once_per_day_after_finished() {
+        let pool = pool().await;
+        let mut alerted = None;
+
+        assert!(check_work_end_alerts(&pool, Some("running"), "2026-10-14", 1_000, &mut alerted).await.unwrap().is_empty());
+        assert_eq!(alerted, None);
+
+        let alerts = check_work_end_alerts(&pool, Some("finished"), "2026-10-14", 2_000, &mut alerted).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert!(!alerts[0].repeat);
+        assert_eq!(alerted.as_deref(), Some("2026-10-14"));
+        assert!(check_work_end_alerts(&pool, Some("finished"), "2026-10-14", 2_001, &mut alerted).await.unwrap().is_empty());
+
+        // 重启后内存中的记录丢失，数据库中已有当天的提醒时也不重复弹出
+        let mut restarted = None;
+        assert!(check_work_end_alerts(&pool, Some("finished"), "2026-10-14", 2_002, &mut restarted).await.unwrap().is_empty());
+        assert_eq!(restarted.as_deref(), Some("2026-10-14"));
+    }
+
+    #[tokio::test]
+    async fn snoozed_alert_fires_whatever_the_countdown_status() {
+        let pool = pool().await;
+        let mut alerted = None;
+        check_work_end_alerts(&pool, Some("finished"), "2026-10-14", 2_000, &mut alerted).await.unwrap();
+
+        // 重置到明天后倒计时为 reset，稍后提醒仍然按时弹出
+        snooze(&pool, "2026-10-14", 2_300).await;
+        assert!(check_work_end_alerts(&pool, Some("reset"), "2026-10-14", 2_299, &mut alerted).await.unwrap().is_empty());
+        let alerts = check_work_end_alerts(&pool, Some("reset"), "2026-10-14", 2_300, &mut alerted).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].repeat);
+        assert_eq!(alerts[0].alert_date, "2026-10-14");
+
+        // “明天早上”的稍后提醒在第二天倒计时还在进行时弹出
+        snooze(&pool, "2026-10-14", 90_000).await;
+        let alerts = check_work_end_alerts(&pool, Some("running"), "2026-10-15", 90_000, &mut alerted).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].repeat);
+
+        // 未启用下班倒计时时也会弹出，并且只弹一次
+        snooze(&pool, "2026-10-14", 95_000).await;
+        assert_eq!(check_work_end_alerts(&pool, None, "2026-10-15", 95_000, &mut alerted).await.unwrap().len(), 1);
+        assert!(check_work_end_alerts(&pool, None, "2026-10-15", 95_001, &mut alerted).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_loops_fire_snoozed_alert_once() {
+        let pool = pool().await;
+        let mut alerted = None;
+        check_work_end_alerts(&pool, Some("finished"), "2026-10-14", 2_000, &mut alerted).await.unwrap();
+        snooze(&pool, "2026-10-14", 2_300).await;
+
+        let (a, b) = tokio::join!(
+            take_snoozed_work_end_alerts(&pool, 2_300),
+            take_snoozed_work_end_alerts(&pool, 2_300)
+        );
+        assert_eq!(a.unwrap().len() + b.unwrap().len(), 1);
+        assert!(take_snoozed_work_end_alerts(&pool, 2_301).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn timer_starts_once() {
+        let timer = CountdownTimerState::default();
+        assert!(timer.try_start());
+        assert!(!timer.try_start());
+        assert!(!timer.try_start());
+    }
+}
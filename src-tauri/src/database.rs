@@ -1,4 +1,4 @@
-use sqlx::SqlitePool;
+use sqlx::{Connection, SqliteConnection, SqlitePool};
 use tauri::State;
 use tauri_plugin_sql::{Migration, MigrationKind};
 
@@ -154,9 +154,539 @@ pub fn get_migrations() -> Vec<Migration> {
             ",
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 2,
+            description: "replace_todo_cycle_with_rrule",
+            sql: "
+                -- 重复规则改为 RFC 5545 RRULE，NULL 表示不重复
+                ALTER TABLE todos ADD COLUMN rrule TEXT;
+                -- 系列第一次发生的开始时间，用于计算 COUNT/INTERVAL
+                ALTER TABLE todos ADD COLUMN series_start INTEGER;
+                
+                UPDATE todos SET rrule = CASE cycle
+                    WHEN 'day' THEN 'FREQ=DAILY'
+                    WHEN 'week' THEN 'FREQ=WEEKLY'
+                    WHEN 'month' THEN 'FREQ=MONTHLY'
+                    WHEN 'year' THEN 'FREQ=YEARLY'
+                    ELSE NULL
+                END;
+                UPDATE todos SET series_start = start_time WHERE rrule IS NOT NULL;
+                
+                ALTER TABLE todos DROP COLUMN cycle;
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 3,
+            description: "create_todo_occurrences",
+            sql: "
+                -- 当前这一次按规则计划的开始时间，推迟时 start_time 会改变而它不变
+                ALTER TABLE todos ADD COLUMN occurrence_start INTEGER;
+                UPDATE todos SET occurrence_start = start_time WHERE rrule IS NOT NULL;
+                
+                -- 循环任务每一次的处理记录
+                CREATE TABLE IF NOT EXISTS todo_occurrences (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    todo_id INTEGER NOT NULL,
+                    scheduled_time INTEGER NOT NULL, -- 按规则计划的开始时间戳（秒）
+                    actual_start INTEGER NOT NULL, -- 实际开始时间戳（秒）
+                    status TEXT NOT NULL, -- done, skipped, missed
+                    recorded_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE (todo_id, scheduled_time),
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_todo_occurrences_todo ON todo_occurrences (todo_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 4,
+            description: "create_todo_reminders",
+            sql: "
+                -- 待办提醒：相对开始或截止时间的偏移
+                CREATE TABLE IF NOT EXISTS todo_reminders (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    todo_id INTEGER NOT NULL,
+                    anchor TEXT NOT NULL, -- start, end
+                    offset_minutes INTEGER NOT NULL DEFAULT 0, -- 负数表示提前
+                    fired_for INTEGER, -- 已经提醒过的开始/截止时间戳（秒），时间改变后会重新提醒
+                    fired_at INTEGER, -- 最近一次提醒的时间戳（秒）
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_todo_reminders_todo ON todo_reminders (todo_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "add_reminder_snooze_and_countdown_alerts",
+            sql: "
+                -- 稍后提醒与确认状态，重启后仍然有效
+                ALTER TABLE todo_reminders ADD COLUMN snoozed_until INTEGER;
+                ALTER TABLE todo_reminders ADD COLUMN acknowledged_at INTEGER;
+                -- 持续提醒间隔（分钟），仅对重要且紧急的待办生效，NULL 表示关闭
+                ALTER TABLE todo_reminders ADD COLUMN nag_minutes INTEGER;
+                
+                -- 下班倒计时结束提醒，每天一条
+                CREATE TABLE IF NOT EXISTS countdown_alerts (
+                    alert_date TEXT PRIMARY KEY, -- 本地日期 YYYY-MM-DD
+                    fired_at INTEGER NOT NULL,
+                    snoozed_until INTEGER,
+                    acknowledged_at INTEGER
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "create_tags",
+            sql: "
+                -- 标签表，如 @office、@home 或项目标签
+                CREATE TABLE IF NOT EXISTS tags (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL UNIQUE,
+                    color TEXT,
+                    sort_order INTEGER NOT NULL DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+                
+                -- 待办与标签的多对多关系
+                CREATE TABLE IF NOT EXISTS todo_tags (
+                    todo_id INTEGER NOT NULL,
+                    tag_id INTEGER NOT NULL,
+                    PRIMARY KEY (todo_id, tag_id),
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE,
+                    FOREIGN KEY (tag_id) REFERENCES tags (id) ON DELETE CASCADE
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_todo_tags_tag ON todo_tags (tag_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "create_projects",
+            sql: "
+                -- 项目/清单，用于区分工作与个人等不同来源的待办
+                CREATE TABLE IF NOT EXISTS projects (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    color TEXT,
+                    icon TEXT,
+                    archived BOOLEAN NOT NULL DEFAULT 0,
+                    sort_order INTEGER NOT NULL DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+                
+                -- 所属项目，NULL 表示未归入任何项目
+                ALTER TABLE todos ADD COLUMN project_id INTEGER REFERENCES projects (id) ON DELETE SET NULL;
+                CREATE INDEX IF NOT EXISTS idx_todos_project ON todos (project_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "add_todo_subtasks",
+            sql: "
+                -- 父任务，NULL 表示顶层待办
+                ALTER TABLE todos ADD COLUMN parent_id INTEGER REFERENCES todos (id) ON DELETE CASCADE;
+                -- 子任务在父任务中的顺序
+                ALTER TABLE todos ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+                -- 子任务全部完成时自动完成父任务
+                ALTER TABLE todos ADD COLUMN auto_complete BOOLEAN NOT NULL DEFAULT 0;
+                CREATE INDEX IF NOT EXISTS idx_todos_parent ON todos (parent_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "create_todos_fts",
+            sql: "
+                -- 标题与备注的全文索引，trigram 分词以支持中文等没有空格分词的文本
+                CREATE VIRTUAL TABLE IF NOT EXISTS todos_fts USING fts5(
+                    title, notes,
+                    content = 'todos', content_rowid = 'id',
+                    tokenize = 'trigram'
+                );
+                
+                CREATE TRIGGER IF NOT EXISTS todos_fts_insert AFTER INSERT ON todos BEGIN
+                    INSERT INTO todos_fts (rowid, title, notes) VALUES (new.id, new.title, new.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_fts_delete AFTER DELETE ON todos BEGIN
+                    INSERT INTO todos_fts (todos_fts, rowid, title, notes) VALUES ('delete', old.id, old.title, old.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_fts_update AFTER UPDATE OF title, notes ON todos BEGIN
+                    INSERT INTO todos_fts (todos_fts, rowid, title, notes) VALUES ('delete', old.id, old.title, old.notes);
+                    INSERT INTO todos_fts (rowid, title, notes) VALUES (new.id, new.title, new.notes);
+                END;
+                
+                -- 为已有的待办建立索引
+                INSERT INTO todos_fts (todos_fts) VALUES ('rebuild');
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "create_smart_lists_table",
+            sql: "
+                -- 智能清单：保存的查询条件，filter 为 JSON 格式的查询条件
+                CREATE TABLE IF NOT EXISTS smart_lists (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    icon TEXT,
+                    color TEXT,
+                    filter TEXT NOT NULL DEFAULT '{}',
+                    sort_order INTEGER NOT NULL DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "add_todo_trash",
+            sql: "
+                -- 移入回收站的时间戳（秒）及删除前的状态，恢复时还原
+                ALTER TABLE todos ADD COLUMN deleted_at INTEGER;
+                ALTER TABLE todos ADD COLUMN deleted_status INTEGER;
+                
+                -- 已删除的待办以最后更新时间作为删除时间
+                UPDATE todos SET deleted_at = CAST(strftime('%s', updated_at) AS INTEGER), deleted_status = 0 WHERE status = 2;
+                
+                CREATE INDEX IF NOT EXISTS idx_todos_deleted_at ON todos (status, deleted_at);
+                
+                -- 回收站设置，retention_days 为 0 表示不自动清理
+                CREATE TABLE IF NOT EXISTS trash_settings (
+                    id INTEGER PRIMARY KEY,
+                    retention_days INTEGER NOT NULL DEFAULT 30,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "create_todo_changes",
+            sql: "
+                -- 可撤销的待办修改，before_state/after_state 为修改前后相关待办的 JSON 快照
+                CREATE TABLE IF NOT EXISTS todo_changes (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    action TEXT NOT NULL, -- add, update, delete
+                    todo_id INTEGER NOT NULL,
+                    title TEXT NOT NULL,
+                    before_state TEXT NOT NULL,
+                    after_state TEXT NOT NULL,
+                    undone BOOLEAN NOT NULL DEFAULT 0, -- 已撤销、可重做
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "create_todo_history",
+            sql: "
+                -- 待办字段的修改记录，old_value/new_value 为 JSON 编码的值，NULL 表示空值
+                CREATE TABLE IF NOT EXISTS todo_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    todo_id INTEGER NOT NULL,
+                    field TEXT NOT NULL, -- title, startTime, endTime, notes, level, rrule, projectId, autoComplete, status, tagIds
+                    old_value TEXT,
+                    new_value TEXT,
+                    source TEXT NOT NULL DEFAULT 'ui', -- ui, shortcut, ai, import
+                    changed_at INTEGER NOT NULL,
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_todo_history_todo ON todo_history (todo_id, changed_at);
+                CREATE INDEX IF NOT EXISTS idx_todo_history_field ON todo_history (field, todo_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "create_todo_dependencies",
+            sql: "
+                -- 待办之间的依赖，todo_id 需要等 depends_on_id 完成后才能开始
+                CREATE TABLE IF NOT EXISTS todo_dependencies (
+                    todo_id INTEGER NOT NULL,
+                    depends_on_id INTEGER NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (todo_id, depends_on_id),
+                    CHECK (todo_id != depends_on_id),
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE,
+                    FOREIGN KEY (depends_on_id) REFERENCES todos (id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_todo_dependencies_depends_on ON todo_dependencies (depends_on_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "create_time_entries",
+            sql: "
+                -- 待办的计时记录，end_time 为空表示正在计时
+                CREATE TABLE IF NOT EXISTS time_entries (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    todo_id INTEGER NOT NULL,
+                    start_time INTEGER NOT NULL,
+                    end_time INTEGER,
+                    note TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_time_entries_todo ON time_entries (todo_id, start_time);
+                CREATE INDEX IF NOT EXISTS idx_time_entries_start ON time_entries (start_time);
+                -- 同一时间只能有一个正在进行的计时
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_running ON time_entries ((end_time IS NULL)) WHERE end_time IS NULL;
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "add_pomodoro",
+            sql: "
+                -- 番茄钟设置
+                CREATE TABLE IF NOT EXISTS pomodoro_settings (
+                    id INTEGER PRIMARY KEY,
+                    focus_minutes INTEGER NOT NULL DEFAULT 25,
+                    short_break_minutes INTEGER NOT NULL DEFAULT 5,
+                    long_break_minutes INTEGER NOT NULL DEFAULT 15,
+                    rounds INTEGER NOT NULL DEFAULT 4, -- 每完成几个专注进行一次长休息
+                    auto_start BOOLEAN NOT NULL DEFAULT 1,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+
+                -- 番茄钟的每个阶段记录到 countdown_records，duration 为实际进行的秒数
+                ALTER TABLE countdown_records ADD COLUMN todo_id INTEGER REFERENCES todos (id) ON DELETE SET NULL;
+                ALTER TABLE countdown_records ADD COLUMN round INTEGER;
+                ALTER TABLE countdown_records ADD COLUMN planned_duration INTEGER;
+
+                CREATE INDEX IF NOT EXISTS idx_countdown_records_todo ON countdown_records (todo_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "add_todo_completed_at",
+            sql: "
+                -- 完成的时间戳（秒），未完成为空
+                ALTER TABLE todos ADD COLUMN completed_at INTEGER;
+
+                -- 已完成的待办只能以最后修改时间近似
+                UPDATE todos SET completed_at = CAST(strftime('%s', updated_at) AS INTEGER)
+                WHERE status = 1 OR (status = 2 AND deleted_status = 1);
+
+                CREATE INDEX IF NOT EXISTS idx_todos_completed_at ON todos (completed_at);
+
+                -- 状态在未完成和已完成之间切换时维护完成时间；撤销重做时写回的完成时间保持不变
+                CREATE TRIGGER IF NOT EXISTS todos_completed_at_set AFTER UPDATE OF status ON todos
+                WHEN new.status = 1 AND old.status = 0 AND new.completed_at IS NULL BEGIN
+                    UPDATE todos SET completed_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = new.id;
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_completed_at_clear AFTER UPDATE OF status ON todos
+                WHEN new.status = 0 AND old.status = 1 AND new.completed_at IS NOT NULL BEGIN
+                    UPDATE todos SET completed_at = NULL WHERE id = new.id;
+                END;
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 18,
+            description: "create_escalation_rules",
+            sql: "
+                -- 临近截止自动升级优先级的规则，默认都不启用
+                CREATE TABLE IF NOT EXISTS escalation_settings (
+                    id INTEGER PRIMARY KEY,
+                    promote_enabled BOOLEAN NOT NULL DEFAULT 0,
+                    promote_hours INTEGER NOT NULL DEFAULT 24, -- 截止前多少小时升级为紧急
+                    overdue_enabled BOOLEAN NOT NULL DEFAULT 0,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+
+                -- 规则的每次触发，同一截止时间每条规则只触发一次；截止时间修改后可以再次触发
+                CREATE TABLE IF NOT EXISTS todo_escalations (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+                    rule TEXT NOT NULL, -- promote, overdue
+                    end_time INTEGER NOT NULL,
+                    old_level INTEGER NOT NULL,
+                    new_level INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    UNIQUE (todo_id, rule, end_time)
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 19,
+            description: "nullable_start_time_and_all_day",
+            sql: "
+                -- SQLite 不能修改列的约束，重建 todos 表：start_time 可为空，表示收集箱中尚未安排时间的待办；
+                -- all_day 为全天待办，开始和截止时间均为当天零点，截止时间按截止日期当天结束计算
+                CREATE TABLE todos_new (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    title TEXT NOT NULL,
+                    start_time INTEGER, -- 开始时间戳（秒），为空表示尚未安排
+                    end_time INTEGER, -- 结束时间戳（秒），可为空
+                    notes TEXT,
+                    level INTEGER NOT NULL DEFAULT 0,
+                    status INTEGER NOT NULL DEFAULT 0, -- 0: pending, 1: completed, 2: deleted
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    rrule TEXT,
+                    series_start INTEGER,
+                    occurrence_start INTEGER,
+                    project_id INTEGER REFERENCES projects (id) ON DELETE SET NULL,
+                    parent_id INTEGER REFERENCES todos (id) ON DELETE CASCADE,
+                    sort_order INTEGER NOT NULL DEFAULT 0,
+                    auto_complete BOOLEAN NOT NULL DEFAULT 0,
+                    deleted_at INTEGER,
+                    deleted_status INTEGER,
+                    completed_at INTEGER,
+                    all_day BOOLEAN NOT NULL DEFAULT 0
+                );
+
+                INSERT INTO todos_new (id, title, start_time, end_time, notes, level, status, created_at, updated_at, rrule, series_start, occurrence_start, project_id, parent_id, sort_order, auto_complete, deleted_at, deleted_status, completed_at)
+                SELECT id, title, start_time, end_time, notes, level, status, created_at, updated_at, rrule, series_start, occurrence_start, project_id, parent_id, sort_order, auto_complete, deleted_at, deleted_status, completed_at FROM todos;
+
+                -- 保留自增序号，已删除的 id 不被重新使用
+                UPDATE sqlite_sequence SET seq = MAX(seq, COALESCE((SELECT seq FROM sqlite_sequence WHERE name = 'todos'), 0)) WHERE name = 'todos_new';
+                INSERT INTO sqlite_sequence (name, seq)
+                SELECT 'todos_new', seq FROM sqlite_sequence
+                WHERE name = 'todos' AND NOT EXISTS (SELECT 1 FROM sqlite_sequence WHERE name = 'todos_new');
+
+                DROP TABLE todos;
+                ALTER TABLE todos_new RENAME TO todos;
+
+                CREATE INDEX IF NOT EXISTS idx_todos_status ON todos (status);
+                CREATE INDEX IF NOT EXISTS idx_todos_level ON todos (level);
+                CREATE INDEX IF NOT EXISTS idx_todos_project ON todos (project_id);
+                CREATE INDEX IF NOT EXISTS idx_todos_parent ON todos (parent_id);
+                CREATE INDEX IF NOT EXISTS idx_todos_deleted_at ON todos (status, deleted_at);
+                CREATE INDEX IF NOT EXISTS idx_todos_completed_at ON todos (completed_at);
+                CREATE INDEX IF NOT EXISTS idx_todos_start_time ON todos (start_time);
+
+                -- 触发器随旧表一起删除，重新创建；id 不变，全文索引无需重建
+                CREATE TRIGGER IF NOT EXISTS todos_fts_insert AFTER INSERT ON todos BEGIN
+                    INSERT INTO todos_fts (rowid, title, notes) VALUES (new.id, new.title, new.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_fts_delete AFTER DELETE ON todos BEGIN
+                    INSERT INTO todos_fts (todos_fts, rowid, title, notes) VALUES ('delete', old.id, old.title, old.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_fts_update AFTER UPDATE OF title, notes ON todos BEGIN
+                    INSERT INTO todos_fts (todos_fts, rowid, title, notes) VALUES ('delete', old.id, old.title, old.notes);
+                    INSERT INTO todos_fts (rowid, title, notes) VALUES (new.id, new.title, new.notes);
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_completed_at_set AFTER UPDATE OF status ON todos
+                WHEN new.status = 1 AND old.status = 0 AND new.completed_at IS NULL BEGIN
+                    UPDATE todos SET completed_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = new.id;
+                END;
+                CREATE TRIGGER IF NOT EXISTS todos_completed_at_clear AFTER UPDATE OF status ON todos
+                WHEN new.status = 0 AND old.status = 1 AND new.completed_at IS NOT NULL BEGIN
+                    UPDATE todos SET completed_at = NULL WHERE id = new.id;
+                END;
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 20,
+            description: "keep_time_entries_of_purged_todos",
+            sql: "
+                -- 待办被彻底删除后保留其计时记录：todo_id 置空，todo_title 记下删除时的标题供统计使用
+                CREATE TABLE time_entries_new (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    todo_id INTEGER,
+                    todo_title TEXT,
+                    start_time INTEGER NOT NULL,
+                    end_time INTEGER,
+                    note TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE SET NULL
+                );
+
+                INSERT INTO time_entries_new (id, todo_id, start_time, end_time, note, created_at, updated_at)
+                SELECT id, todo_id, start_time, end_time, note, created_at, updated_at FROM time_entries;
+
+                -- 保留自增序号
+                UPDATE sqlite_sequence SET seq = MAX(seq, COALESCE((SELECT seq FROM sqlite_sequence WHERE name = 'time_entries'), 0)) WHERE name = 'time_entries_new';
+                INSERT INTO sqlite_sequence (name, seq)
+                SELECT 'time_entries_new', seq FROM sqlite_sequence
+                WHERE name = 'time_entries' AND NOT EXISTS (SELECT 1 FROM sqlite_sequence WHERE name = 'time_entries_new');
+
+                DROP TABLE time_entries;
+                ALTER TABLE time_entries_new RENAME TO time_entries;
+
+                CREATE INDEX IF NOT EXISTS idx_time_entries_todo ON time_entries (todo_id, start_time);
+                CREATE INDEX IF NOT EXISTS idx_time_entries_start ON time_entries (start_time);
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_running ON time_entries ((end_time IS NULL)) WHERE end_time IS NULL;
+
+                -- 删除待办前记下标题，级联删除的子任务同样会触发
+                CREATE TRIGGER IF NOT EXISTS todos_time_entries_title BEFORE DELETE ON todos BEGIN
+                    UPDATE time_entries SET todo_title = old.title WHERE todo_id = old.id;
+                END;
+            ",
+            kind: MigrationKind::Up,
+        },
     ]
 }
 
+// 按版本号依次执行尚未执行过的迁移
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // 重建表时不能级联删除引用它的数据，迁移期间关闭外键约束，每次迁移提交前再检查一遍
+    let mut conn = pool.acquire().await?;
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+    let result = apply_migrations(&mut conn).await;
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+    result
+}
+
+async fn apply_migrations(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for migration in get_migrations() {
+        let applied = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM schema_migrations WHERE version = ?")
+            .bind(migration.version)
+            .fetch_one(&mut *conn)
+            .await?;
+        if applied > 0 {
+            continue;
+        }
+
+        let mut tx = conn.begin().await?;
+        sqlx::query(migration.sql).execute(&mut *tx).await?;
+        if sqlx::query("PRAGMA foreign_key_check").fetch_optional(&mut *tx).await?.is_some() {
+            return Err(sqlx::Error::Protocol(format!("迁移 {} 违反外键约束", migration.description)));
+        }
+        sqlx::query("INSERT INTO schema_migrations (version, description) VALUES (?, ?)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
 // 数据库操作函数 - 只保留倒计时记录相关
 
 #[tauri::command]
@@ -170,4 +700,91 @@ pub async fn save_countdown_record(pool: State<'_, SqlitePool>, mode: String, ta
         .map_err(|e| e.to_string())?;
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 只执行到 version 为止的迁移，模拟旧版本的数据库
+    async fn migrate_to(pool: &SqlitePool, version: i64) {
+        sqlx::query("CREATE TABLE schema_migrations (version INTEGER PRIMARY KEY, description TEXT NOT NULL, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP)")
+            .execute(pool)
+            .await
+            .unwrap();
+        for migration in get_migrations().into_iter().filter(|m| m.version <= version) {
+            sqlx::query(migration.sql).execute(pool).await.unwrap();
+            sqlx::query("INSERT INTO schema_migrations (version, description) VALUES (?, ?)")
+                .bind(migration.version)
+                .bind(migration.description)
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn fts_ids(pool: &SqlitePool, term: &str) -> Vec<i64> {
+        sqlx::query_scalar::<_, i64>("SELECT rowid FROM todos_fts WHERE todos_fts MATCH ? ORDER BY rowid")
+            .bind(format!("\"{}\"", term))
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rebuilding_todos_keeps_ids_and_search() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        migrate_to(&pool, 18).await;
+
+        for title in ["quarterly report", "team meeting", "deleted draft"] {
+            sqlx::query("INSERT INTO todos (title, start_time, notes) VALUES (?, 1000, 'weekly notes')")
+                .bind(title)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO todos (title, start_time, parent_id) VALUES ('subtask', 1000, 1)").execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO time_entries (todo_id, start_time, end_time) VALUES (2, 0, 60)").execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM todos WHERE id = 3").execute(&pool).await.unwrap();
+
+        run_migrations(&pool).await.unwrap();
+
+        let todos = sqlx::query_as::<_, (i64, String, Option<i64>, bool)>("SELECT id, title, parent_id, all_day FROM todos ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            todos,
+            [
+                (1, "quarterly report".to_string(), None, false),
+                (2, "team meeting".to_string(), None, false),
+                (4, "subtask".to_string(), Some(1), false),
+            ]
+        );
+
+        // 已删除的 id 不被重新使用，开始时间可以为空
+        let id = sqlx::query("INSERT INTO todos (title, start_time) VALUES ('inbox meeting', NULL)")
+            .execute(&pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        assert_eq!(id, 5);
+
+        // 全文索引的触发器仍然有效
+        assert_eq!(fts_ids(&pool, "meeting").await, [2, 5]);
+        assert_eq!(fts_ids(&pool, "weekly").await, [1, 2]);
+        sqlx::query("UPDATE todos SET title = 'annual report' WHERE id = 2").execute(&pool).await.unwrap();
+        assert_eq!(fts_ids(&pool, "meeting").await, [5]);
+        assert_eq!(fts_ids(&pool, "report").await, [1, 2]);
+        sqlx::query("DELETE FROM todos WHERE id = 1").execute(&pool).await.unwrap();
+        assert_eq!(fts_ids(&pool, "report").await, [2]);
+        assert!(fts_ids(&pool, "draft").await.is_empty());
+
+        // 完成时间的触发器仍然有效
+        sqlx::query("UPDATE todos SET status = 1 WHERE id = 2").execute(&pool).await.unwrap();
+        let completed_at = sqlx::query_scalar::<_, Option<i64>>("SELECT completed_at FROM todos WHERE id = 2").fetch_one(&pool).await.unwrap();
+        assert!(completed_at.is_some());
+
+        let entry = sqlx::query_as::<_, (Option<i64>, i64)>("SELECT todo_id, end_time FROM time_entries").fetch_one(&pool).await.unwrap();
+        assert_eq!(entry, (Some(2), 60));
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+use crate::todo::{Todo, TODO_SELECT};
+
+// 前置任务中还有未完成的，待办即为被阻塞
+const BLOCKED_SELECT: &str = "SELECT DISTINCT d.todo_id FROM todo_dependencies d
+     JOIN todos t ON t.id = d.todo_id JOIN todos b ON b.id = d.depends_on_id
+     WHERE t.status = 0 AND b.status = 0";
+
+// 待办的前置任务和后续任务
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoDependencies {
+    #[serde(rename = "blockedBy")]
+    pub blocked_by: Vec<Todo>, // 需要先完成的待办
+    pub blocking: Vec<Todo>, // 等待该待办完成的待办
+}
+
+// todos-unblocked 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct TodosUnblockedEvent {
+    #[serde(rename = "completedIds")]
+    pub completed_ids: Vec<i64>,
+    pub ids: Vec<i64>, // 因此不再被阻塞的待办
+}
+
+// 为一组todo标记是否被阻塞
+pub(crate) async fn attach_blocked(pool: &SqlitePool, todos: &mut [Todo]) -> Result<(), String> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+    let ids = todos.iter().map(|t| t.id.to_string()).collect::<Vec<_>>().join(",");
+    let blocked = sqlx::query_scalar::<_, i64>(&format!("{} AND d.todo_id IN ({})", BLOCKED_SELECT, ids))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for todo in todos.iter_mut() {
+        todo.blocked = blocked.contains(&todo.id);
+    }
+    Ok(())
+}
+
+// 当前所有被阻塞的待办，完成待办前后各取一次，差集即为被解除阻塞的待办
+pub(crate) async fn blocked_todo_ids(conn: &mut SqliteConnection) -> Result<HashSet<i64>, String> {
+    let ids = sqlx::query_scalar::<_, i64>(BLOCKED_SELECT)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(ids.into_iter().collect())
+}
+
+// 之前被阻塞、现在仍未完成且不再被阻塞的待办
+pub(crate) async fn unblocked_since(conn: &mut SqliteConnection, before: &HashSet<i64>) -> Result<Vec<i64>, String> {
+    let after = blocked_todo_ids(conn).await?;
+    let candidates = before.difference(&after).map(|id| id.to_string()).collect::<Vec<_>>();
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+    sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT id FROM todos WHERE id IN ({}) AND status = 0 ORDER BY id",
+        candidates.join(",")
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn emit_todos_unblocked(app_handle: &AppHandle, completed_ids: &[i64], ids: Vec<i64>) {
+    if ids.is_empty() {
+        return;
+    }
+    let event = TodosUnblockedEvent { completed_ids: completed_ids.to_vec(), ids };
+    if let Err(e) = app_handle.emit("todos-unblocked", event) {
+        eprintln!("Failed to emit todos-unblocked event: {}", e);
+    }
+}
+
+async fn load_active_todo(conn: &mut SqliteConnection, id: i64) -> Result<Todo, String> {
+    sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ? AND status != 2", TODO_SELECT))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Todo not found".to_string())
+}
+
+// 循环任务一直处于未完成状态，作为前置任务会永远阻塞
+pub(crate) async fn ensure_not_blocking(conn: &mut SqliteConnection, id: i64) -> Result<(), String> {
+    let blocking = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM todo_dependencies WHERE depends_on_id = ?)"
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    if blocking {
+        return Err("该待办是其他待办的前置任务，不能设为循环任务".to_string());
+    }
+    Ok(())
+}
+
+// 添加依赖：todo_id 需要等 depends_on_id 完成后才能开始
+#[tauri::command]
+pub async fn add_todo_dependency(pool: State<'_, SqlitePool>, todo_id: i64, depends_on_id: i64) -> Result<(), String> {
+    insert_dependency(pool.inner(), todo_id, depends_on_id).await
+}
+
+async fn insert_dependency(pool: &SqlitePool, todo_id: i64, depends_on_id: i64) -> Result<(), String> {
+    if todo_id == depends_on_id {
+        return Err("待办不能依赖自身".to_string());
+    }
+    // 检查和写入放在同一个事务中，避免并发添加 A→B 和 B→A 时都通过循环检查
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    load_active_todo(&mut tx, todo_id).await?;
+    let blocker = load_active_todo(&mut tx, depends_on_id).await?;
+    if blocker.rrule.is_some() {
+        return Err("循环任务不能作为前置任务".to_string());
+    }
+
+    // depends_on_id 直接或间接依赖 todo_id 时会形成循环
+    let cycle = sqlx::query_scalar::<_, bool>(
+        "WITH RECURSIVE chain(id) AS (
+             SELECT ?
+             UNION
+             SELECT d.depends_on_id FROM todo_dependencies d JOIN chain ON d.todo_id = chain.id
+         )
+         SELECT EXISTS (SELECT 1 FROM chain WHERE id = ?)"
+    )
+    .bind(depends_on_id)
+    .bind(todo_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    if cycle {
+        return Err("依赖关系不能形成循环".to_string());
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO todo_dependencies (todo_id, depends_on_id) VALUES (?, ?)")
+        .bind(todo_id)
+        .bind(depends_on_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_todo_dependency(pool: State<'_, SqlitePool>, todo_id: i64, depends_on_id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM todo_dependencies WHERE todo_id = ? AND depends_on_id = ?")
+        .bind(todo_id)
+        .bind(depends_on_id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 获取待办的前置任务和后续任务，不含回收站中的待办
+#[tauri::command]
+pub async fn get_todo_dependencies(pool: State<'_, SqlitePool>, todo_id: i64) -> Result<TodoDependencies, String> {
+    let mut blocked_by = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE id IN (SELECT depends_on_id FROM todo_dependencies WHERE todo_id = ?) AND status != 2 ORDER BY status, start_time",
+        TODO_SELECT
+    ))
+    .bind(todo_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut blocking = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE id IN (SELECT todo_id FROM todo_dependencies WHERE depends_on_id = ?) AND status != 2 ORDER BY status, start_time",
+        TODO_SELECT
+    ))
+    .bind(todo_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_blocked(pool.inner(), &mut blocked_by).await?;
+    attach_blocked(pool.inner(), &mut blocking).await?;
+    Ok(TodoDependencies { blocked_by, blocking })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_todo(pool: &SqlitePool, title: &str, rrule: Option<&str>) -> i64 {
+        sqlx::query("INSERT INTO todos (title, start_time, rrule, series_start) VALUES (?, 1000, ?, 1000)")
+            .bind(title)
+            .bind(rrule)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn complete(pool: &SqlitePool, id: i64) -> Vec<i64> {
+        let mut conn = pool.acquire().await.unwrap();
+        let before = blocked_todo_ids(&mut conn).await.unwrap();
+        sqlx::query("UPDATE todos SET status = 1 WHERE id = ?").bind(id).execute(&mut *conn).await.unwrap();
+        unblocked_since(&mut conn, &before).await.unwrap()
+    }
+
+    async fn dependency_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM todo_dependencies").fetch_one(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn cycles_are_rejected() {
+        let pool = pool().await;
+        let a = add_todo(&pool, "A", None).await;
+        let b = add_todo(&pool, "B", None).await;
+        let c = add_todo(&pool, "C", None).await;
+
+        assert!(insert_dependency(&pool, a, a).await.is_err());
+        insert_dependency(&pool, a, b).await.unwrap();
+        // 重复添加不报错
+        insert_dependency(&pool, a, b).await.unwrap();
+        assert_eq!(insert_dependency(&pool, b, a).await.unwrap_err(), "依赖关系不能形成循环");
+
+        insert_dependency(&pool, b, c).await.unwrap();
+        assert_eq!(insert_dependency(&pool, c, a).await.unwrap_err(), "依赖关系不能形成循环");
+        assert_eq!(dependency_count(&pool).await, 2);
+    }
+
+    #[tokio::test]
+    async fn recurring_todo_cannot_block() {
+        let pool = pool().await;
+        let a = add_todo(&pool, "A", None).await;
+        let daily = add_todo(&pool, "每日站会", Some("FREQ=DAILY")).await;
+
+        assert_eq!(insert_dependency(&pool, a, daily).await.unwrap_err(), "循环任务不能作为前置任务");
+        // 循环任务本身可以等待其他待办
+        insert_dependency(&pool, daily, a).await.unwrap();
+        assert!(insert_dependency(&pool, a, 999).await.is_err());
+        assert_eq!(dependency_count(&pool).await, 1);
+    }
+
+    #[tokio::test]
+    async fn blocked_flag_and_unblocked_ids() {
+        let pool = pool().await;
+        let a = add_todo(&pool, "A", None).await;
+        let b = add_todo(&pool, "B", None).await;
+        let c = add_todo(&pool, "C", None).await;
+        let d = add_todo(&pool, "D", None).await;
+        let e = add_todo(&pool, "E", None).await;
+        insert_dependency(&pool, a, b).await.unwrap();
+        insert_dependency(&pool, a, c).await.unwrap();
+        insert_dependency(&pool, d, b).await.unwrap();
+        insert_dependency(&pool, e, c).await.unwrap();
+
+        let mut todos = sqlx::query_as::<_, Todo>(&format!("{} ORDER BY id", TODO_SELECT)).fetch_all(&pool).await.unwrap();
+        attach_blocked(&pool, &mut todos).await.unwrap();
+        let blocked = todos.iter().map(|t| (t.id, t.blocked)).collect::<Vec<_>>();
+        assert_eq!(blocked, [(a, true), (b, false), (c, false), (d, true), (e, true)]);
+
+        // A 还在等 C，只有 D 解除阻塞
+        assert_eq!(complete(&pool, b).await, [d]);
+        // 已完成的待办不算作解除阻塞
+        sqlx::query("UPDATE todos SET status = 1 WHERE id = ?").bind(e).execute(&pool).await.unwrap();
+        assert_eq!(complete(&pool, c).await, [a]);
+        assert!(complete(&pool, d).await.is_empty());
+    }
+}
@@ -0,0 +1,370 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+use tokio::time::{interval, Duration};
+use crate::history::{record_changes, ChangeSource, FieldChange};
+use crate::priority::Priority;
+use crate::todo::{emit_todos_changed, DEADLINE};
+
+const DEFAULT_PROMOTE_HOURS: i64 = 24;
+// 规则的检查间隔
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EscalationSettings {
+    #[serde(rename = "promoteEnabled")]
+    pub promote_enabled: bool, // 临近截止时升级为紧急
+    #[serde(rename = "promoteHours")]
+    pub promote_hours: i64, // 截止前多少小时升级
+    #[serde(rename = "overdueEnabled")]
+    pub overdue_enabled: bool, // 过了截止时间标记为逾期
+}
+
+// 规则的一次触发，逾期标记不修改优先级，新旧优先级相同
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TodoEscalation {
+    pub id: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    pub title: String,
+    pub rule: String, // promote, overdue
+    #[serde(rename = "endTime")]
+    pub end_time: i64,
+    #[serde(rename = "oldLevel")]
+    pub old_level: i64,
+    #[serde(rename = "newLevel")]
+    pub new_level: i64,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+#[derive(FromRow)]
+struct EscalationCandidate {
+    id: i64,
+    title: String,
+    level: i64,
+    end_time: i64,
+}
+
+async fn load_escalation_settings(pool: &SqlitePool) -> Result<EscalationSettings, String> {
+    let settings = sqlx::query_as::<_, EscalationSettings>(
+        "SELECT promote_enabled, promote_hours, overdue_enabled FROM escalation_settings WHERE id = 1"
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or(EscalationSettings {
+        promote_enabled: false,
+        promote_hours: DEFAULT_PROMOTE_HOURS,
+        overdue_enabled: false,
+    }))
+}
+
+#[tauri::command]
+pub async fn get_escalation_settings(pool: State<'_, SqlitePool>) -> Result<EscalationSettings, String> {
+    load_escalation_settings(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn save_escalation_settings(pool: State<'_, SqlitePool>, settings: EscalationSettings) -> Result<(), String> {
+    if settings.promote_hours <= 0 {
+        return Err("提前的小时数必须大于 0".to_string());
+    }
+    sqlx::query(
+        "INSERT OR REPLACE INTO escalation_settings (id, promote_enabled, promote_hours, overdue_enabled, updated_at)
+         VALUES (1, ?, ?, ?, datetime('now'))"
+    )
+    .bind(settings.promote_enabled)
+    .bind(settings.promote_hours)
+    .bind(settings.overdue_enabled)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 待办的规则触发记录，按时间先后排列
+#[tauri::command]
+pub async fn get_todo_escalations(pool: State<'_, SqlitePool>, todo_id: i64) -> Result<Vec<TodoEscalation>, String> {
+    sqlx::query_as::<_, TodoEscalation>(
+        "SELECT e.id, e.todo_id, t.title, e.rule, e.end_time, e.old_level, e.new_level, e.created_at
+         FROM todo_escalations e JOIN todos t ON t.id = e.todo_id
+         WHERE e.todo_id = ? ORDER BY e.created_at, e.id"
+    )
+    .bind(todo_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 未完成、未触发过该规则且截止时间早于 before 的待办；
+// 循环任务的截止时间随每次发生而变化，不参与升级
+async fn load_candidates(
+    conn: &mut SqliteConnection,
+    rule: &str,
+    before: i64,
+    levels: &[Priority],
+) -> Result<Vec<EscalationCandidate>, String> {
+    let levels = levels.iter().map(|p| p.level().to_string()).collect::<Vec<_>>().join(", ");
+    sqlx::query_as::<_, EscalationCandidate>(&format!(
+        "SELECT id, title, level, end_time FROM todos t
+         WHERE status = 0 AND rrule IS NULL AND end_time IS NOT NULL AND {} < ? AND level IN ({})
+           AND NOT EXISTS (SELECT 1 FROM todo_escalations e WHERE e.todo_id = t.id AND e.rule = ? AND e.end_time = t.end_time)
+         ORDER BY end_time, id",
+        DEADLINE, levels
+    ))
+    .bind(before)
+    .bind(rule)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+async fn record_escalation(
+    conn: &mut SqliteConnection,
+    candidate: EscalationCandidate,
+    rule: &str,
+    new_level: i64,
+    now: i64,
+) -> Result<TodoEscalation, String> {
+    let id = sqlx::query(
+        "INSERT INTO todo_escalations (todo_id, rule, end_time, old_level, new_level, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(candidate.id)
+    .bind(rule)
+    .bind(candidate.end_time)
+    .bind(candidate.level)
+    .bind(new_level)
+    .bind(now)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?
+    .last_insert_rowid();
+
+    Ok(TodoEscalation {
+        id,
+        todo_id: candidate.id,
+        title: candidate.title,
+        rule: rule.to_string(),
+        end_time: candidate.end_time,
+        old_level: candidate.level,
+        new_level,
+        created_at: now,
+    })
+}
+
+// 按已启用的规则检查一遍，返回本次触发的记录；先升级再标记逾期
+pub async fn evaluate_escalations(pool: &SqlitePool, now: i64) -> Result<Vec<TodoEscalation>, String> {
+    let settings = load_escalation_settings(pool).await?;
+    if !settings.promote_enabled && !settings.overdue_enabled {
+        return Ok(Vec::new());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut escalations = Vec::new();
+
+    if settings.promote_enabled {
+        let deadline = now + settings.promote_hours * 60 * 60;
+        let candidates = load_candidates(
+            &mut tx,
+            "promote",
+            deadline,
+            &[Priority::ImportantNotUrgent, Priority::NotImportantNotUrgent],
+        )
+        .await?;
+        for candidate in candidates {
+            let old_level = candidate.level;
+            let new_level = Priority::try_from(old_level)?.urgent().level();
+            sqlx::query("UPDATE todos SET level = ?, updated_at = datetime('now') WHERE id = ?")
+                .bind(new_level)
+                .bind(candidate.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            record_changes(
+                &mut tx,
+                candidate.id,
+                &[FieldChange::new("level", old_level, new_level)],
+                ChangeSource::Escalation,
+            )
+            .await?;
+            escalations.push(record_escalation(&mut tx, candidate, "promote", new_level, now).await?);
+        }
+    }
+
+    if settings.overdue_enabled {
+        let candidates = load_candidates(
+            &mut tx,
+            "overdue",
+            now,
+            &[
+                Priority::ImportantNotUrgent,
+                Priority::ImportantUrgent,
+                Priority::NotImportantNotUrgent,
+                Priority::NotImportantUrgent,
+            ],
+        )
+        .await?;
+        for candidate in candidates {
+            let level = candidate.level;
+            escalations.push(record_escalation(&mut tx, candidate, "overdue", level, now).await?);
+        }
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(escalations)
+}
+
+// 启动优先级自动升级，启动时先检查一次，之后每分钟检查一次
+pub fn start_escalation_checker(app_handle: AppHandle, pool: SqlitePool) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let escalations = match evaluate_escalations(&pool, Utc::now().timestamp()).await {
+                Ok(escalations) => escalations,
+                Err(e) => {
+                    eprintln!("Failed to evaluate escalations: {}", e);
+                    continue;
+                }
+            };
+            if escalations.is_empty() {
+                continue;
+            }
+
+            let mut ids = escalations.iter().map(|e| e.todo_id).collect::<Vec<_>>();
+            ids.sort_unstable();
+            ids.dedup();
+            if let Err(e) = app_handle.emit("todos-escalated", escalations) {
+                eprintln!("Failed to emit todos-escalated event: {}", e);
+            }
+            emit_todos_changed(&app_handle, "escalate", &ids);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60;
+
+    async fn pool(promote: bool, overdue: bool) -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        sqlx::query("INSERT INTO escalation_settings (id, promote_enabled, promote_hours, overdue_enabled) VALUES (1, ?, 24, ?)")
+            .bind(promote)
+            .bind(overdue)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn add_todo(pool: &SqlitePool, level: i64, end_time: i64, rrule: Option<&str>) -> i64 {
+        sqlx::query("INSERT INTO todos (title, start_time, end_time, level, rrule) VALUES ('t', ?, ?, ?, ?)")
+            .bind(end_time - HOUR)
+            .bind(end_time)
+            .bind(level)
+            .bind(rrule)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn level(pool: &SqlitePool, id: i64) -> i64 {
+        sqlx::query_scalar::<_, i64>("SELECT level FROM todos WHERE id = ?").bind(id).fetch_one(pool).await.unwrap()
+    }
+
+    fn summary(escalations: &[TodoEscalation]) -> Vec<(i64, &str, i64, i64)> {
+        escalations.iter().map(|e| (e.todo_id, e.rule.as_str(), e.old_level, e.new_level)).collect()
+    }
+
+    #[tokio::test]
+    async fn promotes_within_window() {
+        let pool = pool(true, false).await;
+        let now = 1_000_000;
+        let important = add_todo(&pool, 0, now + 23 * HOUR, None).await;
+        let not_important = add_todo(&pool, 2, now + 24 * HOUR - 1, None).await;
+        let later = add_todo(&pool, 0, now + 25 * HOUR, None).await;
+        let urgent = add_todo(&pool, 1, now + HOUR, None).await;
+        let recurring = add_todo(&pool, 0, now + HOUR, Some("FREQ=DAILY")).await;
+
+        let escalations = evaluate_escalations(&pool, now).await.unwrap();
+        assert_eq!(summary(&escalations), [(important, "promote", 0, 1), (not_important, "promote", 2, 3)]);
+        assert_eq!(level(&pool, important).await, 1);
+        assert_eq!(level(&pool, not_important).await, 3);
+        for id in [later, urgent, recurring] {
+            assert!(escalations.iter().all(|e| e.todo_id != id));
+        }
+        assert_eq!(level(&pool, later).await, 0);
+
+        let history = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT old_value, new_value, source FROM todo_history WHERE todo_id = ? AND field = 'level'"
+        )
+        .bind(important)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(history, [("0".to_string(), "1".to_string(), "escalation".to_string())]);
+
+        // 进入窗口后才升级
+        let escalations = evaluate_escalations(&pool, now + 2 * HOUR).await.unwrap();
+        assert_eq!(summary(&escalations), [(later, "promote", 0, 1)]);
+    }
+
+    #[tokio::test]
+    async fn promotes_once_per_deadline() {
+        let pool = pool(true, false).await;
+        let now = 1_000_000;
+        let id = add_todo(&pool, 0, now + HOUR, None).await;
+        assert_eq!(evaluate_escalations(&pool, now).await.unwrap().len(), 1);
+
+        // 用户改回不紧急后，同一截止时间不再升级
+        sqlx::query("UPDATE todos SET level = 0 WHERE id = ?").bind(id).execute(&pool).await.unwrap();
+        assert!(evaluate_escalations(&pool, now + 60).await.unwrap().is_empty());
+        assert_eq!(level(&pool, id).await, 0);
+
+        // 截止时间改变后重新计算
+        sqlx::query("UPDATE todos SET end_time = ? WHERE id = ?").bind(now + 2 * HOUR).bind(id).execute(&pool).await.unwrap();
+        assert_eq!(summary(&evaluate_escalations(&pool, now + 120).await.unwrap()), [(id, "promote", 0, 1)]);
+        assert_eq!(sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM todo_escalations").fetch_one(&pool).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn flags_overdue_without_changing_level() {
+        let pool = pool(false, true).await;
+        let now = crate::todo::local_day_start(1_000_000, 0) + 12 * HOUR;
+        let overdue = add_todo(&pool, 2, now - 1, None).await;
+        let pending = add_todo(&pool, 0, now + 1, None).await;
+        // 全天待办到截止日期当天结束才逾期
+        let all_day = add_todo(&pool, 3, crate::todo::local_day_start(now, 0), None).await;
+        sqlx::query("UPDATE todos SET all_day = 1 WHERE id = ?").bind(all_day).execute(&pool).await.unwrap();
+        sqlx::query("UPDATE todos SET status = 1 WHERE id = ?")
+            .bind(add_todo(&pool, 0, now - HOUR, None).await)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let escalations = evaluate_escalations(&pool, now).await.unwrap();
+        assert_eq!(summary(&escalations), [(overdue, "overdue", 2, 2)]);
+        assert_eq!(level(&pool, overdue).await, 2);
+        assert!(evaluate_escalations(&pool, now).await.unwrap().is_empty());
+
+        let tomorrow = crate::todo::local_day_start(now, 1);
+        let escalations = evaluate_escalations(&pool, tomorrow + 1).await.unwrap();
+        assert_eq!(summary(&escalations), [(all_day, "overdue", 3, 3), (pending, "overdue", 0, 0)]);
+    }
+
+    #[tokio::test]
+    async fn disabled_rules_do_nothing() {
+        let pool = pool(false, false).await;
+        let id = add_todo(&pool, 0, 1_000, None).await;
+        assert!(evaluate_escalations(&pool, 1_000_000).await.unwrap().is_empty());
+        assert_eq!(level(&pool, id).await, 0);
+    }
+}
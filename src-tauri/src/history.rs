@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::State;
+use crate::todo::Todo;
+
+const DEFAULT_REPORT_LIMIT: i64 = 10;
+
+// 修改的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ChangeSource {
+    #[default]
+    #[serde(rename = "ui")]
+    Ui,
+    #[serde(rename = "shortcut")]
+    Shortcut,
+    #[serde(rename = "ai")]
+    Ai,
+    #[serde(rename = "import")]
+    Import,
+    #[serde(rename = "escalation")]
+    Escalation,
+}
+
+impl ChangeSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChangeSource::Ui => "ui",
+            ChangeSource::Shortcut => "shortcut",
+            ChangeSource::Ai => "ai",
+            ChangeSource::Import => "import",
+            ChangeSource::Escalation => "escalation",
+        }
+    }
+}
+
+// 一个字段的修改，字段名与前端使用的名称一致
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldChange {
+    field: &'static str,
+    old_value: Value,
+    new_value: Value,
+}
+
+impl FieldChange {
+    pub(crate) fn new(field: &'static str, old_value: impl Into<Value>, new_value: impl Into<Value>) -> Self {
+        FieldChange { field, old_value: old_value.into(), new_value: new_value.into() }
+    }
+}
+
+#[derive(FromRow)]
+struct TodoHistoryRow {
+    id: i64,
+    todo_id: i64,
+    field: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    source: String,
+    changed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoHistoryEntry {
+    pub id: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    pub field: String,
+    #[serde(rename = "oldValue")]
+    pub old_value: Value,
+    #[serde(rename = "newValue")]
+    pub new_value: Value,
+    pub source: String,
+    #[serde(rename = "changedAt")]
+    pub changed_at: i64, // 修改时间戳（秒）
+}
+
+// 被推迟次数最多的待办
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PostponedTodo {
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    pub title: String,
+    #[serde(rename = "deadlinePostpones")]
+    pub deadline_postpones: i64, // 截止时间被推后的次数
+    #[serde(rename = "startPostpones")]
+    pub start_postpones: i64, // 开始时间被推后的次数
+    #[serde(rename = "deadlineDelay")]
+    pub deadline_delay: i64, // 截止时间累计推后的秒数
+    #[serde(rename = "lastPostponedAt")]
+    pub last_postponed_at: i64,
+}
+
+// 比较修改前后的待办，列出发生变化的字段
+pub(crate) fn diff_todos(old: &Todo, new: &Todo) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    if old.title != new.title {
+        changes.push(FieldChange::new("title", old.title.clone(), new.title.clone()));
+    }
+    if old.start_time != new.start_time {
+        changes.push(FieldChange::new("startTime", old.start_time, new.start_time));
+    }
+    if old.end_time != new.end_time {
+        changes.push(FieldChange::new("endTime", old.end_time, new.end_time));
+    }
+    if old.notes != new.notes {
+        changes.push(FieldChange::new("notes", old.notes.clone(), new.notes.clone()));
+    }
+    if old.level != new.level {
+        changes.push(FieldChange::new("level", old.level, new.level));
+    }
+    if old.rrule != new.rrule {
+        changes.push(FieldChange::new("rrule", old.rrule.clone(), new.rrule.clone()));
+    }
+    if old.project_id != new.project_id {
+        changes.push(FieldChange::new("projectId", old.project_id, new.project_id));
+    }
+    if old.auto_complete != new.auto_complete {
+        changes.push(FieldChange::new("autoComplete", old.auto_complete, new.auto_complete));
+    }
+    if old.all_day != new.all_day {
+        changes.push(FieldChange::new("allDay", old.all_day, new.all_day));
+    }
+    if old.status != new.status {
+        changes.push(FieldChange::new("status", old.status, new.status));
+    }
+    changes
+}
+
+// 循环任务记录当前这一次的结果并推进到下一次：开始和截止时间随之移动但不是推迟，
+// 改为记录当前这一次计划时间的推进，系列结束时还有状态的变化
+pub(crate) fn diff_advanced_series(old: &Todo, new: &Todo) -> Vec<FieldChange> {
+    let mut changes = diff_todos(old, new);
+    changes.retain(|c| !matches!(c.field, "startTime" | "endTime"));
+    let old_occurrence = old.occurrence_start.or(old.start_time);
+    if old_occurrence != new.occurrence_start {
+        changes.push(FieldChange::new("occurrenceStart", old_occurrence, new.occurrence_start));
+    }
+    changes
+}
+
+// 标签按 id 排序后比较，顺序不同不算修改
+pub(crate) async fn tag_change(conn: &mut SqliteConnection, todo_id: i64, tag_ids: &[i64]) -> Result<Option<FieldChange>, String> {
+    let old_ids = sqlx::query_scalar::<_, i64>("SELECT tag_id FROM todo_tags WHERE todo_id = ? ORDER BY tag_id")
+        .bind(todo_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut new_ids = tag_ids.to_vec();
+    new_ids.sort_unstable();
+    new_ids.dedup();
+    if old_ids == new_ids {
+        return Ok(None);
+    }
+    Ok(Some(FieldChange::new("tagIds", old_ids, new_ids)))
+}
+
+fn encode(value: &Value) -> Option<String> {
+    if value.is_null() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn decode(value: Option<String>) -> Value {
+    value.and_then(|v| serde_json::from_str(&v).ok()).unwrap_or(Value::Null)
+}
+
+pub(crate) async fn record_changes(
+    conn: &mut SqliteConnection,
+    todo_id: i64,
+    changes: &[FieldChange],
+    source: ChangeSource,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    for change in changes {
+        sqlx::query("INSERT INTO todo_history (todo_id, field, old_value, new_value, source, changed_at) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(todo_id)
+            .bind(change.field)
+            .bind(encode(&change.old_value))
+            .bind(encode(&change.new_value))
+            .bind(source.as_str())
+            .bind(now)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// 待办的修改时间线，按时间先后排列
+#[tauri::command]
+pub async fn get_todo_timeline(pool: State<'_, SqlitePool>, todo_id: i64) -> Result<Vec<TodoHistoryEntry>, String> {
+    let rows = sqlx::query_as::<_, TodoHistoryRow>(
+        "SELECT id, todo_id, field, old_value, new_value, source, changed_at FROM todo_history WHERE todo_id = ? ORDER BY changed_at, id"
+    )
+    .bind(todo_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TodoHistoryEntry {
+            id: row.id,
+            todo_id: row.todo_id,
+            field: row.field,
+            old_value: decode(row.old_value),
+            new_value: decode(row.new_value),
+            source: row.source,
+            changed_at: row.changed_at,
+        })
+        .collect())
+}
+
+// 开始或截止时间被推后次数最多的待办（不含回收站中的待办）
+#[tauri::command]
+pub async fn get_most_postponed_todos(pool: State<'_, SqlitePool>, limit: Option<i64>) -> Result<Vec<PostponedTodo>, String> {
+    sqlx::query_as::<_, PostponedTodo>(
+        "SELECT h.todo_id, t.title,
+                COALESCE(SUM(h.field = 'endTime'), 0) AS deadline_postpones,
+                COALESCE(SUM(h.field = 'startTime'), 0) AS start_postpones,
+                COALESCE(SUM(CASE WHEN h.field = 'endTime' THEN CAST(h.new_value AS INTEGER) - CAST(h.old_value AS INTEGER) END), 0) AS deadline_delay,
+                MAX(h.changed_at) AS last_postponed_at
+         FROM todo_history h JOIN todos t ON t.id = h.todo_id
+         WHERE h.field IN ('startTime', 'endTime') AND t.status != 2
+           AND h.old_value IS NOT NULL AND h.new_value IS NOT NULL
+           AND CAST(h.new_value AS INTEGER) > CAST(h.old_value AS INTEGER)
+         GROUP BY h.todo_id
+         ORDER BY deadline_postpones + start_postpones DESC, deadline_delay DESC, h.todo_id
+         LIMIT ?",
+    )
+    .bind(limit.unwrap_or(DEFAULT_REPORT_LIMIT))
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row, SqlitePool};
 use tauri::State;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Holiday {
@@ -66,6 +67,78 @@ pub struct SyncHolidayParams {
     pub year: i32,
 }
 
+/// 工作日日历：已同步的节假日（放假 / 调休上班）优先，其余按单双休判断
+#[derive(Debug, Clone)]
+pub struct WorkdayCalendar {
+    work_days: String, // "single" 单休, "double" 双休
+    overrides: HashMap<NaiveDate, bool>, // 日期 -> 是否放假
+}
+
+impl Default for WorkdayCalendar {
+    fn default() -> Self {
+        WorkdayCalendar::new("double")
+    }
+}
+
+impl WorkdayCalendar {
+    pub fn new(work_days: &str) -> Self {
+        WorkdayCalendar {
+            work_days: work_days.to_string(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, date: NaiveDate, is_off_day: bool) {
+        self.overrides.insert(date, is_off_day);
+    }
+
+    pub fn is_workday(&self, date: NaiveDate) -> bool {
+        if let Some(is_off_day) = self.overrides.get(&date) {
+            return !is_off_day;
+        }
+        match date.weekday() {
+            Weekday::Sun => false,
+            Weekday::Sat => self.work_days == "single",
+            _ => true,
+        }
+    }
+
+    // 当天或之前最近的工作日
+    pub fn previous_workday(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (0..366)
+            .map(|i| date - Duration::days(i))
+            .find(|d| self.is_workday(*d))
+    }
+
+    // 当天或之后最近的工作日
+    pub fn next_workday(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (0..366)
+            .map(|i| date + Duration::days(i))
+            .find(|d| self.is_workday(*d))
+    }
+}
+
+/// 根据倒计时配置中的单双休和已同步的节假日构建工作日日历
+pub async fn load_workday_calendar(pool: &SqlitePool) -> Result<WorkdayCalendar, String> {
+    let config = crate::config::load_countdown_config_from_db_internal(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut calendar = WorkdayCalendar::new(&config.work_days);
+    
+    let rows = sqlx::query_as::<_, HolidayRow>("SELECT name, date, is_off_day FROM holidays")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    for row in rows {
+        if let Ok(date) = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d") {
+            calendar.set(date, row.is_off_day);
+        }
+    }
+    
+    Ok(calendar)
+}
+
 /// 从GitHub获取节假日数据
 async fn fetch_holiday_data(year: i32, proxy_url: Option<String>) -> Result<HolidayYear, String> {
     let base_url = "https://raw.githubusercontent.com/NateScarlet/holiday-cn/master".to_string();
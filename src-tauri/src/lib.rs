@@ -4,12 +4,32 @@ use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::{TrayIconBuilder, TrayIconEvent};
 use tauri::{Manager, PhysicalPosition};
 // 导入自定义模块
+pub mod ai;
+pub mod bulk;
 pub mod config;
 pub mod countdown;
 pub mod database;
+pub mod dependency;
+pub mod escalation;
+pub mod history;
 pub mod holiday;
 pub mod motivation;
+pub mod pomodoro;
+pub mod priority;
+pub mod project;
+pub mod query;
+pub mod quick_add;
+pub mod recurrence;
+pub mod reminder;
+pub mod search;
+pub mod smart_list;
+pub mod statistics;
+pub mod subtask;
+pub mod tag;
+pub mod time_entry;
 pub mod todo;
+pub mod trash;
+pub mod undo;
 pub mod window_commands;
 
 pub use database::get_migrations;
@@ -166,31 +186,52 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             let handle = app.handle().clone();
             let rt = tokio::runtime::Runtime::new().unwrap();
             let pool = rt.block_on(async {
                 let pool = db_pool(&handle).await.expect("Failed to create database pool.");
-                let migrations = get_migrations();
-                for migration in migrations {
-                    sqlx::query(&migration.sql)
-                        .execute(&pool)
-                        .await
-                        .expect(&format!("Failed to execute migration: {}", migration.description));
-                }
+                database::run_migrations(&pool)
+                    .await
+                    .expect("Failed to execute migrations.");
                 pool
             });
 
             app.manage(pool);
+            app.manage(pomodoro::PomodoroState::default());
+            app.manage(countdown::CountdownTimerState::default());
 
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 let pool_state: tauri::State<SqlitePool> = app_handle.state();
-                if let Err(e) = countdown::start_countdown_timer(app_handle.clone(), pool_state).await {
+                let timer_state: tauri::State<countdown::CountdownTimerState> = app_handle.state();
+                if let Err(e) = countdown::start_countdown_timer(app_handle.clone(), pool_state, timer_state).await {
                     eprintln!("Failed to start countdown timer: {}", e);
                 }
             });
 
+            // 启动待办提醒调度
+            let app_handle_reminder = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let pool_state: tauri::State<SqlitePool> = app_handle_reminder.state();
+                reminder::start_reminder_scheduler(app_handle_reminder.clone(), pool_state.inner().clone());
+            });
+
+            // 启动回收站自动清理
+            let app_handle_trash = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let pool_state: tauri::State<SqlitePool> = app_handle_trash.state();
+                trash::start_trash_purger(pool_state.inner().clone());
+            });
+
+            // 启动临近截止的优先级自动升级
+            let app_handle_escalation = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let pool_state: tauri::State<SqlitePool> = app_handle_escalation.state();
+                escalation::start_escalation_checker(app_handle_escalation.clone(), pool_state.inner().clone());
+            });
+
             // 加载并应用窗口设置
             let app_handle_settings = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -276,7 +317,20 @@ pub fn run() {
             window_commands::set_window_monitor,
             countdown::update_countdown_config,
             countdown::start_countdown_timer,
+            pomodoro::get_pomodoro_settings,
+            pomodoro::save_pomodoro_settings,
+            pomodoro::get_pomodoro_status,
+            pomodoro::start_pomodoro,
+            pomodoro::pause_pomodoro,
+            pomodoro::resume_pomodoro,
+            pomodoro::skip_pomodoro_phase,
+            pomodoro::stop_pomodoro,
+            pomodoro::set_pomodoro_todo,
+            priority::get_eisenhower_matrix,
+            priority::move_todo_to_quadrant,
             countdown::reset_work_end_countdown_to_next_day,
+            countdown::snooze_countdown_alert,
+            countdown::acknowledge_countdown_alert,
             config::load_countdown_config_from_db,
             config::save_countdown_config_to_db,
             database::save_countdown_record,
@@ -288,9 +342,77 @@ pub fn run() {
             todo::add_todo,
             todo::get_all_todos,
             todo::get_recent_todos,
+            todo::get_inbox_todos,
+            todo::schedule_todo,
             todo::get_todo_by_id,
             todo::update_todo,
             todo::delete_todo,
+            bulk::bulk_complete_todos,
+            bulk::bulk_uncomplete_todos,
+            bulk::bulk_delete_todos,
+            bulk::bulk_restore_todos,
+            bulk::bulk_reschedule_todos,
+            bulk::bulk_set_todo_level,
+            undo::undo_last_todo_change,
+            undo::redo_todo_change,
+            history::get_todo_timeline,
+            history::get_most_postponed_todos,
+            dependency::add_todo_dependency,
+            dependency::remove_todo_dependency,
+            dependency::get_todo_dependencies,
+            time_entry::start_time_tracking,
+            time_entry::stop_time_tracking,
+            time_entry::get_running_time_entry,
+            time_entry::get_time_entries,
+            time_entry::add_time_entry,
+            time_entry::update_time_entry,
+            time_entry::delete_time_entry,
+            time_entry::get_time_report,
+            time_entry::export_time_report_csv,
+            statistics::get_todo_statistics,
+            trash::list_deleted_todos,
+            trash::restore_todo,
+            trash::purge_todo,
+            trash::get_trash_settings,
+            trash::save_trash_settings,
+            escalation::get_escalation_settings,
+            escalation::save_escalation_settings,
+            escalation::get_todo_escalations,
+            quick_add::parse_quick_add,
+            ai::ai_extract_todo,
+            todo::complete_todo_occurrence,
+            todo::skip_todo_occurrence,
+            todo::postpone_todo_occurrence,
+            todo::get_todo_occurrences,
+            subtask::reorder_subtasks,
+            search::search_todos,
+            query::query_todos,
+            smart_list::get_smart_lists,
+            smart_list::get_smart_list_counts,
+            smart_list::add_smart_list,
+            smart_list::update_smart_list,
+            smart_list::delete_smart_list,
+            smart_list::reorder_smart_lists,
+            smart_list::evaluate_smart_list,
+            reminder::add_todo_reminder,
+            reminder::get_todo_reminders,
+            reminder::delete_todo_reminder,
+            reminder::snooze_todo_reminder,
+            reminder::acknowledge_todo_reminder,
+            tag::get_all_tags,
+            tag::add_tag,
+            tag::update_tag,
+            tag::delete_tag,
+            tag::reorder_tags,
+            tag::set_todo_tags,
+            project::get_all_projects,
+            project::add_project,
+            project::update_project,
+            project::delete_project,
+            project::reorder_projects,
+            project::archive_project,
+            project::move_todos_to_project,
+            project::get_project_stats,
             config::load_todo_color_settings,
             config::save_todo_color_settings,
             config::apply_todo_colors_to_main_window,
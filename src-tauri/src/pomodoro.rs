@@ -0,0 +1,496 @@
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_notification::NotificationExt;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PomodoroSettings {
+    #[serde(rename = "focusMinutes")]
+    pub focus_minutes: i64,
+    #[serde(rename = "shortBreakMinutes")]
+    pub short_break_minutes: i64,
+    #[serde(rename = "longBreakMinutes")]
+    pub long_break_minutes: i64,
+    pub rounds: i64, // 每完成几个专注进行一次长休息
+    #[serde(rename = "autoStart")]
+    pub auto_start: bool, // 阶段结束后自动开始下一阶段，否则暂停等待继续
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        PomodoroSettings {
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            rounds: 4,
+            auto_start: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PomodoroPhase {
+    #[serde(rename = "focus")]
+    Focus,
+    #[serde(rename = "shortBreak")]
+    ShortBreak,
+    #[serde(rename = "longBreak")]
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    // 保存到 countdown_records 的 mode
+    fn mode(self) -> &'static str {
+        match self {
+            PomodoroPhase::Focus => "pomodoroFocus",
+            PomodoroPhase::ShortBreak => "pomodoroShortBreak",
+            PomodoroPhase::LongBreak => "pomodoroLongBreak",
+        }
+    }
+}
+
+// pomodoro-update 事件的内容，计时循环每秒发送一次
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    pub round: i64, // 当前是第几个专注
+    pub rounds: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: Option<i64>,
+    pub duration: i64, // 本阶段计划时长（秒）
+    pub remaining: i64, // 剩余秒数
+    pub status: String, // "running", "paused"
+}
+
+// pomodoro-phase 事件的内容，阶段结束或被跳过时发送
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroPhaseEvent {
+    pub finished: PomodoroPhase,
+    pub result: String, // "finished", "skipped"
+    #[serde(rename = "todoId")]
+    pub todo_id: Option<i64>,
+    pub next: PomodoroStatus,
+}
+
+// 一个结束的阶段，保存到 countdown_records
+struct PomodoroRecord {
+    phase: PomodoroPhase,
+    round: i64,
+    todo_id: Option<i64>,
+    planned: i64,
+    duration: i64, // 实际进行的秒数，不含暂停
+    status: &'static str, // "finished", "skipped", "stopped"
+    started_at: i64,
+    finished_at: i64,
+}
+
+struct PomodoroSession {
+    settings: PomodoroSettings,
+    phase: PomodoroPhase,
+    round: i64,
+    todo_id: Option<i64>,
+    started_at: i64, // 本阶段开始的时间戳（秒）
+    planned: i64,
+    elapsed: i64, // 暂停前累计进行的秒数
+    active_since: Option<i64>, // 为空表示已暂停
+}
+
+impl PomodoroSession {
+    fn new(settings: PomodoroSettings, todo_id: Option<i64>, now: i64) -> Self {
+        let planned = settings.focus_minutes * 60;
+        PomodoroSession {
+            settings,
+            phase: PomodoroPhase::Focus,
+            round: 1,
+            todo_id,
+            started_at: now,
+            planned,
+            elapsed: 0,
+            active_since: Some(now),
+        }
+    }
+
+    fn elapsed_at(&self, now: i64) -> i64 {
+        let active = self.active_since.map_or(0, |since| (now - since).max(0));
+        (self.elapsed + active).min(self.planned)
+    }
+
+    fn status_at(&self, now: i64) -> PomodoroStatus {
+        PomodoroStatus {
+            phase: self.phase,
+            round: self.round,
+            rounds: self.settings.rounds,
+            todo_id: self.todo_id,
+            duration: self.planned,
+            remaining: self.planned - self.elapsed_at(now),
+            status: if self.active_since.is_some() { "running" } else { "paused" }.to_string(),
+        }
+    }
+
+    fn record(&self, now: i64, status: &'static str) -> PomodoroRecord {
+        PomodoroRecord {
+            phase: self.phase,
+            round: self.round,
+            todo_id: self.todo_id,
+            planned: self.planned,
+            duration: self.elapsed_at(now),
+            status,
+            started_at: self.started_at,
+            finished_at: now,
+        }
+    }
+
+    fn pause(&mut self, now: i64) {
+        if self.active_since.is_some() {
+            self.elapsed = self.elapsed_at(now);
+            self.active_since = None;
+        }
+    }
+
+    fn resume(&mut self, now: i64) {
+        if self.active_since.is_none() {
+            self.active_since = Some(now);
+        }
+    }
+
+    // 结束当前阶段并进入下一阶段：专注之后短休息，每 rounds 个专注之后长休息
+    fn advance(&mut self, now: i64, status: &'static str) -> PomodoroRecord {
+        let record = self.record(now, status);
+        let (phase, minutes) = match self.phase {
+            PomodoroPhase::Focus if self.round >= self.settings.rounds => (PomodoroPhase::LongBreak, self.settings.long_break_minutes),
+            PomodoroPhase::Focus => (PomodoroPhase::ShortBreak, self.settings.short_break_minutes),
+            PomodoroPhase::ShortBreak => {
+                self.round += 1;
+                (PomodoroPhase::Focus, self.settings.focus_minutes)
+            }
+            PomodoroPhase::LongBreak => {
+                self.round = 1;
+                (PomodoroPhase::Focus, self.settings.focus_minutes)
+            }
+        };
+        self.phase = phase;
+        self.planned = minutes * 60;
+        self.started_at = now;
+        self.elapsed = 0;
+        self.active_since = self.settings.auto_start.then_some(now);
+        record
+    }
+}
+
+// 番茄钟的运行状态，由 Rust 的计时循环推进，窗口隐藏时也会继续
+#[derive(Default)]
+pub struct PomodoroState(Mutex<Option<PomodoroSession>>);
+
+impl PomodoroState {
+    fn with_session<T>(&self, f: impl FnOnce(&mut Option<PomodoroSession>) -> T) -> Result<T, String> {
+        let mut session = self.0.lock().map_err(|e| e.to_string())?;
+        Ok(f(&mut session))
+    }
+
+    pub fn status(&self, now: i64) -> Result<Option<PomodoroStatus>, String> {
+        self.with_session(|session| session.as_ref().map(|s| s.status_at(now)))
+    }
+}
+
+async fn load_pomodoro_settings(pool: &SqlitePool) -> Result<PomodoroSettings, String> {
+    let settings = sqlx::query_as::<_, PomodoroSettings>(
+        "SELECT focus_minutes, short_break_minutes, long_break_minutes, rounds, auto_start FROM pomodoro_settings WHERE id = 1"
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or_default())
+}
+
+async fn save_record(pool: &SqlitePool, record: PomodoroRecord) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO countdown_records (mode, duration, status, created_at, finished_at, todo_id, round, planned_duration)
+         VALUES (?, ?, ?, datetime(?, 'unixepoch'), datetime(?, 'unixepoch'), ?, ?, ?)"
+    )
+    .bind(record.phase.mode())
+    .bind(record.duration)
+    .bind(record.status)
+    .bind(record.started_at)
+    .bind(record.finished_at)
+    .bind(record.todo_id)
+    .bind(record.round)
+    .bind(record.planned)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn ensure_todo(pool: &SqlitePool, todo_id: Option<i64>) -> Result<(), String> {
+    let Some(todo_id) = todo_id else {
+        return Ok(());
+    };
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM todos WHERE id = ? AND status != 2)")
+        .bind(todo_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err("Todo not found".to_string());
+    }
+    Ok(())
+}
+
+// 推进番茄钟，当前阶段到时间则记录并进入下一阶段
+pub async fn tick_pomodoro(pool: &SqlitePool, state: &PomodoroState, now: i64) -> Result<Option<PomodoroPhaseEvent>, String> {
+    let finished = state.with_session(|session| {
+        let session = session.as_mut()?;
+        if session.active_since.is_none() || session.elapsed_at(now) < session.planned {
+            return None;
+        }
+        let record = session.advance(now, "finished");
+        Some((record, session.status_at(now)))
+    })?;
+
+    let Some((record, next)) = finished else {
+        return Ok(None);
+    };
+    let event = PomodoroPhaseEvent { finished: record.phase, result: record.status.to_string(), todo_id: record.todo_id, next };
+    save_record(pool, record).await?;
+    Ok(Some(event))
+}
+
+pub fn fire_pomodoro_phase(app_handle: &AppHandle, event: PomodoroPhaseEvent) {
+    let (title, body) = match event.finished {
+        PomodoroPhase::Focus => ("专注结束", "休息一下吧"),
+        _ => ("休息结束", "开始下一个专注"),
+    };
+    if let Err(e) = app_handle.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show pomodoro notification: {}", e);
+    }
+
+    if let Err(e) = app_handle.emit("pomodoro-phase", event) {
+        eprintln!("Failed to emit pomodoro-phase event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_settings(pool: State<'_, SqlitePool>) -> Result<PomodoroSettings, String> {
+    load_pomodoro_settings(pool.inner()).await
+}
+
+// 保存设置，从下一次开始番茄钟时生效
+#[tauri::command]
+pub async fn save_pomodoro_settings(pool: State<'_, SqlitePool>, settings: PomodoroSettings) -> Result<(), String> {
+    if [settings.focus_minutes, settings.short_break_minutes, settings.long_break_minutes, settings.rounds]
+        .iter()
+        .any(|v| *v <= 0)
+    {
+        return Err("番茄钟的时长和轮数必须大于 0".to_string());
+    }
+    sqlx::query(
+        "INSERT OR REPLACE INTO pomodoro_settings (id, focus_minutes, short_break_minutes, long_break_minutes, rounds, auto_start, updated_at)
+         VALUES (1, ?, ?, ?, ?, ?, datetime('now'))"
+    )
+    .bind(settings.focus_minutes)
+    .bind(settings.short_break_minutes)
+    .bind(settings.long_break_minutes)
+    .bind(settings.rounds)
+    .bind(settings.auto_start)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_status(pomodoro: State<'_, PomodoroState>) -> Result<Option<PomodoroStatus>, String> {
+    pomodoro.status(chrono::Utc::now().timestamp())
+}
+
+// 开始新的番茄钟，从第一个专注开始；正在进行的番茄钟记为中止
+#[tauri::command]
+pub async fn start_pomodoro(
+    pool: State<'_, SqlitePool>,
+    pomodoro: State<'_, PomodoroState>,
+    todo_id: Option<i64>,
+) -> Result<PomodoroStatus, String> {
+    ensure_todo(pool.inner(), todo_id).await?;
+    let settings = load_pomodoro_settings(pool.inner()).await?;
+    let now = chrono::Utc::now().timestamp();
+
+    let (previous, status) = pomodoro.with_session(|session| {
+        let previous = session.take().map(|s| s.record(now, "stopped"));
+        let started = session.insert(PomodoroSession::new(settings, todo_id, now));
+        (previous, started.status_at(now))
+    })?;
+    if let Some(record) = previous {
+        save_record(pool.inner(), record).await?;
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn pause_pomodoro(pomodoro: State<'_, PomodoroState>) -> Result<PomodoroStatus, String> {
+    let now = chrono::Utc::now().timestamp();
+    pomodoro.with_session(|session| {
+        let session = session.as_mut().ok_or("番茄钟未开始")?;
+        session.pause(now);
+        Ok(session.status_at(now))
+    })?
+}
+
+#[tauri::command]
+pub async fn resume_pomodoro(pomodoro: State<'_, PomodoroState>) -> Result<PomodoroStatus, String> {
+    let now = chrono::Utc::now().timestamp();
+    pomodoro.with_session(|session| {
+        let session = session.as_mut().ok_or("番茄钟未开始")?;
+        session.resume(now);
+        Ok(session.status_at(now))
+    })?
+}
+
+// 跳过当前阶段，按实际进行的时长记录
+#[tauri::command]
+pub async fn skip_pomodoro_phase(
+    app_handle: AppHandle,
+    pool: State<'_, SqlitePool>,
+    pomodoro: State<'_, PomodoroState>,
+) -> Result<PomodoroStatus, String> {
+    let now = chrono::Utc::now().timestamp();
+    let (record, next) = pomodoro.with_session(|session| {
+        let session = session.as_mut().ok_or("番茄钟未开始")?;
+        let record = session.advance(now, "skipped");
+        Ok::<_, String>((record, session.status_at(now)))
+    })??;
+
+    let event = PomodoroPhaseEvent { finished: record.phase, result: record.status.to_string(), todo_id: record.todo_id, next: next.clone() };
+    save_record(pool.inner(), record).await?;
+    if let Err(e) = app_handle.emit("pomodoro-phase", event) {
+        eprintln!("Failed to emit pomodoro-phase event: {}", e);
+    }
+    Ok(next)
+}
+
+// 结束番茄钟，当前阶段按实际进行的时长记为中止
+#[tauri::command]
+pub async fn stop_pomodoro(pool: State<'_, SqlitePool>, pomodoro: State<'_, PomodoroState>) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    let record = pomodoro.with_session(|session| session.take().map(|s| s.record(now, "stopped")))?;
+    if let Some(record) = record {
+        save_record(pool.inner(), record).await?;
+    }
+    Ok(())
+}
+
+// 修改关联的待办，当前及之后的专注都记到该待办
+#[tauri::command]
+pub async fn set_pomodoro_todo(
+    pool: State<'_, SqlitePool>,
+    pomodoro: State<'_, PomodoroState>,
+    todo_id: Option<i64>,
+) -> Result<PomodoroStatus, String> {
+    ensure_todo(pool.inner(), todo_id).await?;
+    let now = chrono::Utc::now().timestamp();
+    pomodoro.with_session(|session| {
+        let session = session.as_mut().ok_or("番茄钟未开始")?;
+        session.todo_id = todo_id;
+        Ok(session.status_at(now))
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(rounds: i64, auto_start: bool) -> PomodoroSettings {
+        PomodoroSettings { rounds, auto_start, ..PomodoroSettings::default() }
+    }
+
+    #[test]
+    fn long_break_after_rounds() {
+        let mut session = PomodoroSession::new(settings(2, true), None, 0);
+        let mut phases = Vec::new();
+        let mut now = 0;
+        for _ in 0..5 {
+            now += session.planned;
+            let record = session.advance(now, "finished");
+            assert_eq!(record.duration, record.planned);
+            phases.push((session.phase, session.round, session.planned));
+        }
+        assert_eq!(
+            phases,
+            [
+                (PomodoroPhase::ShortBreak, 1, 5 * 60),
+                (PomodoroPhase::Focus, 2, 25 * 60),
+                (PomodoroPhase::LongBreak, 2, 15 * 60),
+                (PomodoroPhase::Focus, 1, 25 * 60),
+                (PomodoroPhase::ShortBreak, 1, 5 * 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn pause_keeps_elapsed_time() {
+        let mut session = PomodoroSession::new(settings(4, true), None, 0);
+        session.pause(100);
+        session.pause(200);
+        let status = session.status_at(1_000);
+        assert_eq!((status.remaining, status.status.as_str()), (1_400, "paused"));
+
+        session.resume(1_000);
+        session.resume(1_050);
+        let status = session.status_at(1_100);
+        assert_eq!((status.remaining, status.status.as_str()), (1_300, "running"));
+    }
+
+    #[test]
+    fn skip_records_actual_duration() {
+        let mut session = PomodoroSession::new(settings(4, false), Some(7), 0);
+        session.pause(300);
+        session.resume(900);
+        let record = session.advance(1_000, "skipped");
+        assert_eq!(
+            (record.phase, record.status, record.duration, record.planned, record.started_at, record.finished_at, record.todo_id),
+            (PomodoroPhase::Focus, "skipped", 400, 25 * 60, 0, 1_000, Some(7))
+        );
+        // 不自动开始时下一阶段处于暂停
+        let status = session.status_at(2_000);
+        assert_eq!((status.phase, status.remaining, status.status.as_str()), (PomodoroPhase::ShortBreak, 5 * 60, "paused"));
+    }
+
+    #[tokio::test]
+    async fn tick_records_finished_phase() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        let todo_id = sqlx::query("INSERT INTO todos (title, start_time) VALUES ('写周报', 0)")
+            .execute(&pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        let state = PomodoroState(Mutex::new(Some(PomodoroSession::new(settings(4, false), Some(todo_id), 0))));
+
+        assert!(tick_pomodoro(&pool, &state, 25 * 60 - 1).await.unwrap().is_none());
+        let event = tick_pomodoro(&pool, &state, 25 * 60 + 3).await.unwrap().unwrap();
+        assert_eq!((event.finished, event.result.as_str(), event.todo_id), (PomodoroPhase::Focus, "finished", Some(todo_id)));
+        assert_eq!((event.next.phase, event.next.status.as_str()), (PomodoroPhase::ShortBreak, "paused"));
+        // 暂停中的下一阶段不会被推进
+        assert!(tick_pomodoro(&pool, &state, 10_000).await.unwrap().is_none());
+
+        let row = sqlx::query_as::<_, (String, i64, String, String, String, Option<i64>, i64, i64)>(
+            "SELECT mode, duration, status, created_at, finished_at, todo_id, round, planned_duration FROM countdown_records"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            row,
+            (
+                "pomodoroFocus".to_string(),
+                25 * 60,
+                "finished".to_string(),
+                "1970-01-01 00:00:00".to_string(),
+                "1970-01-01 00:25:03".to_string(),
+                Some(todo_id),
+                1,
+                25 * 60
+            )
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, State};
+use crate::dependency::attach_blocked;
+use crate::query::DateWindow;
+use crate::subtask::attach_subtasks;
+use crate::tag::attach_tags;
+use crate::todo::{process_expired_todos, update_todo, Todo, UpdateTodoParams, DEADLINE, START_LATEST, TODO_SELECT};
+
+// 按四象限排序：重要且紧急、重要不紧急、不重要但紧急、不重要不紧急
+pub(crate) const LEVEL_ORDER: &str = "CASE level WHEN 1 THEN 1 WHEN 0 THEN 2 WHEN 3 THEN 3 WHEN 2 THEN 4 ELSE 5 END";
+
+// 待办的优先级，即四象限；与前端和数据库之间仍以 level 数字传递
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "i64", into = "i64")]
+pub enum Priority {
+    ImportantNotUrgent = 0,
+    ImportantUrgent = 1,
+    NotImportantNotUrgent = 2,
+    NotImportantUrgent = 3,
+}
+
+impl Priority {
+    pub fn level(self) -> i64 {
+        self as i64
+    }
+
+    // 与 LEVEL_ORDER 一致的排序序号
+    pub fn rank(self) -> i64 {
+        match self {
+            Priority::ImportantUrgent => 1,
+            Priority::ImportantNotUrgent => 2,
+            Priority::NotImportantUrgent => 3,
+            Priority::NotImportantNotUrgent => 4,
+        }
+    }
+
+    // 同一重要程度下的紧急优先级
+    pub fn urgent(self) -> Priority {
+        match self {
+            Priority::ImportantNotUrgent => Priority::ImportantUrgent,
+            Priority::NotImportantNotUrgent => Priority::NotImportantUrgent,
+            urgent => urgent,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Priority::ImportantNotUrgent => "重要不紧急",
+            Priority::ImportantUrgent => "重要且紧急",
+            Priority::NotImportantNotUrgent => "不重要不紧急",
+            Priority::NotImportantUrgent => "不重要但紧急",
+        }
+    }
+}
+
+impl TryFrom<i64> for Priority {
+    type Error = String;
+
+    fn try_from(level: i64) -> Result<Self, Self::Error> {
+        match level {
+            0 => Ok(Priority::ImportantNotUrgent),
+            1 => Ok(Priority::ImportantUrgent),
+            2 => Ok(Priority::NotImportantNotUrgent),
+            3 => Ok(Priority::NotImportantUrgent),
+            _ => Err(format!("无效的优先级: {}", level)),
+        }
+    }
+}
+
+impl From<Priority> for i64 {
+    fn from(priority: Priority) -> Self {
+        priority.level()
+    }
+}
+
+// 数据库中的 level 的排序序号，无效的排在最后
+pub(crate) fn level_rank(level: i64) -> i64 {
+    Priority::try_from(level).map_or(5, Priority::rank)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixTodo {
+    #[serde(flatten)]
+    pub todo: Todo,
+    pub overdue: bool, // 已过截止时间仍未完成
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Quadrant {
+    pub level: Priority,
+    pub label: String,
+    pub count: i64,
+    #[serde(rename = "overdueCount")]
+    pub overdue_count: i64,
+    pub todos: Vec<MatrixTodo>,
+}
+
+impl Quadrant {
+    fn new(level: Priority) -> Self {
+        Quadrant { level, label: level.label().to_string(), count: 0, overdue_count: 0, todos: Vec::new() }
+    }
+
+    fn push(&mut self, todo: Todo, now: i64) {
+        let overdue = matches!(todo.deadline(), Some(deadline) if deadline < now);
+        self.count += 1;
+        if overdue {
+            self.overdue_count += 1;
+        }
+        self.todos.push(MatrixTodo { todo, overdue });
+    }
+}
+
+// 四象限视图
+#[derive(Debug, Clone, Serialize)]
+pub struct EisenhowerMatrix {
+    #[serde(rename = "importantUrgent")]
+    pub important_urgent: Quadrant,
+    #[serde(rename = "importantNotUrgent")]
+    pub important_not_urgent: Quadrant,
+    #[serde(rename = "notImportantUrgent")]
+    pub not_important_urgent: Quadrant,
+    #[serde(rename = "notImportantNotUrgent")]
+    pub not_important_not_urgent: Quadrant,
+}
+
+impl EisenhowerMatrix {
+    fn quadrant_mut(&mut self, priority: Priority) -> &mut Quadrant {
+        match priority {
+            Priority::ImportantUrgent => &mut self.important_urgent,
+            Priority::ImportantNotUrgent => &mut self.important_not_urgent,
+            Priority::NotImportantUrgent => &mut self.not_important_urgent,
+            Priority::NotImportantNotUrgent => &mut self.not_important_not_urgent,
+        }
+    }
+}
+
+async fn load_matrix(pool: &SqlitePool, range: Option<DateWindow>) -> Result<EisenhowerMatrix, String> {
+    let now = chrono::Utc::now().timestamp();
+    // 指定时间范围时，范围之前已过期的待办同样列出
+    let (from, to) = range.map_or((None, None), |window| {
+        let (from, to) = window.range(Local::now().date_naive());
+        (Some(from), Some(to))
+    });
+
+    let mut todos = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE status = 0 AND parent_id IS NULL
+           AND (? IS NULL OR ({} >= ? AND start_time < ?) OR (end_time IS NOT NULL AND {} < ?))
+         ORDER BY start_time IS NULL, start_time, id",
+        TODO_SELECT, START_LATEST, DEADLINE
+    ))
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(pool, &mut todos).await?;
+    attach_subtasks(pool, &mut todos, false).await?;
+    attach_blocked(pool, &mut todos).await?;
+
+    let mut matrix = EisenhowerMatrix {
+        important_urgent: Quadrant::new(Priority::ImportantUrgent),
+        important_not_urgent: Quadrant::new(Priority::ImportantNotUrgent),
+        not_important_urgent: Quadrant::new(Priority::NotImportantUrgent),
+        not_important_not_urgent: Quadrant::new(Priority::NotImportantNotUrgent),
+    };
+    for todo in todos {
+        // 数据库中无效的 level 按不重要不紧急处理
+        let priority = Priority::try_from(todo.level).unwrap_or(Priority::NotImportantNotUrgent);
+        matrix.quadrant_mut(priority).push(todo, now);
+    }
+    Ok(matrix)
+}
+
+// 按四象限分组的未完成待办，不指定范围时返回全部
+#[tauri::command]
+pub async fn get_eisenhower_matrix(pool: State<'_, SqlitePool>, range: Option<DateWindow>) -> Result<EisenhowerMatrix, String> {
+    process_expired_todos(pool.inner()).await?;
+    load_matrix(pool.inner(), range).await
+}
+
+// 把待办移到另一个象限，与修改优先级相同，可撤销并记录修改历史
+#[tauri::command]
+pub async fn move_todo_to_quadrant(
+    app_handle: AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    level: Priority,
+) -> Result<(), String> {
+    update_todo(app_handle, pool, UpdateTodoParams::with_level(id, level)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::AddTodoParams;
+    use serde_json::json;
+
+    #[test]
+    fn invalid_level_is_rejected() {
+        assert_eq!(Priority::try_from(1), Ok(Priority::ImportantUrgent));
+        assert_eq!(Priority::try_from(7), Err("无效的优先级: 7".to_string()));
+        assert!(Priority::try_from(-1).is_err());
+        assert_eq!(level_rank(7), 5);
+        assert_eq!(serde_json::to_value(Priority::NotImportantUrgent).unwrap(), json!(3));
+    }
+
+    #[test]
+    fn params_with_invalid_level_fail_to_deserialize() {
+        let params = serde_json::from_value::<AddTodoParams>(json!({ "title": "A", "level": 1 })).unwrap();
+        assert_eq!(serde_json::to_value(&params).unwrap()["level"], json!(1));
+        let err = serde_json::from_value::<AddTodoParams>(json!({ "title": "A", "level": 7 })).unwrap_err();
+        assert!(err.to_string().contains("无效的优先级: 7"));
+
+        assert!(serde_json::from_value::<UpdateTodoParams>(json!({ "id": 1 })).is_ok());
+        assert!(serde_json::from_value::<UpdateTodoParams>(json!({ "id": 1, "level": 2 })).is_ok());
+        let err = serde_json::from_value::<UpdateTodoParams>(json!({ "id": 1, "level": 7 })).err().unwrap();
+        assert!(err.to_string().contains("无效的优先级: 7"));
+    }
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, State};
+use crate::history::{record_changes, ChangeSource, FieldChange};
+use crate::todo::{emit_todos_changed, nullable, DEADLINE};
+use crate::undo::ChangeRecorder;
+
+const PROJECT_SELECT: &str = "SELECT id, name, color, icon, archived, sort_order, created_at, updated_at FROM projects";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub archived: bool, // 归档后项目下的待办不再出现在列表中
+    #[serde(rename = "sortOrder")]
+    pub sort_order: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// 每个项目的待办统计，project_id 为空表示未归入项目的待办
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ProjectStats {
+    #[serde(rename = "projectId")]
+    pub project_id: Option<i64>,
+    pub pending: i64,
+    pub overdue: i64, // 未完成且已过截止时间
+    pub completed: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AddProjectParams {
+    name: String,
+    color: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateProjectParams {
+    id: i64,
+    name: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    icon: Option<Option<String>>,
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("项目名称不能为空".to_string());
+    }
+    Ok(name.to_string())
+}
+
+#[tauri::command]
+pub async fn get_all_projects(pool: State<'_, SqlitePool>, include_archived: Option<bool>) -> Result<Vec<Project>, String> {
+    let filter = if include_archived.unwrap_or(false) { "" } else { " WHERE archived = 0" };
+    sqlx::query_as::<_, Project>(&format!("{}{} ORDER BY sort_order, id", PROJECT_SELECT, filter))
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_project(pool: State<'_, SqlitePool>, params: AddProjectParams) -> Result<i64, String> {
+    let name = validate_name(&params.name)?;
+
+    // 新项目排在最后
+    let result = sqlx::query(
+        "INSERT INTO projects (name, color, icon, sort_order) VALUES (?, ?, ?, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM projects))"
+    )
+    .bind(name)
+    .bind(params.color)
+    .bind(params.icon)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_project(pool: State<'_, SqlitePool>, params: UpdateProjectParams) -> Result<(), String> {
+    let mut project = sqlx::query_as::<_, Project>(&format!("{} WHERE id = ?", PROJECT_SELECT))
+        .bind(params.id)
+        .fetch_optional(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Project not found")?;
+
+    if let Some(name) = params.name {
+        project.name = validate_name(&name)?;
+    }
+    if let Some(color) = params.color {
+        project.color = color;
+    }
+    if let Some(icon) = params.icon {
+        project.icon = icon;
+    }
+
+    sqlx::query("UPDATE projects SET name = ?, color = ?, icon = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(&project.name)
+        .bind(&project.color)
+        .bind(&project.icon)
+        .bind(project.id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// 删除项目，项目下的待办保留并移出项目
+#[tauri::command]
+pub async fn delete_project(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE todos SET project_id = NULL, updated_at = datetime('now') WHERE project_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM projects WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 按传入的顺序重新排列项目
+#[tauri::command]
+pub async fn reorder_projects(pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (index, id) in ids.iter().enumerate() {
+        sqlx::query("UPDATE projects SET sort_order = ?, updated_at = datetime('now') WHERE id = ?")
+            .bind(index as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 归档或取消归档项目，项目下的待办随之隐藏或恢复
+#[tauri::command]
+pub async fn archive_project(pool: State<'_, SqlitePool>, id: i64, archived: bool) -> Result<(), String> {
+    let result = sqlx::query("UPDATE projects SET archived = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(archived)
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("Project not found".to_string());
+    }
+    Ok(())
+}
+
+// 将待办移到指定项目，project_id 为空表示移出项目；每条待办的移动都可撤销并记录修改历史
+#[tauri::command]
+pub async fn move_todos_to_project(
+    app_handle: AppHandle,
+    pool: State<'_, SqlitePool>,
+    todo_ids: Vec<i64>,
+    project_id: Option<i64>,
+) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    if let Some(project_id) = project_id {
+        let exists = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM projects WHERE id = ?")
+            .bind(project_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists == 0 {
+            return Err("Project not found".to_string());
+        }
+    }
+
+    // 先取出所有待办原来的项目，有不存在或已在回收站中的待办时整体不移动
+    let mut moves = Vec::with_capacity(todo_ids.len());
+    let mut missing = Vec::new();
+    for &todo_id in &todo_ids {
+        let old_project_id = sqlx::query_scalar::<_, Option<i64>>("SELECT project_id FROM todos WHERE id = ? AND status != 2")
+            .bind(todo_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        match old_project_id {
+            Some(old_project_id) => moves.push((todo_id, old_project_id)),
+            None => missing.push(todo_id.to_string()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!("待办不存在: {}", missing.join(", ")));
+    }
+
+    let mut moved = Vec::new();
+    for (todo_id, old_project_id) in moves {
+        if old_project_id == project_id || moved.contains(&todo_id) {
+            continue;
+        }
+        let recorder = ChangeRecorder::begin(&mut tx, "update", Some(todo_id)).await?;
+        sqlx::query("UPDATE todos SET project_id = ?, updated_at = datetime('now') WHERE id = ?")
+            .bind(project_id)
+            .bind(todo_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        record_changes(&mut tx, todo_id, &[FieldChange::new("projectId", old_project_id, project_id)], ChangeSource::Ui).await?;
+        recorder.finish(&mut tx, todo_id).await?;
+        moved.push(todo_id);
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    if !moved.is_empty() {
+        emit_todos_changed(&app_handle, "update", &moved);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_project_stats(pool: State<'_, SqlitePool>) -> Result<Vec<ProjectStats>, String> {
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query_as::<_, ProjectStats>(&format!(
+        "SELECT project_id,
+                COALESCE(SUM(status = 0), 0) AS pending,
+                COALESCE(SUM(status = 0 AND end_time IS NOT NULL AND {} < ?), 0) AS overdue,
+                COALESCE(SUM(status = 1), 0) AS completed
+         FROM todos WHERE status != 2 GROUP BY project_id",
+        DEADLINE
+    ))
+    .bind(now)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 列表查询的项目过滤条件，未指定项目时排除归档项目下的待办，打开归档项目时仍能看到其中的待办
+pub(crate) fn project_filter_clause(project_id: Option<i64>) -> String {
+    match project_id {
+        Some(project_id) => format!(" AND project_id = {}", project_id),
+        None => " AND (project_id IS NULL OR project_id NOT IN (SELECT id FROM projects WHERE archived = 1))".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn titles(pool: &SqlitePool, project_id: Option<i64>) -> Vec<String> {
+        sqlx::query_scalar::<_, String>(&format!(
+            "SELECT title FROM todos WHERE status != 2{} ORDER BY id",
+            project_filter_clause(project_id)
+        ))
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn archived_project_lists_its_own_todos() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name, archived) VALUES (1, '进行中', 0), (2, '已归档', 1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO todos (title, start_time, project_id) VALUES ('收件箱', 0, NULL), ('进行中的待办', 0, 1), ('归档的待办', 0, 2)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(titles(&pool, None).await, ["收件箱", "进行中的待办"]);
+        assert_eq!(titles(&pool, Some(1)).await, ["进行中的待办"]);
+        assert_eq!(titles(&pool, Some(2)).await, ["归档的待办"]);
+    }
+}
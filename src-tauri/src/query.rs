@@ -0,0 +1,434 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use tauri::State;
+use crate::priority::{level_rank, LEVEL_ORDER};
+use crate::project::project_filter_clause;
+use crate::search::{like_pattern, split_terms};
+use crate::dependency::attach_blocked;
+use crate::subtask::attach_subtasks;
+use crate::tag::{attach_tags, tag_filter_clause};
+use crate::todo::{process_expired_todos, Todo, DEADLINE, START_LATEST, TODO_SELECT};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TodoSortKey {
+    #[default]
+    #[serde(rename = "startTime")]
+    StartTime,
+    #[serde(rename = "endTime")]
+    EndTime, // 没有截止时间的排在最后
+    #[serde(rename = "createdAt")]
+    CreatedAt,
+    #[serde(rename = "updatedAt")]
+    UpdatedAt,
+    #[serde(rename = "priority")]
+    Priority, // 重要且紧急 > 重要不紧急 > 不重要但紧急 > 不重要不紧急，同级按开始时间
+    #[serde(rename = "title")]
+    Title,
+}
+
+impl TodoSortKey {
+    // 排序用的表达式，分页游标按同样的顺序记录最后一条的值；没有开始时间的排在最后
+    fn columns(self) -> Vec<&'static str> {
+        match self {
+            TodoSortKey::StartTime => vec!["COALESCE(start_time, 9223372036854775807)"],
+            TodoSortKey::EndTime => vec!["COALESCE(end_time, 9223372036854775807)"],
+            TodoSortKey::CreatedAt => vec!["created_at"],
+            TodoSortKey::UpdatedAt => vec!["updated_at"],
+            TodoSortKey::Priority => vec![LEVEL_ORDER, "COALESCE(start_time, 9223372036854775807)"],
+            TodoSortKey::Title => vec!["title"],
+        }
+    }
+
+    fn values(self, todo: &Todo) -> Vec<Value> {
+        match self {
+            TodoSortKey::StartTime => vec![todo.start_time.unwrap_or(i64::MAX).into()],
+            TodoSortKey::EndTime => vec![todo.end_time.unwrap_or(i64::MAX).into()],
+            TodoSortKey::CreatedAt => vec![todo.created_at.clone().into()],
+            TodoSortKey::UpdatedAt => vec![todo.updated_at.clone().into()],
+            TodoSortKey::Priority => vec![level_rank(todo.level).into(), todo.start_time.unwrap_or(i64::MAX).into()],
+            TodoSortKey::Title => vec![todo.title.clone().into()],
+        }
+    }
+}
+
+// 相对于当前日期的时间窗口，保存的智能清单每次计算时按当天重新求值
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DateWindow {
+    #[serde(rename = "today")]
+    Today,
+    #[serde(rename = "tomorrow")]
+    Tomorrow,
+    #[serde(rename = "thisWeek")]
+    ThisWeek, // 周一到周日
+    #[serde(rename = "next7Days")]
+    Next7Days, // 从今天起的七天
+    #[serde(rename = "thisMonth")]
+    ThisMonth,
+}
+
+impl DateWindow {
+    // 返回本地时间下的 [开始, 结束) 时间戳
+    pub(crate) fn range(self, today: NaiveDate) -> (i64, i64) {
+        let (from, to) = match self {
+            DateWindow::Today => (today, today + Duration::days(1)),
+            DateWindow::Tomorrow => (today + Duration::days(1), today + Duration::days(2)),
+            DateWindow::ThisWeek => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                (monday, monday + Duration::days(7))
+            }
+            DateWindow::Next7Days => (today, today + Duration::days(7)),
+            DateWindow::ThisMonth => {
+                let first = today.with_day(1).unwrap_or(today);
+                let next = if first.month() == 12 {
+                    NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+                };
+                (first, next.unwrap_or(first + Duration::days(31)))
+            }
+        };
+        (local_timestamp(from), local_timestamp(to))
+    }
+}
+
+pub(crate) fn local_timestamp(date: NaiveDate) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+// 待办查询条件，所有条件之间为“且”；智能清单也保存这个结构
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TodoFilter {
+    #[serde(default)]
+    pub statuses: Option<Vec<i64>>, // 默认未完成和已完成
+    #[serde(default)]
+    pub levels: Option<Vec<i64>>,
+    #[serde(rename = "startFrom", default)]
+    pub start_from: Option<i64>,
+    #[serde(rename = "startTo", default)]
+    pub start_to: Option<i64>,
+    #[serde(rename = "endFrom", default)]
+    pub end_from: Option<i64>, // 按截止时间筛选，全天待办截止到截止日期当天结束
+    #[serde(rename = "endTo", default)]
+    pub end_to: Option<i64>,
+    #[serde(rename = "startWindow", default)]
+    pub start_window: Option<DateWindow>, // 与 startFrom/startTo 同时生效
+    #[serde(default)]
+    pub overdue: bool, // 未完成且已过截止时间
+    #[serde(default)]
+    pub scheduled: Option<bool>, // false 只查收集箱中尚未安排时间的待办
+    #[serde(rename = "hasDeadline", default)]
+    pub has_deadline: Option<bool>,
+    #[serde(rename = "recurringOnly", default)]
+    pub recurring_only: bool,
+    #[serde(default)]
+    pub text: Option<String>, // 标题或备注包含所有关键词
+    #[serde(rename = "tagIds", default)]
+    pub tag_ids: Option<Vec<i64>>,
+    #[serde(rename = "matchAllTags", default)]
+    pub match_all_tags: bool,
+    #[serde(rename = "projectId", default)]
+    pub project_id: Option<i64>,
+    #[serde(rename = "includeSubtasks", default)]
+    pub include_subtasks: bool,
+    #[serde(default)]
+    pub sort: TodoSortKey,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageParams {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    cursor: Option<String>, // 上一页返回的 nextCursor，传入时忽略 offset
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoPage {
+    pub items: Vec<Todo>,
+    pub total: i64, // 符合条件的总数，不受分页影响
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    #[serde(rename = "nextOffset")]
+    pub next_offset: Option<i64>,
+}
+
+fn push_id_list(qb: &mut QueryBuilder<'_, Sqlite>, column: &str, ids: &[i64]) {
+    qb.push(format!(" AND {} IN (", column));
+    let mut separated = qb.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    separated.push_unseparated(")");
+}
+
+fn push_filter(qb: &mut QueryBuilder<'_, Sqlite>, filter: &TodoFilter) {
+    match filter.statuses.as_deref() {
+        Some(statuses) if !statuses.is_empty() => push_id_list(qb, "status", statuses),
+        _ => {
+            qb.push(" AND status IN (0, 1)");
+        }
+    }
+    if let Some(levels) = filter.levels.as_deref().filter(|l| !l.is_empty()) {
+        push_id_list(qb, "level", levels);
+    }
+    if let Some(start_from) = filter.start_from {
+        qb.push(format!(" AND {} >= ", START_LATEST)).push_bind(start_from);
+    }
+    if let Some(start_to) = filter.start_to {
+        qb.push(" AND start_time <= ").push_bind(start_to);
+    }
+    if let Some(end_from) = filter.end_from {
+        qb.push(format!(" AND {} >= ", DEADLINE)).push_bind(end_from);
+    }
+    if let Some(end_to) = filter.end_to {
+        qb.push(format!(" AND {} <= ", DEADLINE)).push_bind(end_to);
+    }
+    if let Some(window) = filter.start_window {
+        let (from, to) = window.range(Local::now().date_naive());
+        qb.push(format!(" AND {} >= ", START_LATEST)).push_bind(from);
+        qb.push(" AND start_time < ").push_bind(to);
+    }
+    if filter.overdue {
+        qb.push(format!(" AND status = 0 AND end_time IS NOT NULL AND {} < ", DEADLINE))
+            .push_bind(chrono::Utc::now().timestamp());
+    }
+    match filter.scheduled {
+        Some(true) => {
+            qb.push(" AND start_time IS NOT NULL");
+        }
+        Some(false) => {
+            qb.push(" AND start_time IS NULL");
+        }
+        None => {}
+    }
+    match filter.has_deadline {
+        Some(true) => {
+            qb.push(" AND end_time IS NOT NULL");
+        }
+        Some(false) => {
+            qb.push(" AND end_time IS NULL");
+        }
+        None => {}
+    }
+    if filter.recurring_only {
+        qb.push(" AND rrule IS NOT NULL");
+    }
+    if !filter.include_subtasks {
+        qb.push(" AND parent_id IS NULL");
+    }
+    for term in split_terms(filter.text.as_deref().unwrap_or_default()) {
+        let pattern = like_pattern(&term);
+        qb.push(" AND (title LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR notes LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '\\')");
+    }
+    qb.push(tag_filter_clause(filter.tag_ids.as_deref(), filter.match_all_tags));
+    qb.push(project_filter_clause(filter.project_id));
+}
+
+fn decode_cursor(cursor: &str, expected: usize) -> Result<Vec<Value>, String> {
+    let values: Vec<Value> = serde_json::from_str(cursor).map_err(|_| "无效的分页游标".to_string())?;
+    let valid = values.len() == expected + 1
+        && values.iter().all(|v| v.is_i64() || v.is_string())
+        && values.last().is_some_and(|v| v.is_i64());
+    if !valid {
+        return Err("无效的分页游标".to_string());
+    }
+    Ok(values)
+}
+
+// 符合条件的待办总数
+pub(crate) async fn count_todos(pool: &SqlitePool, filter: &TodoFilter) -> Result<i64, String> {
+    let mut qb = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM todos WHERE 1 = 1");
+    push_filter(&mut qb, filter);
+    qb.build_query_scalar::<i64>()
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// 查询一页待办，返回总数以及下一页的游标和偏移量（没有下一页时为空）
+pub(crate) async fn query_todo_page(pool: &SqlitePool, filter: &TodoFilter, page: &PageParams) -> Result<TodoPage, String> {
+    let limit = page.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let columns = filter.sort.columns();
+    let direction = if filter.descending { "DESC" } else { "ASC" };
+
+    let total = count_todos(pool, filter).await?;
+
+    let mut qb = QueryBuilder::<Sqlite>::new(format!("{} WHERE 1 = 1", TODO_SELECT));
+    push_filter(&mut qb, filter);
+
+    // 游标分页：按 (排序字段..., id) 的行值比较取上一页最后一条之后的数据
+    let mut offset = page.offset.unwrap_or(0).max(0);
+    if let Some(cursor) = page.cursor.as_deref() {
+        offset = 0;
+        let values = decode_cursor(cursor, columns.len())?;
+        qb.push(format!(" AND ({}, id) {} (", columns.join(", "), if filter.descending { "<" } else { ">" }));
+        let mut separated = qb.separated(", ");
+        for value in values {
+            match value {
+                Value::String(s) => separated.push_bind(s),
+                other => separated.push_bind(other.as_i64().unwrap_or_default()),
+            };
+        }
+        separated.push_unseparated(")");
+    }
+
+    let order = columns
+        .iter()
+        .chain(std::iter::once(&"id"))
+        .map(|c| format!("{} {}", c, direction))
+        .collect::<Vec<_>>()
+        .join(", ");
+    qb.push(format!(" ORDER BY {}", order));
+    qb.push(" LIMIT ").push_bind(limit + 1);
+    qb.push(" OFFSET ").push_bind(offset);
+
+    let mut items = qb
+        .build_query_as::<Todo>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let has_more = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+    let next_cursor = match items.last() {
+        Some(last) if has_more => {
+            let mut values = filter.sort.values(last);
+            values.push(last.id.into());
+            Some(Value::Array(values).to_string())
+        }
+        _ => None,
+    };
+    let next_offset = has_more.then_some(offset + items.len() as i64);
+
+    attach_tags(pool, &mut items).await?;
+    attach_subtasks(pool, &mut items, false).await?;
+    attach_blocked(pool, &mut items).await?;
+
+    Ok(TodoPage { items, total, next_cursor, next_offset })
+}
+
+#[tauri::command]
+pub async fn query_todos(
+    pool: State<'_, SqlitePool>,
+    filter: TodoFilter,
+    page: Option<PageParams>,
+) -> Result<TodoPage, String> {
+    process_expired_todos(pool.inner()).await?;
+    query_todo_page(pool.inner(), &filter, &page.unwrap_or_default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        // 开始时间、优先级和标题都有大量相同的值，只能靠 id 区分先后
+        for i in 0..11 {
+            sqlx::query("INSERT INTO todos (title, start_time, level) VALUES (?, ?, ?)")
+                .bind(if i % 2 == 0 { "周报" } else { "会议" })
+                .bind(if i < 8 { Some(100 * (i % 3)) } else { None })
+                .bind(i % 2)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    // 按游标翻完所有页，返回依次取到的 id
+    async fn page_through(pool: &SqlitePool, filter: &TodoFilter, limit: i64) -> Vec<i64> {
+        let mut ids = Vec::new();
+        let mut page = PageParams { limit: Some(limit), ..Default::default() };
+        loop {
+            let result = query_todo_page(pool, filter, &page).await.unwrap();
+            assert_eq!(result.total, 11);
+            assert!(result.items.len() as i64 <= limit);
+            ids.extend(result.items.iter().map(|t| t.id));
+            match result.next_cursor {
+                Some(cursor) => page.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn cursor_pages_through_ties_once() {
+        let pool = pool().await;
+        for sort in [TodoSortKey::StartTime, TodoSortKey::Priority, TodoSortKey::Title, TodoSortKey::EndTime] {
+            for descending in [false, true] {
+                let filter = TodoFilter { sort, descending, ..Default::default() };
+                let all = query_todo_page(&pool, &filter, &PageParams { limit: Some(100), ..Default::default() })
+                    .await
+                    .unwrap();
+                let expected = all.items.iter().map(|t| t.id).collect::<Vec<_>>();
+                assert_eq!(expected.len(), 11);
+                assert!(all.next_cursor.is_none());
+
+                for limit in [1, 2, 3, 4] {
+                    assert_eq!(page_through(&pool, &filter, limit).await, expected, "{:?} {} {}", sort, descending, limit);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_cursor_is_rejected() {
+        let pool = pool().await;
+        let filter = TodoFilter::default();
+        for cursor in ["oops", "[1]", "[1, 2, 3]", "[1, \"2\"]"] {
+            let page = PageParams { cursor: Some(cursor.to_string()), ..Default::default() };
+            assert!(query_todo_page(&pool, &filter, &page).await.is_err(), "{}", cursor);
+        }
+    }
+
+    #[tokio::test]
+    async fn deadline_range_uses_end_of_all_day_todos() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        let day = crate::todo::local_day_start(1_000_000, 0);
+        let next_day = crate::todo::local_day_start(day, 1);
+        let mut ids = Vec::new();
+        for (end_time, all_day) in [(Some(day), true), (Some(day + 3600), false), (None, false)] {
+            let id = sqlx::query("INSERT INTO todos (title, start_time, end_time, all_day) VALUES ('t', ?, ?, ?)")
+                .bind(day)
+                .bind(end_time)
+                .bind(all_day)
+                .execute(&pool)
+                .await
+                .unwrap()
+                .last_insert_rowid();
+            ids.push(id);
+        }
+
+        let query = |end_from: Option<i64>, end_to: Option<i64>| {
+            let pool = pool.clone();
+            async move {
+                let filter = TodoFilter { end_from, end_to, ..Default::default() };
+                let page = query_todo_page(&pool, &filter, &PageParams::default()).await.unwrap();
+                page.items.iter().map(|t| t.id).collect::<Vec<_>>()
+            }
+        };
+        // 全天待办截止到当天结束，中午之后仍在范围内
+        assert_eq!(query(Some(day + 12 * 3600), None).await, [ids[0]]);
+        assert_eq!(query(None, Some(day + 3600)).await, [ids[1]]);
+        assert_eq!(query(Some(day), Some(next_day)).await, [ids[0], ids[1]]);
+    }
+}
@@ -0,0 +1,1208 @@
+// 快速添加：不依赖网络和 AI，从一句中文或英文里解析出时间、重复规则和优先级，
+// 剩下的文字作为标题，例如“明天下午3点交周报，每周五重复”
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use crate::priority::Priority;
+use crate::recurrence::{days_in_month, local_to_timestamp, normalize_rule, weekday_code};
+use crate::todo::AddTodoParams;
+
+// 开头的客套话，直接去掉
+const FILLERS: &[&str] = &["提醒我", "记得", "remind me to", "remind me"];
+
+// 英文星期名和常用缩写，长的在前；sun、sat 等缩写也是普通单词，只在 on、next、every 等之后识别
+const ENGLISH_WEEKDAYS: &[(&str, Weekday)] = &[
+    ("monday", Weekday::Mon),
+    ("mon", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("tues", Weekday::Tue),
+    ("tue", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("wed", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("thurs", Weekday::Thu),
+    ("thur", Weekday::Thu),
+    ("thu", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("fri", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sat", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+    ("sun", Weekday::Sun),
+];
+
+// 相对今天的日期，部分同时带有时段
+const RELATIVE_DAYS: &[(&str, i64, Option<DayPeriod>)] = &[
+    ("大后天", 3, None),
+    ("后天", 2, None),
+    ("明天", 1, None),
+    ("明日", 1, None),
+    ("明早", 1, Some(DayPeriod::Morning)),
+    ("明晚", 1, Some(DayPeriod::Evening)),
+    ("今天", 0, None),
+    ("今日", 0, None),
+    ("今早", 0, Some(DayPeriod::Morning)),
+    ("今晚", 0, Some(DayPeriod::Evening)),
+    ("day after tomorrow", 2, None),
+    ("tomorrow", 1, None),
+    ("tmrw", 1, None),
+    ("tmr", 1, None),
+    ("today", 0, None),
+    ("tonight", 0, Some(DayPeriod::Evening)),
+];
+
+// 周X 的前缀，数字为相对本周的周数，None 表示今天或之后最近的一天
+const WEEK_PREFIXES: &[(&str, Option<i64>)] = &[
+    ("下个星期", Some(1)),
+    ("下星期", Some(1)),
+    ("下礼拜", Some(1)),
+    ("下周", Some(1)),
+    ("这个星期", Some(0)),
+    ("本星期", Some(0)),
+    ("本周", Some(0)),
+    ("这周", Some(0)),
+    ("星期", None),
+    ("礼拜", None),
+    ("周", None),
+];
+
+const PERIODS: &[(&str, DayPeriod)] = &[
+    ("凌晨", DayPeriod::EarlyMorning),
+    ("早上", DayPeriod::Morning),
+    ("早晨", DayPeriod::Morning),
+    ("上午", DayPeriod::Morning),
+    ("中午", DayPeriod::Noon),
+    ("下午", DayPeriod::Afternoon),
+    ("傍晚", DayPeriod::Evening),
+    ("晚上", DayPeriod::Evening),
+    ("in the morning", DayPeriod::Morning),
+    ("in the afternoon", DayPeriod::Afternoon),
+    ("in the evening", DayPeriod::Evening),
+    ("morning", DayPeriod::Morning),
+    ("afternoon", DayPeriod::Afternoon),
+    ("evening", DayPeriod::Evening),
+];
+
+// 优先级标记：(词, 是否重要, 是否紧急)，否定的写法在前
+const PRIORITY_MARKERS: &[(&str, Option<bool>, Option<bool>)] = &[
+    ("重要且紧急", Some(true), Some(true)),
+    ("重要紧急", Some(true), Some(true)),
+    ("不重要", Some(false), None),
+    ("重要", Some(true), None),
+    ("不紧急", None, Some(false)),
+    ("不急", None, Some(false)),
+    ("紧急", None, Some(true)),
+    ("加急", None, Some(true)),
+    ("很急", None, Some(true)),
+    ("high priority", Some(true), Some(true)),
+    ("low priority", Some(false), Some(false)),
+    ("not important", Some(false), None),
+    ("unimportant", Some(false), None),
+    ("important", Some(true), None),
+    ("not urgent", None, Some(false)),
+    ("urgent", None, Some(true)),
+    ("asap", None, Some(true)),
+];
+
+// “前”后面跟这些字时是“前台”“前面”之类的词，不表示截止
+const NOT_DEADLINE_AFTER_QIAN: &str = "台面往端排线方";
+
+// 分隔句子成分的标点，标题首尾的这些字符会被去掉
+const PUNCTUATION: &str = "，,。.、;；:：!！~～-";
+
+// 指定了日期又没说上午下午时，早于这个钟点的按下午算，如“明天3点”为 15 点
+const AFTERNOON_BEFORE_HOUR: u32 = 7;
+
+// 时段，决定“3点”是上午还是下午，没有具体时刻时使用默认时刻
+#[derive(Debug, Clone, Copy)]
+enum DayPeriod {
+    EarlyMorning,
+    Morning,
+    Noon,
+    Afternoon,
+    Evening,
+}
+
+impl DayPeriod {
+    fn default_hour(self) -> u32 {
+        match self {
+            DayPeriod::EarlyMorning => 6,
+            DayPeriod::Morning => 9,
+            DayPeriod::Noon => 12,
+            DayPeriod::Afternoon => 15,
+            DayPeriod::Evening => 20,
+        }
+    }
+
+    fn adjust(self, hour: u32) -> u32 {
+        match self {
+            DayPeriod::Afternoon | DayPeriod::Evening if hour < 12 => hour + 12,
+            // 中午1点
+            DayPeriod::Noon if hour < 6 => hour + 12,
+            _ => hour,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Clock {
+    hour: u32,
+    minute: u32,
+    // 只写了“3点”，没有上午下午之分
+    ambiguous: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Repeat {
+    Daily,
+    Workday,
+    Weekly(Vec<Weekday>),
+    Monthly(Option<i32>),
+    Yearly,
+}
+
+impl Repeat {
+    fn rule(&self) -> String {
+        match self {
+            Repeat::Daily => "FREQ=DAILY".to_string(),
+            Repeat::Workday => "FREQ=DAILY;X-WORKDAY=ONLY".to_string(),
+            Repeat::Weekly(days) if days.is_empty() => "FREQ=WEEKLY".to_string(),
+            Repeat::Weekly(days) => {
+                let codes: Vec<&str> = days.iter().map(|d| weekday_code(*d)).collect();
+                format!("FREQ=WEEKLY;BYDAY={}", codes.join(","))
+            }
+            Repeat::Monthly(Some(day)) => format!("FREQ=MONTHLY;BYMONTHDAY={}", day),
+            Repeat::Monthly(None) => "FREQ=MONTHLY".to_string(),
+            Repeat::Yearly => "FREQ=YEARLY".to_string(),
+        }
+    }
+
+    // 只给出重复规则时，系列从今天或之后第一个符合规则的日期开始
+    fn first_date(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Repeat::Daily | Repeat::Yearly | Repeat::Monthly(None) => today,
+            Repeat::Weekly(days) => days
+                .iter()
+                .map(|d| next_weekday(today, *d))
+                .min()
+                .unwrap_or(today),
+            Repeat::Workday => (0..7)
+                .map(|i| today + Duration::days(i))
+                .find(|d| d.weekday().num_days_from_monday() < 5)
+                .unwrap_or(today),
+            Repeat::Monthly(Some(day)) => {
+                let this_month = month_day(today, *day);
+                if this_month >= today {
+                    this_month
+                } else {
+                    month_day(today.checked_add_months(Months::new(1)).unwrap_or(today), *day)
+                }
+            }
+        }
+    }
+}
+
+// date 所在月的第 day 天，-1 表示最后一天，超出当月天数时取最后一天
+fn month_day(date: NaiveDate, day: i32) -> NaiveDate {
+    let last = days_in_month(date.year(), date.month());
+    let day = if day < 0 { last } else { (day as u32).min(last) };
+    date.with_day(day).unwrap_or(date)
+}
+
+// 今天所在周（周一开始）之后第 weeks 周的星期 weekday
+fn week_day(today: NaiveDate, weeks: i64, weekday: Weekday) -> NaiveDate {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    monday + Duration::days(weeks * 7 + weekday.num_days_from_monday() as i64)
+}
+
+// 今天或之后最近的星期 weekday
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let diff = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(diff as i64)
+}
+
+fn chinese_digit(c: char) -> Option<u32> {
+    match c {
+        '零' => Some(0),
+        '一' => Some(1),
+        '二' | '两' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    }
+}
+
+fn chinese_weekday(c: char) -> Option<Weekday> {
+    match c {
+        '一' | '1' => Some(Weekday::Mon),
+        '二' | '2' => Some(Weekday::Tue),
+        '三' | '3' => Some(Weekday::Wed),
+        '四' | '4' => Some(Weekday::Thu),
+        '五' | '5' => Some(Weekday::Fri),
+        '六' | '6' => Some(Weekday::Sat),
+        '日' | '天' | '七' | '7' => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// 去掉首尾的空白和标点，合并连续空白
+fn clean_title(title: &str) -> String {
+    let collapsed = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed
+        .trim_matches(|c: char| c.is_whitespace() || PUNCTUATION.contains(c))
+        .to_string()
+}
+
+struct QuickAddParser {
+    chars: Vec<char>,
+    now: NaiveDateTime,
+    date: Option<NaiveDate>,
+    clock: Option<Clock>,
+    period: Option<DayPeriod>,
+    // “2小时后”这类精确到时刻的偏移
+    offset: Option<Duration>,
+    // “周五前”“by friday”：解析出的时间作为截止时间
+    deadline: bool,
+    repeat: Option<Repeat>,
+    important: Option<bool>,
+    urgent: Option<bool>,
+}
+
+impl QuickAddParser {
+    fn new(text: &str, now: NaiveDateTime) -> Self {
+        QuickAddParser {
+            chars: text.chars().collect(),
+            now,
+            date: None,
+            clock: None,
+            period: None,
+            offset: None,
+            deadline: false,
+            repeat: None,
+            important: None,
+            urgent: None,
+        }
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.now.date()
+    }
+
+    fn char_at(&self, pos: usize) -> Option<char> {
+        self.chars.get(pos).copied()
+    }
+
+    // pos 处是否为 text（英文不区分大小写），返回字符数
+    fn literal(&self, pos: usize, text: &str) -> Option<usize> {
+        let mut len = 0;
+        for c in text.chars() {
+            if self.char_at(pos + len).map(|x| x.to_ascii_lowercase()) != Some(c) {
+                return None;
+            }
+            len += 1;
+        }
+        Some(len)
+    }
+
+    // 英文单词还要求前后不是字母或数字，避免 mon 匹配到 month
+    fn word(&self, pos: usize, text: &str) -> Option<usize> {
+        let len = self.literal(pos, text)?;
+        let alnum = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+        if text.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && (alnum(pos.checked_sub(1).and_then(|p| self.char_at(p))) || alnum(self.char_at(pos + len)))
+        {
+            return None;
+        }
+        Some(len)
+    }
+
+    fn any_word(&self, pos: usize, words: &[&str]) -> Option<usize> {
+        words.iter().find_map(|w| self.word(pos, w))
+    }
+
+    // pos 之前跳过空白后是句首或标点
+    fn separated_before(&self, pos: usize) -> bool {
+        self.chars[..pos.min(self.chars.len())]
+            .iter()
+            .rev()
+            .find(|c| !c.is_whitespace())
+            .is_none_or(|c| PUNCTUATION.contains(*c))
+    }
+
+    // pos 之后跳过空白后是句末或标点
+    fn separated_after(&self, pos: usize) -> bool {
+        self.chars[pos.min(self.chars.len())..]
+            .iter()
+            .find(|c| !c.is_whitespace())
+            .is_none_or(|c| PUNCTUATION.contains(*c))
+    }
+
+    fn spaces(&self, pos: usize) -> usize {
+        self.chars[pos.min(self.chars.len())..]
+            .iter()
+            .take_while(|c| c.is_whitespace())
+            .count()
+    }
+
+    // 阿拉伯数字（最多4位）或不超过两位的中文数字
+    fn number(&self, pos: usize) -> Option<(u32, usize)> {
+        let digits = self.chars[pos.min(self.chars.len())..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits > 0 {
+            if digits > 4 {
+                return None;
+            }
+            let value = self.chars[pos..pos + digits].iter().collect::<String>().parse().ok()?;
+            return Some((value, digits));
+        }
+        let ten = |p: usize| self.char_at(p) == Some('十');
+        if let Some(tens) = self.char_at(pos).and_then(chinese_digit) {
+            if ten(pos + 1) {
+                return match self.char_at(pos + 2).and_then(chinese_digit) {
+                    Some(ones) => Some((tens * 10 + ones, 3)),
+                    None => Some((tens * 10, 2)),
+                };
+            }
+            return Some((tens, 1));
+        }
+        if ten(pos) {
+            return match self.char_at(pos + 1).and_then(chinese_digit) {
+                Some(ones) => Some((10 + ones, 2)),
+                None => Some((10, 1)),
+            };
+        }
+        None
+    }
+
+    fn english_weekday(&self, pos: usize, abbreviations: bool) -> Option<(usize, Weekday)> {
+        ENGLISH_WEEKDAYS
+            .iter()
+            .filter(|(name, _)| abbreviations || name.ends_with("day"))
+            .find_map(|(name, weekday)| self.word(pos, name).map(|len| (len, *weekday)))
+    }
+
+    // 扫描整句，去掉识别出的部分，剩下的作为标题
+    fn extract_title(&mut self) -> String {
+        let mut title = String::new();
+        let mut pos = 0;
+        while pos < self.chars.len() {
+            match self.match_at(pos) {
+                Some(len) => pos += len,
+                None => {
+                    title.push(self.chars[pos]);
+                    pos += 1;
+                }
+            }
+        }
+        clean_title(&title)
+    }
+
+    fn match_at(&mut self, pos: usize) -> Option<usize> {
+        if let Some(len) = FILLERS.iter().find_map(|w| self.word(pos, w)) {
+            return Some(len);
+        }
+        self.match_repeat(pos)
+            .or_else(|| self.match_priority(pos))
+            .or_else(|| self.match_prefixed(pos))
+            .or_else(|| self.match_when(pos))
+    }
+
+    // 每天、每周一三五、每月1号、工作日、every monday……，后面可以跟“重复”
+    fn match_repeat(&mut self, pos: usize) -> Option<usize> {
+        let (len, repeat) = if let Some(len) = self.any_word(pos, &["每个工作日", "每工作日", "工作日"]) {
+            (len, Repeat::Workday)
+        } else if let Some(len) = self.any_word(pos, &["每天", "每日"]) {
+            (len, Repeat::Daily)
+        } else if let Some(len) = self.any_word(pos, &["每个星期", "每星期", "每礼拜", "每周"]) {
+            let (days_len, days) = self.chinese_weekday_list(pos + len);
+            (len + days_len, Repeat::Weekly(days))
+        } else if let Some(len) = self.any_word(pos, &["每个月", "每月"]) {
+            let at = pos + len;
+            if let Some(end) = self.any_word(at, &["最后一天", "月底", "底"]) {
+                (len + end, Repeat::Monthly(Some(-1)))
+            } else if let Some((day, day_len)) = self.number(at).filter(|(d, _)| (1..=31).contains(d)) {
+                match self.any_word(at + day_len, &["号", "日"]) {
+                    Some(unit) => (len + day_len + unit, Repeat::Monthly(Some(day as i32))),
+                    None => (len, Repeat::Monthly(None)),
+                }
+            } else {
+                (len, Repeat::Monthly(None))
+            }
+        } else if let Some(len) = self.any_word(pos, &["每年"]) {
+            (len, Repeat::Yearly)
+        } else {
+            self.english_repeat(pos)?
+        };
+        self.repeat = Some(repeat);
+        let at = pos + len;
+        let suffix = self
+            .any_word(at, &["重复", "循环"])
+            .or_else(|| self.word(at + self.spaces(at), "repeat").map(|l| l + self.spaces(at)))
+            .unwrap_or(0);
+        Some(len + suffix)
+    }
+
+    // every day、every mon and thu、repeat weekly……
+    fn english_repeat(&self, pos: usize) -> Option<(usize, Repeat)> {
+        let prefix = match self.word(pos, "repeat") {
+            Some(len) if self.spaces(pos + len) > 0 => len + self.spaces(pos + len),
+            _ => 0,
+        };
+        let at = pos + prefix;
+        let (len, repeat) = if let Some(len) = self.any_word(at, &["every weekday", "every workday", "weekdays"]) {
+            (len, Repeat::Workday)
+        } else if let Some(len) = self.any_word(at, &["every day", "everyday"]) {
+            (len, Repeat::Daily)
+        } else if let Some((len, days)) = self.english_weekday_list(at) {
+            (len, Repeat::Weekly(days))
+        } else if let Some(len) = self.word(at, "every week") {
+            (len, Repeat::Weekly(Vec::new()))
+        } else if let Some(len) = self.word(at, "every month") {
+            (len, Repeat::Monthly(None))
+        } else if let Some(len) = self.word(at, "every year") {
+            (len, Repeat::Yearly)
+        } else {
+            const ADVERBS: &[(&str, Repeat)] = &[
+                ("daily", Repeat::Daily),
+                ("weekly", Repeat::Weekly(Vec::new())),
+                ("monthly", Repeat::Monthly(None)),
+                ("yearly", Repeat::Yearly),
+                ("annually", Repeat::Yearly),
+            ];
+            let (len, repeat) = ADVERBS.iter().find_map(|(w, repeat)| self.word(at, w).map(|len| (len, repeat.clone())))?;
+            // 这些词也常作形容词（monthly report），只在 repeat 前后或句末时表示重复
+            let end = at + len;
+            if prefix == 0 && self.word(end + self.spaces(end), "repeat").is_none() && !self.separated_after(end) {
+                return None;
+            }
+            (len, repeat)
+        };
+        Some((prefix + len, repeat))
+    }
+
+    // 每周后面的星期列表：一三五、一、三、五、周末
+    fn chinese_weekday_list(&self, pos: usize) -> (usize, Vec<Weekday>) {
+        if let Some(len) = self.word(pos, "末") {
+            return (len, vec![Weekday::Sat, Weekday::Sun]);
+        }
+        let mut days = Vec::new();
+        let mut at = pos;
+        let mut consumed = 0;
+        loop {
+            let mut next = at;
+            if !days.is_empty() {
+                while self.char_at(next).is_some_and(|c| "、,，和/".contains(c)) {
+                    next += 1;
+                }
+                next += self.any_word(next, &["星期", "周"]).unwrap_or(0);
+            }
+            match self.char_at(next).and_then(chinese_weekday) {
+                Some(day) => {
+                    if !days.contains(&day) {
+                        days.push(day);
+                    }
+                    at = next + 1;
+                    consumed = at - pos;
+                }
+                None => break,
+            }
+        }
+        days.sort_by_key(|d| d.num_days_from_monday());
+        (consumed, days)
+    }
+
+    // every monday、every mon and thu
+    fn english_weekday_list(&self, pos: usize) -> Option<(usize, Vec<Weekday>)> {
+        let len = self.word(pos, "every")?;
+        let mut at = pos + len + self.spaces(pos + len);
+        let (day_len, day) = self.english_weekday(at, true)?;
+        let mut days = vec![day];
+        at += day_len;
+        loop {
+            let mut next = at + self.spaces(at);
+            if self.char_at(next) == Some(',') {
+                next += 1;
+                next += self.spaces(next);
+            }
+            if let Some(and) = self.word(next, "and") {
+                next += and;
+                next += self.spaces(next);
+            }
+            match self.english_weekday(next, true) {
+                Some((day_len, day)) => {
+                    if !days.contains(&day) {
+                        days.push(day);
+                    }
+                    at = next + day_len;
+                }
+                None => break,
+            }
+        }
+        days.sort_by_key(|d| d.num_days_from_monday());
+        Some((at - pos, days))
+    }
+
+    fn match_priority(&mut self, pos: usize) -> Option<usize> {
+        // 连续的感叹号：两个为紧急，三个及以上为重要且紧急
+        let run = self.chars[pos..].iter().take_while(|c| **c == '!' || **c == '！').count();
+        if run >= 2 {
+            self.urgent = Some(true);
+            if run >= 3 {
+                self.important = Some(true);
+            }
+            return Some(run);
+        }
+        let (len, important, urgent) = PRIORITY_MARKERS
+            .iter()
+            .find_map(|(w, important, urgent)| self.word(pos, w).map(|len| (len, *important, *urgent)))?;
+        // 只识别句首、句末或用标点隔开的标记，“准备重要客户会议”里的“重要”是标题的一部分
+        if !self.separated_before(pos) && !self.separated_after(pos + len) {
+            return None;
+        }
+        if important.is_some() {
+            self.important = important;
+        }
+        if urgent.is_some() {
+            self.urgent = urgent;
+        }
+        Some(len)
+    }
+
+    // on friday、at 3pm、by friday、before 5pm
+    fn match_prefixed(&mut self, pos: usize) -> Option<usize> {
+        let (len, deadline) = if let Some(len) = self.any_word(pos, &["by", "before", "due"]) {
+            (len, true)
+        } else if let Some(len) = self.any_word(pos, &["on", "at"]) {
+            (len, false)
+        } else {
+            return None;
+        };
+        let gap = self.spaces(pos + len);
+        if gap == 0 {
+            return None;
+        }
+        let start = pos + len + gap;
+        let matched = self.match_date(start, true).or_else(|| self.match_clock(start))?;
+        if deadline {
+            self.deadline = true;
+        }
+        Some(start + matched - pos)
+    }
+
+    // 日期或时刻，后面跟“前”“之前”表示截止
+    fn match_when(&mut self, pos: usize) -> Option<usize> {
+        let len = self
+            .match_date(pos, false)
+            .or_else(|| self.match_period(pos))
+            .or_else(|| self.match_clock(pos))?;
+        let at = pos + len;
+        if let Some(suffix) = self.any_word(at, &["之前", "以前"]) {
+            self.deadline = true;
+            return Some(len + suffix);
+        }
+        if self.char_at(at) == Some('前')
+            && !self.char_at(at + 1).is_some_and(|c| NOT_DEADLINE_AFTER_QIAN.contains(c))
+        {
+            self.deadline = true;
+            return Some(len + 1);
+        }
+        Some(len)
+    }
+
+    // prefixed 表示前面有 on、by 等介词，这时才识别星期的缩写
+    fn match_date(&mut self, pos: usize, prefixed: bool) -> Option<usize> {
+        let today = self.today();
+        for (word, days, period) in RELATIVE_DAYS {
+            if let Some(len) = self.word(pos, word) {
+                self.date = Some(today + Duration::days(*days));
+                if period.is_some() {
+                    self.period = *period;
+                }
+                return Some(len);
+            }
+        }
+        self.match_weekday(pos, prefixed)
+            .or_else(|| self.match_month(pos))
+            .or_else(|| self.match_after(pos))
+            .or_else(|| self.match_day_of_month(pos))
+            .or_else(|| self.match_iso_date(pos))
+    }
+
+    // 周五、本周五、下周一、周末、next friday、weekend
+    fn match_weekday(&mut self, pos: usize, prefixed: bool) -> Option<usize> {
+        let today = self.today();
+        for (prefix, weeks) in WEEK_PREFIXES {
+            let Some(len) = self.word(pos, prefix) else { continue };
+            let target = match self.char_at(pos + len) {
+                Some('末') => Some(Weekday::Sat),
+                Some(c) => chinese_weekday(c),
+                None => None,
+            };
+            if let Some(weekday) = target {
+                self.date = Some(match weeks {
+                    Some(weeks) => week_day(today, *weeks, weekday),
+                    None => next_weekday(today, weekday),
+                });
+                return Some(len + 1);
+            }
+            // 只说“下周”时取下周一
+            if *weeks == Some(1) {
+                self.date = Some(week_day(today, 1, Weekday::Mon));
+                return Some(len);
+            }
+        }
+        for (prefix, weeks) in [("next", 1), ("this", 0)] {
+            let Some(len) = self.word(pos, prefix) else { continue };
+            let start = pos + len + self.spaces(pos + len);
+            if let Some((day_len, weekday)) = self.english_weekday(start, true) {
+                self.date = Some(week_day(today, weeks, weekday));
+                return Some(start + day_len - pos);
+            }
+            if let Some(day_len) = self.word(start, "weekend") {
+                self.date = Some(week_day(today, weeks, Weekday::Sat));
+                return Some(start + day_len - pos);
+            }
+            if weeks == 1 {
+                if let Some(day_len) = self.word(start, "week") {
+                    self.date = Some(week_day(today, 1, Weekday::Mon));
+                    return Some(start + day_len - pos);
+                }
+            }
+        }
+        if let Some(len) = self.word(pos, "weekend") {
+            self.date = Some(next_weekday(today, Weekday::Sat));
+            return Some(len);
+        }
+        let (len, weekday) = self.english_weekday(pos, prefixed)?;
+        self.date = Some(next_weekday(today, weekday));
+        Some(len)
+    }
+
+    // 月底、下月底、下个月5号、end of month、next month
+    fn match_month(&mut self, pos: usize) -> Option<usize> {
+        let today = self.today();
+        let next_month = today.checked_add_months(Months::new(1))?;
+        if let Some(len) = self.any_word(pos, &["本月底", "这个月底", "月底", "end of the month", "end of month", "eom"]) {
+            self.date = Some(month_day(today, -1));
+            return Some(len);
+        }
+        if let Some(len) = self.any_word(pos, &["下个月底", "下月底", "end of next month"]) {
+            self.date = Some(month_day(next_month, -1));
+            return Some(len);
+        }
+        if let Some(len) = self.any_word(pos, &["下个月", "下月"]) {
+            let at = pos + len;
+            if let Some((day, day_len)) = self.number(at).filter(|(d, _)| (1..=31).contains(d)) {
+                if let Some(unit) = self.any_word(at + day_len, &["号", "日"]) {
+                    self.date = Some(month_day(next_month, day as i32));
+                    return Some(len + day_len + unit);
+                }
+            }
+            self.date = Some(month_day(next_month, 1));
+            return Some(len);
+        }
+        let len = self.word(pos, "next month")?;
+        self.date = Some(month_day(next_month, 1));
+        Some(len)
+    }
+
+    // 3天后、两周后、2小时后、in 3 days、in 2 hours
+    fn match_after(&mut self, pos: usize) -> Option<usize> {
+        let (amount, len, unit) = if let Some(in_len) = self.word(pos, "in") {
+            let at = pos + in_len + self.spaces(pos + in_len);
+            let (amount, num_len) = match self.any_word(at, &["an", "a"]) {
+                Some(len) => (1, len),
+                None => self.number(at).filter(|_| self.char_at(at).is_some_and(|c| c.is_ascii_digit()))?,
+            };
+            let at = at + num_len + self.spaces(at + num_len);
+            const UNITS: &[(&str, char)] = &[
+                ("days", 'd'),
+                ("day", 'd'),
+                ("weeks", 'w'),
+                ("week", 'w'),
+                ("months", 'm'),
+                ("month", 'm'),
+                ("hours", 'h'),
+                ("hour", 'h'),
+                ("hrs", 'h'),
+                ("hr", 'h'),
+                ("minutes", 'n'),
+                ("minute", 'n'),
+                ("mins", 'n'),
+                ("min", 'n'),
+            ];
+            let (unit_len, unit) = UNITS.iter().find_map(|(w, u)| self.word(at, w).map(|l| (l, *u)))?;
+            (amount, at + unit_len - pos, unit)
+        } else {
+            let (amount, num_len) = self.number(pos)?;
+            let mut at = pos + num_len;
+            at += self.word(at, "个").unwrap_or(0);
+            const UNITS: &[(&str, char)] = &[
+                ("天", 'd'),
+                ("星期", 'w'),
+                ("礼拜", 'w'),
+                ("周", 'w'),
+                ("月", 'm'),
+                ("小时", 'h'),
+                ("钟头", 'h'),
+                ("分钟", 'n'),
+            ];
+            let (unit_len, unit) = UNITS.iter().find_map(|(w, u)| self.word(at, w).map(|l| (l, *u)))?;
+            at += unit_len;
+            let suffix = self.any_word(at, &["之后", "以后", "后"])?;
+            (amount, at + suffix - pos, unit)
+        };
+        let today = self.today();
+        match unit {
+            'd' => self.date = Some(today + Duration::days(amount as i64)),
+            'w' => self.date = Some(today + Duration::weeks(amount as i64)),
+            'm' => self.date = Some(today.checked_add_months(Months::new(amount))?),
+            'h' => self.offset = Some(Duration::hours(amount as i64)),
+            _ => self.offset = Some(Duration::minutes(amount as i64)),
+        }
+        Some(len)
+    }
+
+    // 5月20日、20号：已经过去的日期取下一年或下个月
+    fn match_day_of_month(&mut self, pos: usize) -> Option<usize> {
+        let today = self.today();
+        let (first, first_len) = self.number(pos)?;
+        let at = pos + first_len;
+        if let Some(month_len) = self.word(at, "月") {
+            let at = at + month_len;
+            let (day, day_len) = self.number(at)?;
+            let unit = self.any_word(at + day_len, &["日", "号"])?;
+            let date = NaiveDate::from_ymd_opt(today.year(), first, day)?;
+            self.date = Some(if date < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, first, day).unwrap_or(date)
+            } else {
+                date
+            });
+            return Some(at + day_len + unit - pos);
+        }
+        let unit = self.any_word(at, &["号", "日"])?;
+        if !(1..=31).contains(&first) {
+            return None;
+        }
+        // 本月没有这一天（如 31 号）时顺延到之后有这一天的月份
+        let date = (0..3).find_map(|months| {
+            let month = today.with_day(1)?.checked_add_months(Months::new(months))?;
+            month.with_day(first).filter(|d| *d >= today)
+        })?;
+        self.date = Some(date);
+        Some(first_len + unit)
+    }
+
+    // 2026-05-20、2026/05/20
+    fn match_iso_date(&mut self, pos: usize) -> Option<usize> {
+        let text: String = self.chars.get(pos..pos + 10)?.iter().collect();
+        let date = NaiveDate::parse_from_str(&text.replace('/', "-"), "%Y-%m-%d").ok()?;
+        if self.char_at(pos + 10).is_some_and(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        self.date = Some(date);
+        Some(10)
+    }
+
+    fn match_period(&mut self, pos: usize) -> Option<usize> {
+        let (len, period) = PERIODS
+            .iter()
+            .find_map(|(w, period)| self.word(pos, w).map(|len| (len, *period)))?;
+        self.period = Some(period);
+        Some(len)
+    }
+
+    // 3点、3点半、3点15分、15:30、3pm、3:30 pm、noon
+    fn match_clock(&mut self, pos: usize) -> Option<usize> {
+        if let Some(len) = self.word(pos, "noon") {
+            self.clock = Some(Clock { hour: 12, minute: 0, ambiguous: false });
+            return Some(len);
+        }
+        if pos > 0 && self.char_at(pos - 1).is_some_and(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let (hour, hour_len) = self.number(pos)?;
+        let mut at = pos + hour_len;
+        if let Some(dot) = self.any_word(at, &["点", "點"]) {
+            at += dot;
+            let mut minute = 0;
+            if let Some(half) = self.word(at, "半") {
+                minute = 30;
+                at += half;
+            } else if let Some(quarter) = self.word(at, "一刻") {
+                minute = 15;
+                at += quarter;
+            } else if let Some((m, m_len)) = self.number(at).filter(|(m, _)| *m < 60) {
+                minute = m;
+                at += m_len;
+                at += self.word(at, "分").unwrap_or(0);
+            }
+            at += self.word(at, "钟").unwrap_or(0);
+            if hour > 24 {
+                return None;
+            }
+            self.clock = Some(Clock { hour: hour % 24, minute, ambiguous: (1..12).contains(&hour) });
+            return Some(at - pos);
+        }
+        // 以下只接受阿拉伯数字
+        if !self.char_at(pos).is_some_and(|c| c.is_ascii_digit()) || hour_len > 2 {
+            return None;
+        }
+        let mut minute = None;
+        if self.char_at(at).is_some_and(|c| c == ':' || c == '：') {
+            let digits: String = self.chars.get(at + 1..at + 3)?.iter().collect();
+            if !digits.chars().all(|c| c.is_ascii_digit()) || self.char_at(at + 3).is_some_and(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            minute = Some(digits.parse::<u32>().ok()?);
+            at += 3;
+        }
+        let gap = self.spaces(at);
+        let meridiem = ["am", "a.m.", "pm", "p.m."]
+            .iter()
+            .find_map(|w| self.literal(at + gap, w).map(|len| (len, w.starts_with('p'))))
+            .filter(|(len, _)| !self.char_at(at + gap + len).is_some_and(|c| c.is_ascii_alphanumeric()));
+        let hour = match meridiem {
+            Some((len, pm)) => {
+                if !(1..=12).contains(&hour) {
+                    return None;
+                }
+                at += gap + len;
+                match (pm, hour) {
+                    (true, 12) => 12,
+                    (true, h) => h + 12,
+                    (false, 12) => 0,
+                    (false, h) => h,
+                }
+            }
+            None if minute.is_some() && hour < 24 => hour,
+            None => return None,
+        };
+        let minute = minute.unwrap_or(0);
+        if minute >= 60 {
+            return None;
+        }
+        self.clock = Some(Clock { hour, minute, ambiguous: false });
+        Some(at - pos)
+    }
+
+    fn level(&self) -> Priority {
+        // 与新建待办的默认值一致：重要不紧急
+        match (self.important.unwrap_or(true), self.urgent.unwrap_or(false)) {
+            (true, true) => Priority::ImportantUrgent,
+            (true, false) => Priority::ImportantNotUrgent,
+            (false, true) => Priority::NotImportantUrgent,
+            (false, false) => Priority::NotImportantNotUrgent,
+        }
+    }
+
+    // 时段把“3点”定为下午，只有时段时取默认时刻
+    fn clock_time(&self) -> Option<(NaiveTime, bool)> {
+        let (hour, minute, ambiguous) = match (self.clock, self.period) {
+            (Some(clock), Some(period)) => (period.adjust(clock.hour), clock.minute, false),
+            (Some(clock), None) => (clock.hour, clock.minute, clock.ambiguous),
+            (None, Some(period)) => (period.default_hour(), 0, false),
+            (None, None) => return None,
+        };
+        Some((NaiveTime::from_hms_opt(hour, minute, 0)?, ambiguous))
+    }
+
+    // 只有时刻时取之后最近的一次；“3点”这类没说上午下午的也考虑下午
+    fn next_time(&self, time: NaiveTime, ambiguous: bool) -> NaiveDateTime {
+        let today = self.today().and_time(time);
+        let mut candidates = vec![today];
+        if ambiguous {
+            candidates.push(today + Duration::hours(12));
+        }
+        candidates.push(today + Duration::days(1));
+        candidates.into_iter().find(|t| *t > self.now).unwrap_or(today)
+    }
+
+    // 开始时间、截止时间（本地时间）以及是否为全天
+    fn schedule(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>, bool) {
+        let today = self.today();
+        let (when, all_day) = match (self.offset, self.date, self.clock_time()) {
+            (Some(offset), _, _) => (Some(self.now + offset), false),
+            (None, Some(date), Some((time, ambiguous))) => (Some(date.and_time(daytime(time, ambiguous))), false),
+            (None, Some(date), None) => (Some(date.and_time(NaiveTime::MIN)), true),
+            (None, None, Some((time, ambiguous))) => (Some(self.next_time(time, ambiguous)), false),
+            (None, None, None) => match &self.repeat {
+                Some(repeat) => (Some(repeat.first_date(today).and_time(NaiveTime::MIN)), true),
+                None => (None, false),
+            },
+        };
+        match when {
+            // 截止时间之前的待办从现在开始
+            Some(deadline) if self.deadline => {
+                let start = if all_day { today.and_time(NaiveTime::MIN) } else { self.now };
+                (Some(start.min(deadline)), Some(deadline), all_day)
+            }
+            _ => (when, None, all_day),
+        }
+    }
+}
+
+// 指定日期时没说上午下午的钟点：1 到 6 点多半是下午
+fn daytime(time: NaiveTime, ambiguous: bool) -> NaiveTime {
+    if ambiguous && time.hour() < AFTERNOON_BEFORE_HOUR {
+        time + Duration::hours(12)
+    } else {
+        time
+    }
+}
+
+// 按时区 tz 解析，now 为当前时间戳（秒）
+pub fn parse_quick_add_in<Tz: TimeZone>(tz: &Tz, text: &str, now: i64) -> Result<AddTodoParams, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("内容不能为空".to_string());
+    }
+    let now = tz
+        .timestamp_opt(now, 0)
+        .single()
+        .ok_or("无效的时间")?
+        .naive_local();
+    let mut parser = QuickAddParser::new(text, now);
+    let title = parser.extract_title();
+    // 整句都被识别成时间等信息时保留原文作为标题
+    let title = if title.is_empty() { text.to_string() } else { title };
+    let rrule = normalize_rule(parser.repeat.as_ref().map(Repeat::rule).as_deref())?;
+    let (start, end, all_day) = parser.schedule();
+    let to_timestamp = |local: NaiveDateTime| local_to_timestamp(tz, local);
+    Ok(AddTodoParams::parsed(
+        title,
+        start.and_then(to_timestamp),
+        end.and_then(to_timestamp),
+        None,
+        parser.level(),
+        rrule,
+        all_day,
+    ))
+}
+
+// 解析快速添加的一句话，返回新建待办的参数供界面确认后再调用 add_todo
+#[tauri::command]
+pub async fn parse_quick_add(text: String) -> Result<AddTodoParams, String> {
+    parse_quick_add_in(&Local, &text, Utc::now().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Asia::Shanghai;
+    use serde_json::{json, Value};
+
+    // 2026-10-14 是周三
+    const NOW: &str = "2026-10-14 10:00";
+
+    fn local(s: &str) -> i64 {
+        let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        Shanghai.from_local_datetime(&naive).earliest().unwrap().timestamp()
+    }
+
+    fn parse(text: &str) -> Value {
+        serde_json::to_value(parse_quick_add_in(&Shanghai, text, local(NOW)).unwrap()).unwrap()
+    }
+
+    fn time(value: &Value) -> Option<String> {
+        value.as_i64().map(|ts| {
+            Shanghai.timestamp_opt(ts, 0).unwrap().format("%Y-%m-%d %H:%M").to_string()
+        })
+    }
+
+    #[test]
+    fn chinese_date_time_and_weekly_repeat() {
+        let todo = parse("明天下午3点交周报，每周五重复");
+        assert_eq!(todo["title"], "交周报");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-15 15:00"));
+        assert_eq!(todo["endTime"], Value::Null);
+        assert_eq!(todo["rrule"], "FREQ=WEEKLY;BYDAY=FR");
+        assert_eq!(todo["allDay"], false);
+        assert_eq!(todo["level"], 0);
+    }
+
+    #[test]
+    fn date_only_is_all_day() {
+        let todo = parse("下周一 提交报告 !!");
+        assert_eq!(todo["title"], "提交报告");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-19 00:00"));
+        assert_eq!(todo["allDay"], true);
+        assert_eq!(todo["level"], 1);
+
+        let todo = parse("周三 周报");
+        assert_eq!(todo["title"], "周报");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-14 00:00"));
+
+        let todo = parse("5月1日 出发");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2027-05-01 00:00"));
+        let todo = parse("20号交水费");
+        assert_eq!(todo["title"], "交水费");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-20 00:00"));
+    }
+
+    #[test]
+    fn deadline_suffix_sets_end_time() {
+        let todo = parse("月底前交房租，不重要");
+        assert_eq!(todo["title"], "交房租");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-14 00:00"));
+        assert_eq!(time(&todo["endTime"]).as_deref(), Some("2026-10-31 00:00"));
+        assert_eq!(todo["allDay"], true);
+        assert_eq!(todo["level"], 2);
+
+        let todo = parse("去前台取快递");
+        assert_eq!(todo["title"], "去前台取快递");
+        assert_eq!(todo["startTime"], Value::Null);
+    }
+
+    #[test]
+    fn time_only_picks_next_occurrence() {
+        // 10 点时“3点”是下午 3 点，“9点”是明天 9 点
+        assert_eq!(time(&parse("3点开会")["startTime"]).as_deref(), Some("2026-10-14 15:00"));
+        assert_eq!(time(&parse("上午9点半开会")["startTime"]).as_deref(), Some("2026-10-15 09:30"));
+        assert_eq!(time(&parse("晚上八点跑步")["startTime"]).as_deref(), Some("2026-10-14 20:00"));
+        assert_eq!(time(&parse("今晚看电影")["startTime"]).as_deref(), Some("2026-10-14 20:00"));
+        assert_eq!(time(&parse("两小时后喝水")["startTime"]).as_deref(), Some("2026-10-14 12:00"));
+    }
+
+    #[test]
+    fn english_phrases() {
+        let todo = parse("Call mom in 3 days at 5:30pm urgent");
+        assert_eq!(todo["title"], "Call mom");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-17 17:30"));
+        assert_eq!(todo["level"], 1);
+
+        let todo = parse("standup every mon and thu at 9am");
+        assert_eq!(todo["title"], "standup");
+        assert_eq!(todo["rrule"], "FREQ=WEEKLY;BYDAY=MO,TH");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-15 09:00"));
+
+        // 形容词 monthly 不表示重复
+        let todo = parse("Submit the monthly report by friday");
+        assert_eq!(todo["title"], "Submit the monthly report");
+        assert_eq!(todo["rrule"], Value::Null);
+        assert_eq!(time(&todo["endTime"]).as_deref(), Some("2026-10-16 00:00"));
+
+        let todo = parse("Daily standup notes");
+        assert_eq!(todo["title"], "Daily standup notes");
+        assert_eq!(todo["rrule"], Value::Null);
+
+        let todo = parse("water the plants weekly");
+        assert_eq!(todo["title"], "water the plants");
+        assert_eq!(todo["rrule"], "FREQ=WEEKLY");
+
+        let todo = parse("repeat monthly pay rent");
+        assert_eq!(todo["title"], "pay rent");
+        assert_eq!(todo["rrule"], "FREQ=MONTHLY");
+
+        let todo = parse("standup daily repeat at 9am");
+        assert_eq!(todo["title"], "standup");
+        assert_eq!(todo["rrule"], "FREQ=DAILY");
+    }
+
+    #[test]
+    fn priority_markers_inside_title_are_kept() {
+        let todo = parse("准备重要客户会议");
+        assert_eq!(todo["title"], "准备重要客户会议");
+        assert_eq!(todo["level"], 0);
+
+        let todo = parse("制定紧急预案");
+        assert_eq!(todo["title"], "制定紧急预案");
+        assert_eq!(todo["level"], 0);
+
+        let todo = parse("prepare urgent care kit");
+        assert_eq!(todo["title"], "prepare urgent care kit");
+        assert_eq!(todo["level"], 0);
+
+        let todo = parse("紧急：修复线上问题");
+        assert_eq!(todo["title"], "修复线上问题");
+        assert_eq!(todo["level"], 1);
+
+        let todo = parse("修复线上问题 紧急");
+        assert_eq!(todo["title"], "修复线上问题");
+        assert_eq!(todo["level"], 1);
+
+        let todo = parse("整理发票，不重要，不急");
+        assert_eq!(todo["title"], "整理发票");
+        assert_eq!(todo["level"], 2);
+    }
+
+    #[test]
+    fn ambiguous_hour_with_date_prefers_afternoon() {
+        assert_eq!(time(&parse("明天3点开会")["startTime"]).as_deref(), Some("2026-10-15 15:00"));
+        assert_eq!(time(&parse("周五两点半面试")["startTime"]).as_deref(), Some("2026-10-16 14:30"));
+        assert_eq!(time(&parse("明天9点开会")["startTime"]).as_deref(), Some("2026-10-15 09:00"));
+        assert_eq!(time(&parse("明天凌晨3点看球")["startTime"]).as_deref(), Some("2026-10-15 03:00"));
+        assert_eq!(time(&parse("明天15:00开会")["startTime"]).as_deref(), Some("2026-10-15 15:00"));
+        assert_eq!(time(&parse("tomorrow at 3am")["startTime"]).as_deref(), Some("2026-10-15 03:00"));
+    }
+
+    #[test]
+    fn weekday_abbreviations_need_a_prefix() {
+        let todo = parse("buy sun cream");
+        assert_eq!(todo["title"], "buy sun cream");
+        assert_eq!(todo["startTime"], Value::Null);
+
+        let todo = parse("sat exam prep");
+        assert_eq!(todo["title"], "sat exam prep");
+        assert_eq!(todo["startTime"], Value::Null);
+
+        let todo = parse("wed anniversary mon fri");
+        assert_eq!(todo["title"], "wed anniversary mon fri");
+        assert_eq!(todo["startTime"], Value::Null);
+
+        let todo = parse("buy sun cream on sat");
+        assert_eq!(todo["title"], "buy sun cream");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-17 00:00"));
+
+        let todo = parse("sat exam prep next mon");
+        assert_eq!(todo["title"], "sat exam prep");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-19 00:00"));
+
+        let todo = parse("gym friday");
+        assert_eq!(todo["title"], "gym");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-16 00:00"));
+    }
+
+    #[test]
+    fn repeat_only_starts_at_first_occurrence() {
+        let todo = parse("每月1号还信用卡");
+        assert_eq!(todo["title"], "还信用卡");
+        assert_eq!(todo["rrule"], "FREQ=MONTHLY;BYMONTHDAY=1");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-11-01 00:00"));
+        assert_eq!(todo["allDay"], true);
+
+        let todo = parse("每周一、三、五健身");
+        assert_eq!(todo["rrule"], "FREQ=WEEKLY;BYDAY=MO,WE,FR");
+        assert_eq!(time(&todo["startTime"]).as_deref(), Some("2026-10-14 00:00"));
+
+        assert_eq!(parse("工作日打卡")["rrule"], "FREQ=DAILY;X-WORKDAY=ONLY");
+    }
+
+    #[test]
+    fn plain_text_goes_to_inbox() {
+        let todo = parse("  买牛奶  ");
+        assert_eq!(
+            todo,
+            json!({
+                "title": "买牛奶",
+                "startTime": null,
+                "endTime": null,
+                "notes": null,
+                "level": 0,
+                "rrule": null,
+                "tagIds": null,
+                "projectId": null,
+                "parentId": null,
+                "autoComplete": false,
+                "allDay": false,
+            })
+        );
+        assert_eq!(parse("明天")["title"], "明天");
+        assert!(parse_quick_add_in(&Shanghai, "   ", local(NOW)).is_err());
+    }
+}
@@ -0,0 +1,820 @@
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use crate::holiday::WorkdayCalendar;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+// 连续多少个周期没有产生任何日期就认为规则无法再生成（例如 BYSETPOS 越界）
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// BYDAY 中的一项，例如 `TU`、`1MO`、`-1FR`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub nth: Option<i32>,
+    pub weekday: Weekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    Date(NaiveDate),         // 20270630
+    Local(NaiveDateTime),    // 20270630T180000
+    Utc(DateTime<Utc>),      // 20270630T100000Z
+}
+
+/// 扩展字段 X-WORKDAY：按节假日和调休安排调整日期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkdayMode {
+    Only, // 只保留工作日，例如 FREQ=DAILY;X-WORKDAY=ONLY 表示每个工作日
+    Prev, // 非工作日提前到之前最近的工作日
+    Next, // 非工作日顺延到之后最近的工作日
+}
+
+/// RFC 5545 RRULE 的子集：FREQ、INTERVAL、BYDAY、BYMONTHDAY、BYSETPOS、COUNT、UNTIL，
+/// 以及扩展字段 X-WORKDAY
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub by_set_pos: Vec<i32>,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub workday: Option<WorkdayMode>,
+}
+
+impl RecurrenceRule {
+    pub fn new(freq: Frequency) -> Self {
+        RecurrenceRule {
+            freq,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_set_pos: Vec::new(),
+            count: None,
+            until: None,
+            workday: None,
+        }
+    }
+
+    /// 从系列的第一次发生时间开始依次生成所有发生时间（第一次始终是 start 本身）
+    pub fn occurrences<'a>(&'a self, calendar: &'a WorkdayCalendar, start: NaiveDateTime) -> Occurrences<'a> {
+        Occurrences {
+            rule: self,
+            calendar,
+            start,
+            period: 0,
+            buffer: VecDeque::new(),
+            emitted: 0,
+            empty_periods: 0,
+        }
+    }
+
+    /// 计算系列中严格晚于 after 的下一次发生时间（时间戳，秒），系列结束时返回 None
+    ///
+    /// 按用户本地时区的墙上时间推算，跨越夏令时切换时保持相同的钟点。
+    pub fn next_after(&self, calendar: &WorkdayCalendar, series_start: i64, after: i64) -> Option<i64> {
+        self.next_after_in(&Local, calendar, series_start, after)
+    }
+
+    pub fn next_after_in<Tz: TimeZone>(
+        &self,
+        tz: &Tz,
+        calendar: &WorkdayCalendar,
+        series_start: i64,
+        after: i64,
+    ) -> Option<i64> {
+        let start = tz.timestamp_opt(series_start, 0).single()?.naive_local();
+
+        for occurrence in self.occurrences(calendar, start) {
+            let timestamp = local_to_timestamp(tz, occurrence)?;
+            if self.is_past_until(occurrence, timestamp) {
+                return None;
+            }
+            if timestamp > after {
+                return Some(timestamp);
+            }
+        }
+
+        None
+    }
+
+    fn is_past_until(&self, occurrence: NaiveDateTime, timestamp: i64) -> bool {
+        match self.until {
+            Some(Until::Date(date)) => occurrence.date() > date,
+            Some(Until::Local(until)) => occurrence > until,
+            Some(Until::Utc(until)) => timestamp > until.timestamp(),
+            None => false,
+        }
+    }
+
+    // 生成第 period 个周期内的所有候选日期（已排序并应用 BYSETPOS 和 X-WORKDAY）
+    fn period_dates(&self, calendar: &WorkdayCalendar, anchor: NaiveDate, period: i64) -> Vec<NaiveDate> {
+        let step = period * self.interval as i64;
+        // 只取工作日且没有 BYDAY/BYMONTHDAY 时，候选为整个周期的每一天，再由 BYSETPOS 挑选
+        let whole_period = self.workday == Some(WorkdayMode::Only)
+            && self.by_day.is_empty()
+            && self.by_month_day.is_empty();
+        let mut dates = match self.freq {
+            Frequency::Daily => {
+                let day = anchor + Duration::days(step);
+                if self.matches_filters(day) {
+                    vec![day]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let week_start = anchor
+                    - Duration::days(anchor.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(step);
+                let weekdays: Vec<Weekday> = if whole_period {
+                    vec![
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                        Weekday::Sat,
+                        Weekday::Sun,
+                    ]
+                } else if self.by_day.is_empty() {
+                    vec![anchor.weekday()]
+                } else {
+                    self.by_day.iter().map(|d| d.weekday).collect()
+                };
+                weekdays
+                    .into_iter()
+                    .map(|w| week_start + Duration::days(w.num_days_from_monday() as i64))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let total = anchor.year() as i64 * 12 + anchor.month0() as i64 + step;
+                let year = total.div_euclid(12) as i32;
+                let month = total.rem_euclid(12) as u32 + 1;
+                if whole_period {
+                    month_days(year, month)
+                } else {
+                    self.month_dates(anchor, year, month)
+                }
+            }
+            Frequency::Yearly => {
+                let year = anchor.year() + step as i32;
+                if whole_period {
+                    year_days(year)
+                } else if self.by_day.is_empty() && self.by_month_day.is_empty() {
+                    // 2 月 29 日在平年取 2 月 28 日
+                    let day = anchor.day().min(days_in_month(year, anchor.month()));
+                    NaiveDate::from_ymd_opt(year, anchor.month(), day)
+                        .into_iter()
+                        .collect()
+                } else if self.by_month_day.is_empty() {
+                    self.by_day
+                        .iter()
+                        .flat_map(|by_day| weekdays_in_range(year_days(year), *by_day))
+                        .collect()
+                } else {
+                    (1..=12)
+                        .flat_map(|month| self.month_dates(anchor, year, month))
+                        .collect()
+                }
+            }
+        };
+
+        if self.workday == Some(WorkdayMode::Only) {
+            dates.retain(|date| calendar.is_workday(*date));
+        }
+
+        dates.sort();
+        dates.dedup();
+        let mut dates = self.apply_set_pos(dates);
+
+        match self.workday {
+            Some(WorkdayMode::Prev) => dates = dates.into_iter().filter_map(|d| calendar.previous_workday(d)).collect(),
+            Some(WorkdayMode::Next) => dates = dates.into_iter().filter_map(|d| calendar.next_workday(d)).collect(),
+            _ => {}
+        }
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    fn month_dates(&self, anchor: NaiveDate, year: i32, month: u32) -> Vec<NaiveDate> {
+        let days = month_days(year, month);
+
+        if !self.by_month_day.is_empty() {
+            self.by_month_day
+                .iter()
+                .filter_map(|&day| resolve_month_day(year, month, day))
+                .filter(|date| {
+                    self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|by_day| weekdays_in_range(days.clone(), *by_day).contains(date))
+                })
+                .collect()
+        } else if !self.by_day.is_empty() {
+            self.by_day
+                .iter()
+                .flat_map(|by_day| weekdays_in_range(days.clone(), *by_day))
+                .collect()
+        } else {
+            // 锚点日期在本月不存在时取月末，例如 1 月 31 日 → 2 月 28/29 日 → 3 月 31 日
+            let day = anchor.day().min(days_in_month(year, month));
+            NaiveDate::from_ymd_opt(year, month, day)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    // 按天重复时 BYDAY / BYMONTHDAY 只做过滤
+    fn matches_filters(&self, date: NaiveDate) -> bool {
+        let day_ok = self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == date.weekday());
+        let month_day_ok = self.by_month_day.is_empty()
+            || self
+                .by_month_day
+                .iter()
+                .any(|&day| resolve_month_day(date.year(), date.month(), day) == Some(date));
+        day_ok && month_day_ok
+    }
+
+    fn apply_set_pos(&self, dates: Vec<NaiveDate>) -> Vec<NaiveDate> {
+        if self.by_set_pos.is_empty() {
+            return dates;
+        }
+
+        let len = dates.len() as i32;
+        let mut selected: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|&pos| {
+                let index = if pos > 0 { pos - 1 } else { len + pos };
+                if index >= 0 && index < len {
+                    Some(dates[index as usize])
+                } else {
+                    None
+                }
+            })
+            .collect();
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+}
+
+pub struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    calendar: &'a WorkdayCalendar,
+    start: NaiveDateTime,
+    period: i64,
+    buffer: VecDeque<NaiveDateTime>,
+    emitted: u32,
+    empty_periods: u32,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<NaiveDateTime> {
+        if let Some(count) = self.rule.count {
+            if self.emitted >= count {
+                return None;
+            }
+        }
+
+        // DTSTART 本身总是第一次发生
+        if self.emitted == 0 {
+            self.emitted = 1;
+            return Some(self.start);
+        }
+
+        loop {
+            if let Some(occurrence) = self.buffer.pop_front() {
+                if occurrence <= self.start {
+                    continue;
+                }
+                self.emitted += 1;
+                return Some(occurrence);
+            }
+
+            let dates = self.rule.period_dates(self.calendar, self.start.date(), self.period);
+            self.period += 1;
+
+            if dates.is_empty() {
+                self.empty_periods += 1;
+                if self.empty_periods > MAX_EMPTY_PERIODS {
+                    return None;
+                }
+                continue;
+            }
+
+            self.empty_periods = 0;
+            let time = self.start.time();
+            self.buffer.extend(dates.into_iter().map(|date| date.and_time(time)));
+        }
+    }
+}
+
+// 本地时间转时间戳：重复的时刻（夏令时结束）取较早的一个，
+// 不存在的时刻（夏令时开始）顺延到切换之后的第一个有效时刻
+pub(crate) fn local_to_timestamp<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<i64> {
+    let mut candidate = local;
+    for _ in 0..=(24 * 4) {
+        match tz.from_local_datetime(&candidate) {
+            LocalResult::Single(dt) => return Some(dt.timestamp()),
+            LocalResult::Ambiguous(earliest, _) => return Some(earliest.timestamp()),
+            LocalResult::None => candidate += Duration::minutes(15),
+        }
+    }
+    None
+}
+
+fn month_days(year: i32, month: u32) -> Vec<NaiveDate> {
+    (1..=days_in_month(year, month))
+        .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .collect()
+}
+
+fn year_days(year: i32) -> Vec<NaiveDate> {
+    (1..=12).flat_map(|month| month_days(year, month)).collect()
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|date| date.pred_opt())
+        .map(|date| date.day())
+        .unwrap_or(28)
+}
+
+// 负数表示从月末倒数，-1 即最后一天；不存在的日期（如 2 月 30 日）返回 None
+fn resolve_month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    let last = days_in_month(year, month) as i32;
+    let day = if day < 0 { last + day + 1 } else { day };
+    if day < 1 || day > last {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year, month, day as u32)
+}
+
+// 在给定日期范围（一个月或一年）内展开 BYDAY，nth 为负数时从末尾数
+fn weekdays_in_range(days: Vec<NaiveDate>, by_day: ByDay) -> Vec<NaiveDate> {
+    let matching: Vec<NaiveDate> = days.into_iter().filter(|d| d.weekday() == by_day.weekday).collect();
+    match by_day.nth {
+        None => matching,
+        Some(n) => {
+            let len = matching.len() as i32;
+            let index = if n > 0 { n - 1 } else { len + n };
+            if index >= 0 && index < len {
+                vec![matching[index as usize]]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+pub(crate) fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_int_list(key: &str, value: &str) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|item| {
+            item.trim()
+                .parse::<i32>()
+                .map_err(|_| format!("{} 的值无效: {}", key, item))
+        })
+        .collect()
+}
+
+fn parse_until(value: &str) -> Result<Until, String> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let dt = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map_err(|_| format!("UNTIL 的值无效: {}", value))?;
+        return Ok(Until::Utc(dt.and_utc()));
+    }
+    if value.contains('T') {
+        let dt = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .map_err(|_| format!("UNTIL 的值无效: {}", value))?;
+        return Ok(Until::Local(dt));
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .map(Until::Date)
+        .map_err(|_| format!("UNTIL 的值无效: {}", value))
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_uppercase();
+        let s = s.strip_prefix("RRULE:").unwrap_or(&s);
+
+        let mut freq = None;
+        let mut rule = RecurrenceRule::new(Frequency::Daily);
+
+        for part in s.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("重复规则格式无效: {}", part))?;
+            let key = key.trim();
+            let value = value.trim();
+
+            match key {
+                "FREQ" => {
+                    freq = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("不支持的重复频率: {}", value)),
+                    });
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|i| *i >= 1)
+                        .ok_or_else(|| format!("INTERVAL 的值无效: {}", value))?;
+                }
+                "BYDAY" => {
+                    for item in value.split(',') {
+                        let item = item.trim();
+                        if item.len() < 2 {
+                            return Err(format!("BYDAY 的值无效: {}", item));
+                        }
+                        let (nth, code) = item.split_at(item.len() - 2);
+                        let weekday = parse_weekday(code).ok_or_else(|| format!("BYDAY 的值无效: {}", item))?;
+                        let nth = if nth.is_empty() {
+                            None
+                        } else {
+                            let n = nth
+                                .trim_start_matches('+')
+                                .parse::<i32>()
+                                .map_err(|_| format!("BYDAY 的值无效: {}", item))?;
+                            if n == 0 || n.abs() > 53 {
+                                return Err(format!("BYDAY 的值无效: {}", item));
+                            }
+                            Some(n)
+                        };
+                        rule.by_day.push(ByDay { nth, weekday });
+                    }
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = parse_int_list("BYMONTHDAY", value)?;
+                    if rule.by_month_day.iter().any(|d| *d == 0 || d.abs() > 31) {
+                        return Err(format!("BYMONTHDAY 的值无效: {}", value));
+                    }
+                }
+                "BYSETPOS" => {
+                    rule.by_set_pos = parse_int_list("BYSETPOS", value)?;
+                    if rule.by_set_pos.iter().any(|p| *p == 0 || p.abs() > 366) {
+                        return Err(format!("BYSETPOS 的值无效: {}", value));
+                    }
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|c| *c >= 1)
+                            .ok_or_else(|| format!("COUNT 的值无效: {}", value))?,
+                    );
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                "X-WORKDAY" => {
+                    rule.workday = Some(match value {
+                        "ONLY" => WorkdayMode::Only,
+                        "PREV" => WorkdayMode::Prev,
+                        "NEXT" => WorkdayMode::Next,
+                        _ => return Err(format!("X-WORKDAY 的值无效: {}", value)),
+                    });
+                }
+                "WKST" if value == "MO" => {}
+                _ => return Err(format!("不支持的重复规则字段: {}", key)),
+            }
+        }
+
+        rule.freq = freq.ok_or("重复规则缺少 FREQ")?;
+
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT 和 UNTIL 不能同时使用".to_string());
+        }
+        if !rule.by_set_pos.is_empty()
+            && rule.by_day.is_empty()
+            && rule.by_month_day.is_empty()
+            && rule.workday != Some(WorkdayMode::Only)
+        {
+            return Err("BYSETPOS 需要与 BYDAY、BYMONTHDAY 或 X-WORKDAY=ONLY 一起使用".to_string());
+        }
+        if rule.freq == Frequency::Weekly && !rule.by_month_day.is_empty() {
+            return Err("按周重复时不能使用 BYMONTHDAY".to_string());
+        }
+        if matches!(rule.freq, Frequency::Daily | Frequency::Weekly)
+            && rule.by_day.iter().any(|d| d.nth.is_some())
+        {
+            return Err("只有按月或按年重复时 BYDAY 才能带序号".to_string());
+        }
+
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|d| match d.nth {
+                    Some(n) => format!("{}{}", n, weekday_code(d.weekday)),
+                    None => weekday_code(d.weekday).to_string(),
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if !self.by_set_pos.is_empty() {
+            let positions: Vec<String> = self.by_set_pos.iter().map(|p| p.to_string()).collect();
+            write!(f, ";BYSETPOS={}", positions.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        match self.until {
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.format("%Y%m%d"))?,
+            Some(Until::Local(dt)) => write!(f, ";UNTIL={}", dt.format("%Y%m%dT%H%M%S"))?,
+            Some(Until::Utc(dt)) => write!(f, ";UNTIL={}", dt.format("%Y%m%dT%H%M%SZ"))?,
+            None => {}
+        }
+        match self.workday {
+            Some(WorkdayMode::Only) => write!(f, ";X-WORKDAY=ONLY")?,
+            Some(WorkdayMode::Prev) => write!(f, ";X-WORKDAY=PREV")?,
+            Some(WorkdayMode::Next) => write!(f, ";X-WORKDAY=NEXT")?,
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// 旧版 cycle 字段（以及快捷的 workday）到 RRULE 的映射，one 表示不重复
+pub fn legacy_cycle_to_rule(cycle: &str) -> Option<Option<&'static str>> {
+    match cycle {
+        "one" | "" => Some(None),
+        "day" => Some(Some("FREQ=DAILY")),
+        "week" => Some(Some("FREQ=WEEKLY")),
+        "month" => Some(Some("FREQ=MONTHLY")),
+        "year" => Some(Some("FREQ=YEARLY")),
+        "workday" => Some(Some("FREQ=DAILY;X-WORKDAY=ONLY")),
+        _ => None,
+    }
+}
+
+/// 校验并规范化前端传入的重复规则，兼容旧版 one/day/week/month/year
+pub fn normalize_rule(input: Option<&str>) -> Result<Option<String>, String> {
+    let input = match input {
+        Some(s) => s.trim(),
+        None => return Ok(None),
+    };
+
+    if let Some(legacy) = legacy_cycle_to_rule(input) {
+        return Ok(legacy.map(|rule| rule.to_string()));
+    }
+
+    input.parse::<RecurrenceRule>().map(|rule| Some(rule.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+    use chrono_tz::Asia::Shanghai;
+
+    fn local<Tz: TimeZone>(tz: &Tz, s: &str) -> i64 {
+        let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        tz.from_local_datetime(&naive).earliest().unwrap().timestamp()
+    }
+
+    // 依次取出系列的前 n 次（含第一次），以本地时间表示
+    fn series<Tz: TimeZone>(tz: &Tz, rule: &str, start: &str, n: usize) -> Vec<String> {
+        series_with(tz, &WorkdayCalendar::default(), rule, start, n)
+    }
+
+    fn series_with<Tz: TimeZone>(
+        tz: &Tz,
+        calendar: &WorkdayCalendar,
+        rule: &str,
+        start: &str,
+        n: usize,
+    ) -> Vec<String> {
+        let rule: RecurrenceRule = rule.parse().unwrap();
+        let series_start = local(tz, start);
+        let mut result = vec![series_start];
+        while result.len() < n {
+            match rule.next_after_in(tz, calendar, series_start, *result.last().unwrap()) {
+                Some(next) => result.push(next),
+                None => break,
+            }
+        }
+        result
+            .into_iter()
+            .map(|ts| tz.timestamp_opt(ts, 0).unwrap().naive_local().format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn monthly_clamps_to_month_end_and_returns_to_anchor() {
+        assert_eq!(
+            series(&Shanghai, "FREQ=MONTHLY", "2026-01-31 09:00", 4),
+            ["2026-01-31 09:00", "2026-02-28 09:00", "2026-03-31 09:00", "2026-04-30 09:00"]
+        );
+        assert_eq!(
+            series(&Shanghai, "FREQ=MONTHLY", "2028-01-31 09:00", 3),
+            ["2028-01-31 09:00", "2028-02-29 09:00", "2028-03-31 09:00"]
+        );
+        assert_eq!(
+            series(&Shanghai, "FREQ=MONTHLY", "2026-12-31 09:00", 3),
+            ["2026-12-31 09:00", "2027-01-31 09:00", "2027-02-28 09:00"]
+        );
+    }
+
+    #[test]
+    fn yearly_handles_leap_years() {
+        assert_eq!(
+            series(&Shanghai, "FREQ=YEARLY", "2028-02-29 08:00", 5),
+            ["2028-02-29 08:00", "2029-02-28 08:00", "2030-02-28 08:00", "2031-02-28 08:00", "2032-02-29 08:00"]
+        );
+        // 跨过闰日不会像加 365 天那样漂移
+        assert_eq!(
+            series(&Shanghai, "FREQ=YEARLY", "2027-03-01 08:00", 3),
+            ["2027-03-01 08:00", "2028-03-01 08:00", "2029-03-01 08:00"]
+        );
+    }
+
+    #[test]
+    fn keeps_wall_clock_time_across_dst() {
+        // 2027-03-14 美东进入夏令时，2027-11-07 退出
+        assert_eq!(
+            series(&New_York, "FREQ=DAILY", "2027-03-13 09:00", 3),
+            ["2027-03-13 09:00", "2027-03-14 09:00", "2027-03-15 09:00"]
+        );
+        assert_eq!(
+            series(&New_York, "FREQ=WEEKLY", "2027-11-01 09:00", 2),
+            ["2027-11-01 09:00", "2027-11-08 09:00"]
+        );
+
+        let rule: RecurrenceRule = "FREQ=DAILY".parse().unwrap();
+        let start = local(&New_York, "2027-03-13 09:00");
+        assert_eq!(rule.next_after_in(&New_York, &WorkdayCalendar::default(), start, start), Some(start + 23 * 3600));
+    }
+
+    #[test]
+    fn nonexistent_and_ambiguous_local_times() {
+        // 02:30 在进入夏令时当天不存在，顺延到 03:00
+        assert_eq!(
+            series(&New_York, "FREQ=DAILY", "2027-03-13 02:30", 3),
+            ["2027-03-13 02:30", "2027-03-14 03:00", "2027-03-15 02:30"]
+        );
+        // 01:30 在退出夏令时当天出现两次，取较早的一次
+        let rule: RecurrenceRule = "FREQ=DAILY".parse().unwrap();
+        let start = local(&New_York, "2027-11-06 01:30");
+        assert_eq!(rule.next_after_in(&New_York, &WorkdayCalendar::default(), start, start), Some(start + 24 * 3600));
+    }
+
+    #[test]
+    fn weekday_and_set_pos_rules() {
+        assert_eq!(
+            series(&Shanghai, "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR", "2026-10-16 09:00", 3),
+            ["2026-10-16 09:00", "2026-10-19 09:00", "2026-10-20 09:00"]
+        );
+        assert_eq!(
+            series(&Shanghai, "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH", "2026-10-13 09:00", 4),
+            ["2026-10-13 09:00", "2026-10-15 09:00", "2026-10-27 09:00", "2026-10-29 09:00"]
+        );
+        assert_eq!(
+            series(&Shanghai, "FREQ=MONTHLY;BYDAY=-1FR", "2026-10-30 18:00", 3),
+            ["2026-10-30 18:00", "2026-11-27 18:00", "2026-12-25 18:00"]
+        );
+        assert_eq!(
+            series(&Shanghai, "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1", "2026-10-30 18:00", 3),
+            ["2026-10-30 18:00", "2026-11-30 18:00", "2026-12-31 18:00"]
+        );
+    }
+
+    #[test]
+    fn count_and_until_end_the_series() {
+        assert_eq!(series(&Shanghai, "FREQ=DAILY;COUNT=3", "2026-10-30 09:00", 10).len(), 3);
+        assert_eq!(
+            series(&Shanghai, "FREQ=MONTHLY;UNTIL=20270105", "2026-10-31 09:00", 10),
+            ["2026-10-31 09:00", "2026-11-30 09:00", "2026-12-31 09:00"]
+        );
+    }
+
+    #[test]
+    fn parses_and_normalizes_rules() {
+        assert_eq!(normalize_rule(Some("one")), Ok(None));
+        assert_eq!(normalize_rule(Some("month")), Ok(Some("FREQ=MONTHLY".to_string())));
+        assert_eq!(
+            normalize_rule(Some("rrule:freq=weekly;interval=2;byday=tu,th;count=10")),
+            Ok(Some("FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH;COUNT=10".to_string()))
+        );
+        assert!(normalize_rule(Some("FREQ=DAILY;COUNT=2;UNTIL=20270101")).is_err());
+        assert!(normalize_rule(Some("FREQ=WEEKLY;BYDAY=1MO")).is_err());
+        assert!(normalize_rule(Some("FREQ=HOURLY")).is_err());
+    }
+
+    // 2025 年国庆中秋：10 月 1 日至 8 日放假，9 月 28 日（周日）和 10 月 11 日（周六）调休上班
+    fn national_day_2025() -> WorkdayCalendar {
+        let mut calendar = WorkdayCalendar::default();
+        for day in 1..=8 {
+            calendar.set(NaiveDate::from_ymd_opt(2025, 10, day).unwrap(), true);
+        }
+        calendar.set(NaiveDate::from_ymd_opt(2025, 9, 28).unwrap(), false);
+        calendar.set(NaiveDate::from_ymd_opt(2025, 10, 11).unwrap(), false);
+        calendar
+    }
+
+    #[test]
+    fn workday_rules_follow_holiday_calendar() {
+        let calendar = national_day_2025();
+        assert_eq!(
+            series_with(&Shanghai, &calendar, "FREQ=DAILY;X-WORKDAY=ONLY", "2025-09-26 09:30", 8),
+            [
+                "2025-09-26 09:30",
+                "2025-09-28 09:30",
+                "2025-09-29 09:30",
+                "2025-09-30 09:30",
+                "2025-10-09 09:30",
+                "2025-10-10 09:30",
+                "2025-10-11 09:30",
+                "2025-10-13 09:30",
+            ]
+        );
+        // 每月第一个工作日
+        assert_eq!(
+            series_with(&Shanghai, &calendar, "FREQ=MONTHLY;X-WORKDAY=ONLY;BYSETPOS=1", "2025-09-01 10:00", 3),
+            ["2025-09-01 10:00", "2025-10-09 10:00", "2025-11-03 10:00"]
+        );
+        // 每月 25 日及之前的最后一个工作日
+        assert_eq!(
+            series_with(&Shanghai, &calendar, "FREQ=MONTHLY;BYMONTHDAY=25;X-WORKDAY=PREV", "2025-09-25 10:00", 4),
+            ["2025-09-25 10:00", "2025-10-24 10:00", "2025-11-25 10:00", "2025-12-25 10:00"]
+        );
+    }
+
+    #[test]
+    fn single_rest_day_counts_saturday_as_workday() {
+        let calendar = WorkdayCalendar::new("single");
+        assert_eq!(
+            series_with(&Shanghai, &calendar, "FREQ=DAILY;X-WORKDAY=ONLY", "2026-10-16 09:00", 3),
+            ["2026-10-16 09:00", "2026-10-17 09:00", "2026-10-19 09:00"]
+        );
+    }
+}
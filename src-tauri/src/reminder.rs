@@ -0,0 +1,412 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_notification::NotificationExt;
+use tokio::time::{interval, Duration};
+
+// 提醒的锚点时间：start 为开始时间，end 为截止时间；全天待办截止到截止日期当天结束
+const ANCHOR_TIME: &str = "CASE WHEN r.anchor = 'start' THEN t.start_time
+    WHEN t.all_day THEN CAST(strftime('%s', t.end_time, 'unixepoch', 'localtime', '+1 day', 'utc') AS INTEGER)
+    ELSE t.end_time END";
+
+// 超过这个秒数才触发的提醒视为应用关闭期间错过的
+const MISSED_THRESHOLD_SECS: i64 = 60;
+
+// “明天早上”稍后提醒的时间
+const TOMORROW_MORNING: (u32, u32) = (9, 0);
+
+// 稍后提醒的预设时长
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum SnoozePreset {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "tomorrow")]
+    TomorrowMorning,
+}
+
+impl SnoozePreset {
+    // 计算稍后提醒的时间戳（秒）
+    pub fn until(self, now: DateTime<Local>) -> i64 {
+        match self {
+            SnoozePreset::FiveMinutes => (now + ChronoDuration::minutes(5)).timestamp(),
+            SnoozePreset::OneHour => (now + ChronoDuration::hours(1)).timestamp(),
+            SnoozePreset::TomorrowMorning => {
+                let (hour, minute) = TOMORROW_MORNING;
+                let morning = (now.date_naive() + ChronoDuration::days(1))
+                    .and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap());
+                Local
+                    .from_local_datetime(&morning)
+                    .earliest()
+                    .map(|t| t.timestamp())
+                    .unwrap_or_else(|| (now + ChronoDuration::days(1)).timestamp())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TodoReminder {
+    pub id: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    pub anchor: String, // start, end
+    #[serde(rename = "offsetMinutes")]
+    pub offset_minutes: i64, // 相对锚点的分钟数，负数表示提前
+    #[serde(rename = "remindAt")]
+    pub remind_at: Option<i64>, // 按当前开始/截止时间计算的提醒时间戳（秒）
+    #[serde(rename = "firedAt")]
+    pub fired_at: Option<i64>,
+    #[serde(rename = "snoozedUntil")]
+    pub snoozed_until: Option<i64>,
+    #[serde(rename = "acknowledgedAt")]
+    pub acknowledged_at: Option<i64>,
+    #[serde(rename = "nagMinutes")]
+    pub nag_minutes: Option<i64>, // 持续提醒间隔（分钟），仅对重要且紧急的待办生效
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TodoReminderEvent {
+    #[serde(rename = "reminderId")]
+    pub reminder_id: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    pub title: String,
+    pub anchor: String,
+    #[serde(rename = "offsetMinutes")]
+    pub offset_minutes: i64,
+    #[serde(rename = "anchorTime")]
+    pub anchor_time: i64,
+    #[serde(rename = "remindAt")]
+    pub remind_at: i64,
+    #[sqlx(default)]
+    pub missed: bool, // 应用关闭期间错过、启动后补发的提醒
+    #[sqlx(default)]
+    pub repeat: bool, // 稍后提醒或持续提醒再次弹出
+}
+
+#[derive(serde::Deserialize)]
+pub struct AddReminderParams {
+    #[serde(rename = "todoId")]
+    todo_id: i64,
+    anchor: String,
+    #[serde(rename = "offsetMinutes")]
+    offset_minutes: i64,
+    #[serde(rename = "nagMinutes")]
+    nag_minutes: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn add_todo_reminder(pool: State<'_, SqlitePool>, params: AddReminderParams) -> Result<i64, String> {
+    if params.anchor != "start" && params.anchor != "end" {
+        return Err(format!("无效的提醒时间点: {}", params.anchor));
+    }
+    if matches!(params.nag_minutes, Some(m) if m < 1) {
+        return Err("持续提醒间隔至少为1分钟".to_string());
+    }
+
+    let anchor_time = sqlx::query_scalar::<_, Option<i64>>(&format!(
+        "SELECT {} FROM (SELECT ? AS anchor) r JOIN todos t WHERE t.id = ? AND t.status != 2",
+        ANCHOR_TIME
+    ))
+    .bind(&params.anchor)
+    .bind(params.todo_id)
+    .fetch_optional(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "待办不存在".to_string())?
+    .ok_or_else(|| if params.anchor == "start" { "该待办尚未安排开始时间" } else { "该待办没有截止时间" }.to_string())?;
+
+    // 提醒时间已经过去时视为已提醒，避免刚添加就弹出
+    let now = Utc::now().timestamp();
+    let fired_for = (anchor_time + params.offset_minutes * 60 <= now).then_some(anchor_time);
+
+    let result = sqlx::query(
+        "INSERT INTO todo_reminders (todo_id, anchor, offset_minutes, fired_for, nag_minutes) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(params.todo_id)
+    .bind(params.anchor)
+    .bind(params.offset_minutes)
+    .bind(fired_for)
+    .bind(params.nag_minutes)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn get_todo_reminders(pool: State<'_, SqlitePool>, todo_id: i64) -> Result<Vec<TodoReminder>, String> {
+    sqlx::query_as::<_, TodoReminder>(&format!(
+        "SELECT r.id, r.todo_id, r.anchor, r.offset_minutes, ({}) + r.offset_minutes * 60 AS remind_at, r.fired_at,
+                r.snoozed_until, r.acknowledged_at, r.nag_minutes, r.created_at
+         FROM todo_reminders r JOIN todos t ON t.id = r.todo_id
+         WHERE r.todo_id = ? ORDER BY remind_at",
+        ANCHOR_TIME
+    ))
+    .bind(todo_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_todo_reminder(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM todo_reminders WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn snooze_todo_reminder(pool: State<'_, SqlitePool>, id: i64, preset: SnoozePreset) -> Result<i64, String> {
+    let until = preset.until(Local::now());
+    let result = sqlx::query("UPDATE todo_reminders SET snoozed_until = ?, acknowledged_at = NULL WHERE id = ? AND fired_at IS NOT NULL")
+        .bind(until)
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("提醒不存在或尚未触发".to_string());
+    }
+    Ok(until)
+}
+
+#[tauri::command]
+pub async fn acknowledge_todo_reminder(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("UPDATE todo_reminders SET acknowledged_at = ?, snoozed_until = NULL WHERE id = ?")
+        .bind(Utc::now().timestamp())
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 取出需要弹出的提醒并标记为已提醒，包括三种情况：
+// 1. 到期且尚未针对当前开始/截止时间提醒过（时间被修改或循环任务进入下一次后会再次生效）
+// 2. 稍后提醒的时间已到
+// 3. 重要且紧急的待办开启了持续提醒，且未确认、距上次提醒已超过间隔
+pub async fn take_due_reminders(pool: &SqlitePool, now: i64) -> Result<Vec<TodoReminderEvent>, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let mut due = sqlx::query_as::<_, TodoReminderEvent>(&format!(
+        "SELECT * FROM (
+            SELECT r.id AS reminder_id, r.todo_id, t.title, t.level, r.anchor, r.offset_minutes, r.fired_for, r.fired_at,
+                   r.snoozed_until, r.acknowledged_at, r.nag_minutes,
+                   ({anchor}) AS anchor_time, ({anchor}) + r.offset_minutes * 60 AS remind_at,
+                   (r.fired_for IS NOT NULL AND r.fired_for = ({anchor})) AS repeat
+            FROM todo_reminders r JOIN todos t ON t.id = r.todo_id
+            WHERE t.status = 0
+        )
+        WHERE anchor_time IS NOT NULL AND (
+            (NOT repeat AND remind_at <= ?1)
+            OR (repeat AND acknowledged_at IS NULL AND snoozed_until IS NOT NULL AND snoozed_until <= ?1)
+            OR (repeat AND acknowledged_at IS NULL AND snoozed_until IS NULL AND level = 1
+                AND nag_minutes IS NOT NULL AND fired_at + nag_minutes * 60 <= ?1)
+        )
+        ORDER BY remind_at",
+        anchor = ANCHOR_TIME
+    ))
+    .bind(now)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for reminder in due.iter_mut() {
+        reminder.missed = !reminder.repeat && now - reminder.remind_at > MISSED_THRESHOLD_SECS;
+        sqlx::query("UPDATE todo_reminders SET fired_for = ?, fired_at = ?, snoozed_until = NULL, acknowledged_at = NULL WHERE id = ?")
+            .bind(reminder.anchor_time)
+            .bind(now)
+            .bind(reminder.reminder_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(due)
+}
+
+// 通知正文，如“15分钟后截止 · 18:00”
+fn describe_reminder(reminder: &TodoReminderEvent) -> String {
+    let action = if reminder.anchor == "start" { "开始" } else { "截止" };
+    let relative = match reminder.offset_minutes {
+        0 => format!("现在{}", action),
+        m if m < 0 => format!("{}分钟后{}", -m, action),
+        m => format!("已{}{}分钟", action, m),
+    };
+    let time = Local
+        .timestamp_opt(reminder.anchor_time, 0)
+        .single()
+        .map(|t| t.format("%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+
+    if reminder.missed {
+        format!("{} · {}（错过的提醒）", relative, time)
+    } else if reminder.repeat {
+        format!("{} · {}（再次提醒）", relative, time)
+    } else {
+        format!("{} · {}", relative, time)
+    }
+}
+
+// 后台提醒调度，启动时会补发应用关闭期间错过的提醒（每条只补发一次）
+pub fn start_reminder_scheduler(app_handle: AppHandle, pool: SqlitePool) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(15));
+
+        loop {
+            interval.tick().await;
+
+            let due = match take_due_reminders(&pool, Utc::now().timestamp()).await {
+                Ok(due) => due,
+                Err(e) => {
+                    eprintln!("Failed to load due reminders: {}", e);
+                    continue;
+                }
+            };
+
+            for reminder in due {
+                if let Err(e) = app_handle
+                    .notification()
+                    .builder()
+                    .title(&reminder.title)
+                    .body(describe_reminder(&reminder))
+                    .show()
+                {
+                    eprintln!("Failed to show reminder notification: {}", e);
+                }
+
+                if let Err(e) = app_handle.emit("todo-reminder", reminder) {
+                    eprintln!("Failed to emit todo-reminder event: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_todo(pool: &SqlitePool, start_time: i64, level: i64) -> i64 {
+        sqlx::query("INSERT INTO todos (title, start_time, level) VALUES ('写周报', ?, ?)")
+            .bind(start_time)
+            .bind(level)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn add_reminder(pool: &SqlitePool, todo_id: i64, offset_minutes: i64, nag_minutes: Option<i64>) -> i64 {
+        sqlx::query("INSERT INTO todo_reminders (todo_id, anchor, offset_minutes, nag_minutes) VALUES (?, 'start', ?, ?)")
+            .bind(todo_id)
+            .bind(offset_minutes)
+            .bind(nag_minutes)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn execute(pool: &SqlitePool, sql: &str, value: i64) {
+        sqlx::query(sql).bind(value).execute(pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn missed_reminder_fires_once() {
+        let pool = pool().await;
+        let todo_id = add_todo(&pool, 10_000, 0).await;
+        add_reminder(&pool, todo_id, -10, None).await;
+
+        assert!(take_due_reminders(&pool, 9_399).await.unwrap().is_empty());
+
+        // 应用关闭期间错过的提醒在启动后补发一次
+        let due = take_due_reminders(&pool, 20_000).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].anchor_time, due[0].remind_at), (10_000, 9_400));
+        assert!(due[0].missed);
+        assert!(!due[0].repeat);
+        assert!(take_due_reminders(&pool, 20_015).await.unwrap().is_empty());
+        assert!(take_due_reminders(&pool, 30_000).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reminder_fires_again_after_time_changes() {
+        let pool = pool().await;
+        let todo_id = add_todo(&pool, 10_000, 0).await;
+        add_reminder(&pool, todo_id, 0, None).await;
+
+        let due = take_due_reminders(&pool, 10_030).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(!due[0].missed);
+
+        // 已经针对这个开始时间提醒过，开始时间改变后重新生效
+        execute(&pool, "UPDATE todos SET start_time = ?", 50_000).await;
+        assert!(take_due_reminders(&pool, 49_999).await.unwrap().is_empty());
+        let due = take_due_reminders(&pool, 50_000).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].anchor_time, 50_000);
+        assert!(!due[0].repeat);
+
+        // 已完成的待办不再提醒
+        execute(&pool, "UPDATE todos SET start_time = ?, status = 1", 60_000).await;
+        assert!(take_due_reminders(&pool, 60_000).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn snoozed_reminder_fires_again() {
+        let pool = pool().await;
+        let todo_id = add_todo(&pool, 10_000, 0).await;
+        let id = add_reminder(&pool, todo_id, 0, None).await;
+        take_due_reminders(&pool, 10_000).await.unwrap();
+
+        execute(&pool, "UPDATE todo_reminders SET snoozed_until = ?", 10_300).await;
+        assert!(take_due_reminders(&pool, 10_299).await.unwrap().is_empty());
+        let due = take_due_reminders(&pool, 10_300).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].reminder_id, id);
+        assert!(due[0].repeat);
+        assert!(!due[0].missed);
+
+        // 弹出后清除稍后提醒，不会重复弹出
+        assert!(take_due_reminders(&pool, 20_000).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn nag_until_acknowledged_for_urgent_todos() {
+        let pool = pool().await;
+        let urgent = add_todo(&pool, 10_000, 1).await;
+        let normal = add_todo(&pool, 10_000, 0).await;
+        let urgent_reminder = add_reminder(&pool, urgent, 0, Some(5)).await;
+        add_reminder(&pool, normal, 0, Some(5)).await;
+        assert_eq!(take_due_reminders(&pool, 10_000).await.unwrap().len(), 2);
+
+        // 只有重要且紧急的待办持续提醒
+        assert!(take_due_reminders(&pool, 10_299).await.unwrap().is_empty());
+        let due = take_due_reminders(&pool, 10_300).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].reminder_id, urgent_reminder);
+        assert!(due[0].repeat);
+        assert!(take_due_reminders(&pool, 10_599).await.unwrap().is_empty());
+        assert_eq!(take_due_reminders(&pool, 10_600).await.unwrap().len(), 1);
+
+        execute(&pool, "UPDATE todo_reminders SET acknowledged_at = ?", 10_700).await;
+        assert!(take_due_reminders(&pool, 20_000).await.unwrap().is_empty());
+    }
+}
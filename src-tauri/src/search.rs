@@ -0,0 +1,354 @@
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use crate::dependency::attach_blocked;
+use crate::tag::attach_tags;
+use crate::todo::{Todo, TODO_SELECT};
+
+// trigram 分词的最短可检索长度，更短的关键词（如两个汉字）改用 LIKE 匹配
+const TRIGRAM_LEN: usize = 3;
+// 高亮标记先用控制字符占位，转义 HTML 后再替换为 <mark>
+const MARK_START: char = '\u{2}';
+const MARK_END: char = '\u{3}';
+const SNIPPET_CHARS: usize = 24;
+const DEFAULT_LIMIT: i64 = 50;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoSearchResult {
+    pub todo: Todo,
+    #[serde(rename = "titleHighlight")]
+    pub title_highlight: String, // 已转义的 HTML，匹配部分用 <mark> 包裹
+    #[serde(rename = "notesSnippet")]
+    pub notes_snippet: Option<String>, // 备注中匹配位置附近的片段，格式同上
+}
+
+#[derive(FromRow)]
+struct SearchHit {
+    id: i64,
+    title: String,
+    notes: Option<String>,
+}
+
+// 按空白拆分关键词，关键词之间为“且”的关系
+pub(crate) fn split_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        if !terms.iter().any(|t| t == term) {
+            terms.push(term.to_string());
+        }
+    }
+    terms
+}
+
+// 子串匹配已经包含了前缀匹配，关键词作为短语传给 trigram 分词器
+fn fts_phrase(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+pub(crate) fn like_pattern(term: &str) -> String {
+    let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            MARK_START => escaped.push_str("<mark>"),
+            MARK_END => escaped.push_str("</mark>"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+// 找出所有关键词（忽略大小写）在文本中的位置，返回按字符计的区间
+fn match_ranges(chars: &[char], terms: &[String]) -> Vec<(usize, usize)> {
+    let folded: Vec<char> = chars.iter().map(|c| fold(*c)).collect();
+    let mut ranges = Vec::new();
+    for term in terms {
+        let needle: Vec<char> = term.chars().map(fold).collect();
+        if needle.is_empty() || needle.len() > folded.len() {
+            continue;
+        }
+        for start in 0..=folded.len() - needle.len() {
+            if folded[start..start + needle.len()] == needle[..] {
+                ranges.push((start, start + needle.len()));
+            }
+        }
+    }
+
+    // 合并重叠的区间
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn mark(chars: &[char], ranges: &[(usize, usize)], from: usize, to: usize) -> String {
+    let mut marked = String::new();
+    for (i, c) in chars.iter().enumerate().take(to).skip(from) {
+        if ranges.iter().any(|r| r.0 == i) {
+            marked.push(MARK_START);
+        }
+        marked.push(*c);
+        if ranges.iter().any(|r| r.1 == i + 1) {
+            marked.push(MARK_END);
+        }
+    }
+    marked
+}
+
+fn highlight(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let ranges = match_ranges(&chars, terms);
+    escape_html(&mark(&chars, &ranges, 0, chars.len()))
+}
+
+// 截取第一个匹配位置附近的片段，没有匹配时截取开头
+fn snippet(text: &str, terms: &[String]) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return None;
+    }
+    let ranges = match_ranges(&chars, terms);
+    let first = ranges.first().map(|r| r.0).unwrap_or(0);
+    let from = first.saturating_sub(SNIPPET_CHARS / 2);
+    let to = (from + SNIPPET_CHARS).min(chars.len());
+
+    let mut result = mark(&chars, &ranges, from, to);
+    // 截断处的匹配补上结束标记
+    if result.matches(MARK_START).count() > result.matches(MARK_END).count() {
+        result.push(MARK_END);
+    }
+    if from > 0 {
+        result.insert(0, '…');
+    }
+    if to < chars.len() {
+        result.push('…');
+    }
+    Some(escape_html(&result))
+}
+
+// 搜索待办的标题和备注。关键词之间为“且”，每个关键词按子串匹配（包含前缀匹配），
+// 不少于三个字符的关键词走 FTS5 trigram 索引并按相关度排序，更短的关键词用 LIKE 过滤
+#[tauri::command]
+pub async fn search_todos(
+    pool: State<'_, SqlitePool>,
+    query: String,
+    include_completed: Option<bool>,
+    include_deleted: Option<bool>,
+    limit: Option<i64>,
+) -> Result<Vec<TodoSearchResult>, String> {
+    find_todos(
+        pool.inner(),
+        &query,
+        include_completed.unwrap_or(true),
+        include_deleted.unwrap_or(false),
+        limit.unwrap_or(DEFAULT_LIMIT),
+    )
+    .await
+}
+
+async fn find_todos(
+    pool: &SqlitePool,
+    query: &str,
+    include_completed: bool,
+    include_deleted: bool,
+    limit: i64,
+) -> Result<Vec<TodoSearchResult>, String> {
+    let terms = split_terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (long_terms, short_terms): (Vec<&String>, Vec<&String>) =
+        terms.iter().partition(|t| t.chars().count() >= TRIGRAM_LEN);
+
+    let mut statuses = vec!["0"];
+    if include_completed {
+        statuses.push("1");
+    }
+    if include_deleted {
+        statuses.push("2");
+    }
+
+    let mut conditions = vec![format!("t.status IN ({})", statuses.join(","))];
+    for _ in &short_terms {
+        conditions.push("(t.title LIKE ? ESCAPE '\\' OR t.notes LIKE ? ESCAPE '\\')".to_string());
+    }
+
+    let sql = if long_terms.is_empty() {
+        format!(
+            "SELECT t.id, t.title, t.notes FROM todos t WHERE {} ORDER BY t.updated_at DESC LIMIT ?",
+            conditions.join(" AND ")
+        )
+    } else {
+        conditions.insert(0, "todos_fts MATCH ?".to_string());
+        format!(
+            "SELECT t.id, t.title, t.notes FROM todos_fts JOIN todos t ON t.id = todos_fts.rowid
+             WHERE {} ORDER BY bm25(todos_fts) LIMIT ?",
+            conditions.join(" AND ")
+        )
+    };
+
+    let mut hits_query = sqlx::query_as::<_, SearchHit>(&sql);
+    if !long_terms.is_empty() {
+        let match_expr = long_terms.iter().map(|t| fts_phrase(t)).collect::<Vec<_>>().join(" AND ");
+        hits_query = hits_query.bind(match_expr);
+    }
+    for term in &short_terms {
+        let pattern = like_pattern(term);
+        hits_query = hits_query.bind(pattern.clone()).bind(pattern);
+    }
+    let hits = hits_query
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if hits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ids = hits.iter().map(|h| h.id.to_string()).collect::<Vec<_>>().join(",");
+    let mut todos = sqlx::query_as::<_, Todo>(&format!("{} WHERE id IN ({})", TODO_SELECT, ids))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    attach_tags(pool, &mut todos).await?;
+    attach_blocked(pool, &mut todos).await?;
+
+    // 保持相关度顺序
+    let mut results = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let Some(index) = todos.iter().position(|t| t.id == hit.id) else {
+            continue;
+        };
+        results.push(TodoSearchResult {
+            todo: todos.swap_remove(index),
+            title_highlight: highlight(&hit.title, &terms),
+            notes_snippet: hit.notes.as_deref().and_then(|notes| snippet(notes, &terms)),
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(query: &str) -> Vec<String> {
+        split_terms(query)
+    }
+
+    #[test]
+    fn highlight_escapes_html_around_marks() {
+        assert_eq!(
+            highlight("<b>Tom & Jerry's</b>", &terms("tom jerry")),
+            "&lt;b&gt;<mark>Tom</mark> &amp; <mark>Jerry</mark>&#39;s&lt;/b&gt;"
+        );
+        // 关键词本身含有需要转义的字符
+        assert_eq!(highlight("a<b", &terms("<")), "a<mark>&lt;</mark>b");
+        // 重叠的匹配合并为一段
+        assert_eq!(highlight("banana", &terms("ana nan")), "b<mark>anana</mark>");
+        assert_eq!(highlight("report", &terms("meeting")), "report");
+    }
+
+    #[test]
+    fn highlight_cjk_text() {
+        assert_eq!(highlight("准备重要客户会议", &terms("客户")), "准备重要<mark>客户</mark>会议");
+        assert_eq!(highlight("和Alice讨论方案", &terms("alice 方案")), "和<mark>Alice</mark>讨论<mark>方案</mark>");
+    }
+
+    #[test]
+    fn snippet_around_first_match() {
+        let notes = format!("{}客户要求周五前给出报价{}", "前".repeat(30), "后".repeat(30));
+        let found = snippet(&notes, &terms("报价")).unwrap();
+        assert!(found.starts_with('…'));
+        assert!(found.ends_with('…'));
+        assert!(found.contains("<mark>报价</mark>"));
+        assert_eq!(found.chars().filter(|c| *c != '…').count() - "<mark></mark>".len(), SNIPPET_CHARS);
+
+        // 截断处的匹配补上结束标记
+        let notes = format!("{}abcdefghijklmnopqrstuvwxyz", "x".repeat(20));
+        let found = snippet(&notes, &terms("hijklmnopqrstuvwxyz")).unwrap();
+        assert_eq!(found.matches("<mark>").count(), found.matches("</mark>").count());
+
+        assert_eq!(snippet("短备注", &terms("没有")).as_deref(), Some("短备注"));
+        assert_eq!(snippet("", &terms("任何")), None);
+    }
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        for (title, notes, status) in [
+            ("准备重要客户会议", Some("带上报价单"), 0),
+            ("Quarterly report", Some("50% done"), 0),
+            ("客户回访", None, 1),
+            ("Team lunch", Some("500 budget"), 2),
+        ] {
+            sqlx::query("INSERT INTO todos (title, notes, start_time, status) VALUES (?, ?, 0, ?)")
+                .bind(title)
+                .bind(notes)
+                .bind(status)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    async fn search(pool: &SqlitePool, query: &str, include_completed: bool, include_deleted: bool) -> Vec<String> {
+        let mut titles = find_todos(pool, query, include_completed, include_deleted, DEFAULT_LIMIT)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.todo.title)
+            .collect::<Vec<_>>();
+        titles.sort();
+        titles
+    }
+
+    #[tokio::test]
+    async fn short_terms_fall_back_to_like() {
+        let pool = pool().await;
+        // 两个汉字不够 trigram 的长度，用 LIKE 匹配
+        assert_eq!(search(&pool, "客户", true, false).await, ["准备重要客户会议", "客户回访"]);
+        assert_eq!(search(&pool, "客户", false, false).await, ["准备重要客户会议"]);
+        // % 按字面匹配
+        assert_eq!(search(&pool, "0%", true, true).await, ["Quarterly report"]);
+        assert!(search(&pool, "", true, true).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn long_terms_use_fts_index() {
+        let pool = pool().await;
+        assert_eq!(search(&pool, "客户会议", true, false).await, ["准备重要客户会议"]);
+        assert_eq!(search(&pool, "QUARTER", true, false).await, ["Quarterly report"]);
+        // 备注也参与搜索
+        assert_eq!(search(&pool, "报价单", true, false).await, ["准备重要客户会议"]);
+        // 长短关键词混合时同时满足
+        assert_eq!(search(&pool, "客户 会议", true, false).await, ["准备重要客户会议"]);
+        assert!(search(&pool, "客户 lunch", true, true).await.is_empty());
+        // 回收站中的待办默认不出现
+        assert!(search(&pool, "lunch", true, false).await.is_empty());
+        assert_eq!(search(&pool, "lunch", true, true).await, ["Team lunch"]);
+
+        let results = find_todos(&pool, "客户会议", true, false, DEFAULT_LIMIT).await.unwrap();
+        assert_eq!(results[0].title_highlight, "准备重要<mark>客户会议</mark>");
+        assert_eq!(results[0].notes_snippet.as_deref(), Some("带上报价单"));
+    }
+}
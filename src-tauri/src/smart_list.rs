@@ -0,0 +1,259 @@
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use crate::query::{count_todos, query_todo_page, PageParams, TodoFilter, TodoPage};
+use crate::todo::{nullable, process_expired_todos};
+
+const SMART_LIST_SELECT: &str = "SELECT id, name, icon, color, filter, sort_order, created_at, updated_at FROM smart_lists";
+
+#[derive(FromRow)]
+struct SmartListRow {
+    id: i64,
+    name: String,
+    icon: Option<String>,
+    color: Option<String>,
+    filter: String,
+    sort_order: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+// 保存的查询视图，如“过期的重要任务”“本周无截止时间”
+#[derive(Debug, Clone, Serialize)]
+pub struct SmartList {
+    pub id: i64,
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    pub filter: TodoFilter,
+    #[serde(rename = "sortOrder")]
+    pub sort_order: i64,
+    pub count: i64, // 当前符合条件的待办数，用于角标
+    pub error: Option<String>, // 保存的条件无法解析时的原因，此时数量为 0
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmartListCount {
+    pub id: i64,
+    pub count: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AddSmartListParams {
+    name: String,
+    icon: Option<String>,
+    color: Option<String>,
+    filter: TodoFilter,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateSmartListParams {
+    id: i64,
+    name: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    icon: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    color: Option<Option<String>>,
+    filter: Option<TodoFilter>,
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("清单名称不能为空".to_string());
+    }
+    Ok(name.to_string())
+}
+
+// 数据库中的条件无法解析时（如旧版本保存的字段已不再支持）返回错误，不能当作空条件匹配所有待办
+fn parse_filter(row: &SmartListRow) -> Result<TodoFilter, String> {
+    serde_json::from_str(&row.filter).map_err(|e| format!("智能清单「{}」的条件无法解析: {}", row.name, e))
+}
+
+async fn load_rows(pool: &SqlitePool) -> Result<Vec<SmartListRow>, String> {
+    sqlx::query_as::<_, SmartListRow>(&format!("{} ORDER BY sort_order, id", SMART_LIST_SELECT))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn load_row(pool: &SqlitePool, id: i64) -> Result<SmartListRow, String> {
+    sqlx::query_as::<_, SmartListRow>(&format!("{} WHERE id = ?", SMART_LIST_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Smart list not found".to_string())
+}
+
+// 获取所有智能清单及其当前数量
+#[tauri::command]
+pub async fn get_smart_lists(pool: State<'_, SqlitePool>) -> Result<Vec<SmartList>, String> {
+    process_expired_todos(pool.inner()).await?;
+    let mut lists = Vec::new();
+    for row in load_rows(pool.inner()).await? {
+        // 单个清单的条件损坏时不影响其他清单的加载
+        let (filter, count, error) = match parse_filter(&row) {
+            Ok(filter) => {
+                let count = count_todos(pool.inner(), &filter).await?;
+                (filter, count, None)
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                (TodoFilter::default(), 0, Some(e))
+            }
+        };
+        lists.push(SmartList {
+            id: row.id,
+            name: row.name,
+            icon: row.icon,
+            color: row.color,
+            filter,
+            sort_order: row.sort_order,
+            count,
+            error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        });
+    }
+    Ok(lists)
+}
+
+// 只返回各清单的数量，供托盘和主窗口刷新角标
+#[tauri::command]
+pub async fn get_smart_list_counts(pool: State<'_, SqlitePool>) -> Result<Vec<SmartListCount>, String> {
+    process_expired_todos(pool.inner()).await?;
+    let mut counts = Vec::new();
+    for row in load_rows(pool.inner()).await? {
+        let count = match parse_filter(&row) {
+            Ok(filter) => count_todos(pool.inner(), &filter).await?,
+            Err(e) => {
+                eprintln!("{}", e);
+                0
+            }
+        };
+        counts.push(SmartListCount { id: row.id, count });
+    }
+    Ok(counts)
+}
+
+#[tauri::command]
+pub async fn add_smart_list(pool: State<'_, SqlitePool>, params: AddSmartListParams) -> Result<i64, String> {
+    let name = validate_name(&params.name)?;
+    let filter = serde_json::to_string(&params.filter).map_err(|e| e.to_string())?;
+
+    // 新清单排在最后
+    let result = sqlx::query(
+        "INSERT INTO smart_lists (name, icon, color, filter, sort_order) VALUES (?, ?, ?, ?, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM smart_lists))"
+    )
+    .bind(name)
+    .bind(params.icon)
+    .bind(params.color)
+    .bind(filter)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_smart_list(pool: State<'_, SqlitePool>, params: UpdateSmartListParams) -> Result<(), String> {
+    let mut row = load_row(pool.inner(), params.id).await?;
+
+    if let Some(name) = params.name {
+        row.name = validate_name(&name)?;
+    }
+    if let Some(icon) = params.icon {
+        row.icon = icon;
+    }
+    if let Some(color) = params.color {
+        row.color = color;
+    }
+    if let Some(filter) = params.filter {
+        row.filter = serde_json::to_string(&filter).map_err(|e| e.to_string())?;
+    }
+
+    sqlx::query("UPDATE smart_lists SET name = ?, icon = ?, color = ?, filter = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(&row.name)
+        .bind(&row.icon)
+        .bind(&row.color)
+        .bind(&row.filter)
+        .bind(row.id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_smart_list(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM smart_lists WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 按传入的顺序重新排列智能清单
+#[tauri::command]
+pub async fn reorder_smart_lists(pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (index, id) in ids.iter().enumerate() {
+        sqlx::query("UPDATE smart_lists SET sort_order = ?, updated_at = datetime('now') WHERE id = ?")
+            .bind(index as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 按清单保存的条件查询一页待办
+#[tauri::command]
+pub async fn evaluate_smart_list(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    page: Option<PageParams>,
+) -> Result<TodoPage, String> {
+    let row = load_row(pool.inner(), id).await?;
+    process_expired_todos(pool.inner()).await?;
+    let filter = parse_filter(&row)?;
+    query_todo_page(pool.inner(), &filter, &page.unwrap_or_default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(filter: &str) -> SmartListRow {
+        SmartListRow {
+            id: 1,
+            name: "过期的重要任务".to_string(),
+            icon: None,
+            color: None,
+            filter: filter.to_string(),
+            sort_order: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn broken_filter_is_an_error() {
+        let filter = parse_filter(&row(r#"{"levels":[1],"overdue":true}"#)).unwrap();
+        assert_eq!(filter.levels, Some(vec![1]));
+        assert!(filter.overdue);
+
+        // 不再支持的字段值不能退化为匹配所有待办的空条件
+        let error = parse_filter(&row(r#"{"sort":"dueDate"}"#)).unwrap_err();
+        assert!(error.contains("过期的重要任务"));
+        assert!(parse_filter(&row("not json")).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+
+// 所有完成记录：非循环待办按完成时间，循环待办按每一次完成的记录；
+// 循环任务每一次的截止时间按计划开始时间加上待办的时长推算；全天待办截止到截止日期当天结束
+const COMPLETIONS_CTE: &str = "WITH raw_completions AS (
+        SELECT level, completed_at, end_time, all_day FROM todos
+        WHERE rrule IS NULL AND status = 1 AND completed_at IS NOT NULL
+        UNION ALL
+        SELECT t.level, CAST(strftime('%s', o.recorded_at) AS INTEGER),
+               CASE WHEN t.end_time IS NULL THEN NULL ELSE o.scheduled_time + (t.end_time - t.start_time) END, t.all_day
+        FROM todo_occurrences o JOIN todos t ON t.id = o.todo_id
+        WHERE o.status = 'done' AND t.status != 2
+    ),
+    completions AS (
+        SELECT level, completed_at,
+               CASE WHEN all_day THEN CAST(strftime('%s', end_time, 'unixepoch', 'localtime', '+1 day', 'utc') AS INTEGER)
+                    ELSE end_time END AS end_time
+        FROM raw_completions
+    ),
+    ranged AS (SELECT * FROM completions WHERE completed_at >= ? AND completed_at < ?)";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatisticsRange {
+    pub from: i64, // 开始时间戳（秒），含
+    pub to: i64,   // 结束时间戳（秒），不含
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PeriodStatistics {
+    pub period: String, // 日期、周一日期或 YYYY-MM
+    pub completed: i64,
+    #[serde(rename = "onTime")]
+    pub on_time: i64,
+    pub late: i64,
+}
+
+// 每个优先级（四象限）的统计
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LevelStatistics {
+    pub level: i64,
+    pub completed: i64,
+    pub share: f64, // 占区间内全部完成数的比例
+    pub late: i64,
+    #[serde(rename = "averageDelay")]
+    pub average_delay: Option<f64>, // 有截止时间的完成中平均推迟的秒数，按时完成记为 0
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoStatistics {
+    pub from: i64,
+    pub to: i64,
+    pub completed: i64,
+    #[serde(rename = "onTime")]
+    pub on_time: i64,
+    pub late: i64,
+    #[serde(rename = "noDeadline")]
+    pub no_deadline: i64,
+    pub daily: Vec<PeriodStatistics>,
+    pub weekly: Vec<PeriodStatistics>,
+    pub monthly: Vec<PeriodStatistics>,
+    pub levels: Vec<LevelStatistics>,
+    #[serde(rename = "currentStreak")]
+    pub current_streak: i64, // 截至今天（今天还没完成时截至昨天）连续有完成的天数
+    #[serde(rename = "longestStreak")]
+    pub longest_streak: i64,
+}
+
+async fn period_statistics(pool: &SqlitePool, range: &StatisticsRange, period: &str) -> Result<Vec<PeriodStatistics>, String> {
+    sqlx::query_as::<_, PeriodStatistics>(&format!(
+        "{} SELECT {} AS period, COUNT(*) AS completed,
+                COALESCE(SUM(completed_at <= end_time), 0) AS on_time,
+                COALESCE(SUM(completed_at > end_time), 0) AS late
+         FROM ranged GROUP BY period ORDER BY period",
+        COMPLETIONS_CTE, period
+    ))
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 完成情况统计，日期按本地时间划分
+#[tauri::command]
+pub async fn get_todo_statistics(pool: State<'_, SqlitePool>, range: StatisticsRange) -> Result<TodoStatistics, String> {
+    build_statistics(pool.inner(), &range).await
+}
+
+async fn build_statistics(pool: &SqlitePool, range: &StatisticsRange) -> Result<TodoStatistics, String> {
+    if range.to <= range.from {
+        return Err("结束时间必须晚于开始时间".to_string());
+    }
+
+    let daily = period_statistics(pool, range, "date(completed_at, 'unixepoch', 'localtime')").await?;
+    // 'weekday 0' 移到本周日（当天是周日则不动），再减 6 天即为周一
+    let weekly = period_statistics(pool, range, "date(completed_at, 'unixepoch', 'localtime', 'weekday 0', '-6 days')").await?;
+    let monthly = period_statistics(pool, range, "strftime('%Y-%m', completed_at, 'unixepoch', 'localtime')").await?;
+
+    let (completed, on_time, late, no_deadline) = sqlx::query_as::<_, (i64, i64, i64, i64)>(&format!(
+        "{} SELECT COUNT(*), COALESCE(SUM(completed_at <= end_time), 0), COALESCE(SUM(completed_at > end_time), 0),
+                COALESCE(SUM(end_time IS NULL), 0)
+         FROM ranged",
+        COMPLETIONS_CTE
+    ))
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let levels = sqlx::query_as::<_, LevelStatistics>(&format!(
+        "{}, levels(level) AS (VALUES (0), (1), (2), (3))
+         SELECT l.level, COUNT(r.completed_at) AS completed,
+                COALESCE(COUNT(r.completed_at) * 1.0 / NULLIF((SELECT COUNT(*) FROM ranged), 0), 0.0) AS share,
+                COALESCE(SUM(r.completed_at > r.end_time), 0) AS late,
+                AVG(CASE WHEN r.end_time IS NOT NULL THEN MAX(r.completed_at - r.end_time, 0) END) AS average_delay
+         FROM levels l LEFT JOIN ranged r ON r.level = l.level
+         GROUP BY l.level ORDER BY l.level",
+        COMPLETIONS_CTE
+    ))
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // 连续天数不受统计区间限制：日期减去序号相同的为同一段连续日期
+    let (longest_streak, current_streak) = sqlx::query_as::<_, (i64, i64)>(&format!(
+        "{}, days AS (SELECT DISTINCT date(completed_at, 'unixepoch', 'localtime') AS day FROM completions),
+         islands AS (SELECT day, julianday(day) - ROW_NUMBER() OVER (ORDER BY day) AS grp FROM days),
+         streaks AS (SELECT MAX(day) AS last_day, COUNT(*) AS length FROM islands GROUP BY grp)
+         SELECT COALESCE(MAX(length), 0),
+                COALESCE((SELECT MAX(length) FROM streaks WHERE last_day >= date('now', 'localtime', '-1 day')), 0)
+         FROM streaks",
+        COMPLETIONS_CTE
+    ))
+    .bind(range.from)
+    .bind(range.to)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(TodoStatistics {
+        from: range.from,
+        to: range.to,
+        completed,
+        on_time,
+        late,
+        no_deadline,
+        daily,
+        weekly,
+        monthly,
+        levels,
+        current_streak,
+        longest_streak,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::local_day_start;
+
+    const HOUR: i64 = 60 * 60;
+    const DAY: i64 = 24 * HOUR;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    // 插入一条已完成的非循环待办
+    async fn add_completed(pool: &SqlitePool, level: i64, end_time: Option<i64>, all_day: bool, completed_at: i64) {
+        sqlx::query("INSERT INTO todos (title, start_time, end_time, all_day, level, status, completed_at) VALUES ('t', ?, ?, ?, ?, 1, ?)")
+            .bind(end_time.unwrap_or(completed_at))
+            .bind(end_time)
+            .bind(all_day)
+            .bind(level)
+            .bind(completed_at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn add_occurrence(pool: &SqlitePool, todo_id: i64, scheduled_time: i64, status: &str, recorded_at: i64) {
+        sqlx::query("INSERT INTO todo_occurrences (todo_id, scheduled_time, actual_start, status, recorded_at) VALUES (?, ?, ?, ?, datetime(?, 'unixepoch'))")
+            .bind(todo_id)
+            .bind(scheduled_time)
+            .bind(scheduled_time)
+            .bind(status)
+            .bind(recorded_at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    fn range() -> StatisticsRange {
+        let now = chrono::Utc::now().timestamp();
+        StatisticsRange { from: now - 100 * DAY, to: now + DAY }
+    }
+
+    #[tokio::test]
+    async fn on_time_and_late_completions() {
+        let pool = pool().await;
+        let today = local_day_start(chrono::Utc::now().timestamp(), 0);
+        let deadline = today - 5 * DAY + 12 * HOUR;
+
+        add_completed(&pool, 0, Some(deadline), false, deadline - 100).await;
+        add_completed(&pool, 1, Some(deadline), false, deadline + HOUR).await;
+        add_completed(&pool, 2, None, false, deadline).await;
+        // 全天待办截止到当天结束
+        let all_day = local_day_start(today, -5);
+        add_completed(&pool, 0, Some(all_day), true, local_day_start(all_day, 1) - HOUR).await;
+        // 回收站中的待办不计入
+        sqlx::query("INSERT INTO todos (title, start_time, level, status, completed_at) VALUES ('deleted', 0, 1, 2, ?)")
+            .bind(deadline)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // 循环待办按每次完成的记录统计，截止时间为计划开始时间加上时长
+        let series = sqlx::query("INSERT INTO todos (title, start_time, end_time, level, rrule, series_start) VALUES ('daily', ?, ?, 3, 'FREQ=DAILY', ?)")
+            .bind(today)
+            .bind(today + HOUR)
+            .bind(today - 3 * DAY)
+            .execute(&pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        add_occurrence(&pool, series, today - 3 * DAY, "done", today - 3 * DAY + 30 * 60).await;
+        add_occurrence(&pool, series, today - 2 * DAY, "done", today - 2 * DAY + 2 * HOUR).await;
+        add_occurrence(&pool, series, today - DAY, "skipped", today - DAY).await;
+
+        let stats = build_statistics(&pool, &range()).await.unwrap();
+        assert_eq!((stats.completed, stats.on_time, stats.late, stats.no_deadline), (6, 3, 2, 1));
+
+        let levels = stats.levels.iter().map(|l| (l.level, l.completed, l.late, l.average_delay)).collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            [(0, 2, 0, Some(0.0)), (1, 1, 1, Some(HOUR as f64)), (2, 1, 0, None), (3, 2, 1, Some(HOUR as f64 / 2.0))]
+        );
+        assert_eq!(stats.levels[3].share, 2.0 / 6.0);
+        assert_eq!(stats.daily.iter().map(|d| d.completed).sum::<i64>(), 6);
+    }
+
+    async fn add_days(pool: &SqlitePool, days_ago: &[i64]) {
+        let today = local_day_start(chrono::Utc::now().timestamp(), 0);
+        for days in days_ago {
+            add_completed(pool, 0, None, false, local_day_start(today, -days) + 12 * HOUR).await;
+        }
+    }
+
+    async fn streaks(pool: &SqlitePool) -> (i64, i64) {
+        let stats = build_statistics(pool, &range()).await.unwrap();
+        (stats.current_streak, stats.longest_streak)
+    }
+
+    #[tokio::test]
+    async fn streak_ending_today() {
+        let pool = pool().await;
+        add_days(&pool, &[0, 0, 1, 2, 10, 11, 12, 13]).await;
+        assert_eq!(streaks(&pool).await, (3, 4));
+    }
+
+    #[tokio::test]
+    async fn streak_ending_yesterday() {
+        let pool = pool().await;
+        add_days(&pool, &[1, 2, 5]).await;
+        assert_eq!(streaks(&pool).await, (2, 2));
+
+        // 循环待办的完成记录同样计入
+        let series = sqlx::query("INSERT INTO todos (title, start_time, rrule) VALUES ('daily', 0, 'FREQ=DAILY')")
+            .execute(&pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        let today = local_day_start(chrono::Utc::now().timestamp(), 0);
+        add_occurrence(&pool, series, today - 3 * DAY, "done", local_day_start(today, -3) + 12 * HOUR).await;
+        add_occurrence(&pool, series, today - 4 * DAY, "done", local_day_start(today, -4) + 12 * HOUR).await;
+        assert_eq!(streaks(&pool).await, (5, 5));
+    }
+
+    #[tokio::test]
+    async fn streak_broken_before_yesterday() {
+        let pool = pool().await;
+        assert_eq!(streaks(&pool).await, (0, 0));
+        add_days(&pool, &[2, 3]).await;
+        assert_eq!(streaks(&pool).await, (0, 2));
+    }
+}
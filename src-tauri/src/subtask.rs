@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+use crate::dependency::attach_blocked;
+use crate::holiday::load_workday_calendar;
+use crate::tag::attach_tags;
+use crate::todo::{advance_series, Todo, TODO_SELECT};
+
+// 由子任务推算的父任务进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoProgress {
+    pub done: i64,
+    pub total: i64,
+}
+
+// 为一组todo填充子任务进度，nested 为 true 时同时返回子任务本身
+pub(crate) async fn attach_subtasks(pool: &SqlitePool, todos: &mut [Todo], nested: bool) -> Result<(), String> {
+    let ids = todos
+        .iter()
+        .filter(|t| t.parent_id.is_none())
+        .map(|t| t.id.to_string())
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let mut children = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE parent_id IN ({}) AND status != 2 ORDER BY sort_order, id",
+        TODO_SELECT,
+        ids.join(",")
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if nested {
+        attach_tags(pool, &mut children).await?;
+        attach_blocked(pool, &mut children).await?;
+    }
+
+    for child in children {
+        let Some(parent) = todos.iter_mut().find(|t| Some(t.id) == child.parent_id) else {
+            continue;
+        };
+        let progress = parent.progress.get_or_insert(TodoProgress { done: 0, total: 0 });
+        progress.total += 1;
+        if child.status == 1 {
+            progress.done += 1;
+        }
+        if nested {
+            parent.children.push(child);
+        }
+    }
+    Ok(())
+}
+
+// 校验并返回子任务的父任务，只支持一层子任务
+pub(crate) async fn load_parent(conn: &mut SqliteConnection, parent_id: i64) -> Result<Todo, String> {
+    let parent = sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ? AND status != 2", TODO_SELECT))
+        .bind(parent_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("父任务不存在")?;
+    if parent.parent_id.is_some() {
+        return Err("子任务下不能再添加子任务".to_string());
+    }
+    Ok(parent)
+}
+
+// 新子任务排在同级的最后
+pub(crate) async fn next_sort_order(conn: &mut SqliteConnection, parent_id: i64) -> Result<i64, String> {
+    sqlx::query_scalar::<_, i64>("SELECT COALESCE(MAX(sort_order), -1) + 1 FROM todos WHERE parent_id = ?")
+        .bind(parent_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// 子任务变化后同步开启了自动完成的父任务：全部完成时完成父任务（循环任务完成当前这一次），
+// 有子任务重新变为未完成时重新打开父任务
+pub(crate) async fn sync_parent_completion(
+    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
+    parent_id: i64,
+) -> Result<(), String> {
+    let mut parent = load_parent(conn, parent_id).await?;
+    if !parent.auto_complete {
+        return Ok(());
+    }
+
+    let (done, total) = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT COALESCE(SUM(status = 1), 0), COUNT(*) FROM todos WHERE parent_id = ? AND status != 2"
+    )
+    .bind(parent_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let all_done = total > 0 && done == total;
+    if all_done && parent.status == 0 {
+        if parent.rrule.is_some() {
+            let calendar = load_workday_calendar(pool).await?;
+            advance_series(conn, &mut parent, "done", &calendar).await?;
+        } else {
+            set_status(conn, parent_id, 1).await?;
+        }
+    } else if !all_done && parent.status == 1 && parent.rrule.is_none() {
+        set_status(conn, parent_id, 0).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn set_status(conn: &mut SqliteConnection, id: i64, status: i64) -> Result<(), String> {
+    sqlx::query("UPDATE todos SET status = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(status)
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 按传入的顺序重新排列子任务
+#[tauri::command]
+pub async fn reorder_subtasks(pool: State<'_, SqlitePool>, parent_id: i64, ids: Vec<i64>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (index, id) in ids.iter().enumerate() {
+        sqlx::query("UPDATE todos SET sort_order = ?, updated_at = datetime('now') WHERE id = ? AND parent_id = ?")
+            .bind(index as i64)
+            .bind(id)
+            .bind(parent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
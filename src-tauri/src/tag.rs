@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::State;
+use crate::todo::{nullable, Todo};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String, // 如 @office、@home 或项目标签
+    pub color: Option<String>,
+    #[serde(rename = "sortOrder")]
+    pub sort_order: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(FromRow)]
+struct TodoTagRow {
+    todo_id: i64,
+    #[sqlx(flatten)]
+    tag: Tag,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AddTagParams {
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateTagParams {
+    id: i64,
+    name: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    color: Option<Option<String>>, // Option<Option<String>> 用于区分不更新和设置为null
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("标签名称不能为空".to_string());
+    }
+    Ok(name.to_string())
+}
+
+fn map_unique_error(e: sqlx::Error) -> String {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => "标签已存在".to_string(),
+        _ => e.to_string(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_all_tags(pool: State<'_, SqlitePool>) -> Result<Vec<Tag>, String> {
+    sqlx::query_as::<_, Tag>("SELECT id, name, color, sort_order, created_at, updated_at FROM tags ORDER BY sort_order, id")
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_tag(pool: State<'_, SqlitePool>, params: AddTagParams) -> Result<i64, String> {
+    let name = validate_name(&params.name)?;
+
+    // 新标签排在最后
+    let result = sqlx::query(
+        "INSERT INTO tags (name, color, sort_order) VALUES (?, ?, (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM tags))"
+    )
+    .bind(name)
+    .bind(params.color)
+    .execute(pool.inner())
+    .await
+    .map_err(map_unique_error)?;
+
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_tag(pool: State<'_, SqlitePool>, params: UpdateTagParams) -> Result<(), String> {
+    let mut tag = sqlx::query_as::<_, Tag>("SELECT id, name, color, sort_order, created_at, updated_at FROM tags WHERE id = ?")
+        .bind(params.id)
+        .fetch_optional(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Tag not found")?;
+
+    if let Some(name) = params.name {
+        tag.name = validate_name(&name)?;
+    }
+    if let Some(color) = params.color {
+        tag.color = color;
+    }
+
+    sqlx::query("UPDATE tags SET name = ?, color = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(&tag.name)
+        .bind(&tag.color)
+        .bind(tag.id)
+        .execute(pool.inner())
+        .await
+        .map_err(map_unique_error)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_tag(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    // todo_tags 通过外键级联删除
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 按传入的顺序重新排列标签
+#[tauri::command]
+pub async fn reorder_tags(pool: State<'_, SqlitePool>, ids: Vec<i64>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (index, id) in ids.iter().enumerate() {
+        sqlx::query("UPDATE tags SET sort_order = ?, updated_at = datetime('now') WHERE id = ?")
+            .bind(index as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_todo_tags(pool: State<'_, SqlitePool>, todo_id: i64, tag_ids: Vec<i64>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    replace_todo_tags(&mut tx, todo_id, &tag_ids).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 用给定的标签替换todo现有的标签
+pub(crate) async fn replace_todo_tags(conn: &mut SqliteConnection, todo_id: i64, tag_ids: &[i64]) -> Result<(), String> {
+    sqlx::query("DELETE FROM todo_tags WHERE todo_id = ?")
+        .bind(todo_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for tag_id in tag_ids {
+        sqlx::query("INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?, ?)")
+            .bind(todo_id)
+            .bind(tag_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// 为一组todo填充标签
+pub(crate) async fn attach_tags(pool: &SqlitePool, todos: &mut [Todo]) -> Result<(), String> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+
+    let ids = todos.iter().map(|t| t.id.to_string()).collect::<Vec<_>>().join(",");
+    let rows = sqlx::query_as::<_, TodoTagRow>(&format!(
+        "SELECT tt.todo_id, t.id, t.name, t.color, t.sort_order, t.created_at, t.updated_at
+         FROM todo_tags tt JOIN tags t ON t.id = tt.tag_id
+         WHERE tt.todo_id IN ({}) ORDER BY t.sort_order, t.id",
+        ids
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for row in rows {
+        if let Some(todo) = todos.iter_mut().find(|t| t.id == row.todo_id) {
+            todo.tags.push(row.tag);
+        }
+    }
+    Ok(())
+}
+
+// 列表查询的标签过滤条件，match_all 为 true 时需要同时带有所有标签，否则带有任一标签即可
+pub(crate) fn tag_filter_clause(tag_ids: Option<&[i64]>, match_all: bool) -> String {
+    let tag_ids = match tag_ids {
+        Some(ids) if !ids.is_empty() => ids,
+        _ => return String::new(),
+    };
+
+    let ids = tag_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    let required = if match_all { tag_ids.len() } else { 1 };
+    format!(
+        " AND id IN (SELECT todo_id FROM todo_tags WHERE tag_id IN ({}) GROUP BY todo_id HAVING COUNT(DISTINCT tag_id) >= {})",
+        ids, required
+    )
+}
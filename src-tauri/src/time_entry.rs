@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use crate::priority::Priority;
+use crate::todo::nullable;
+
+// 待办被彻底删除后 todo_id 为空，标题取删除时记下的 todo_title
+const TIME_ENTRY_SELECT: &str = "SELECT e.id, e.todo_id, COALESCE(t.title, e.todo_title, '') AS todo_title, e.start_time, e.end_time, e.note,
+     COALESCE(e.end_time, CAST(strftime('%s', 'now') AS INTEGER)) - e.start_time AS duration, e.created_at, e.updated_at
+     FROM time_entries e LEFT JOIN todos t ON t.id = e.todo_id";
+
+// 一段计时，end_time 为空表示正在计时
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TimeEntry {
+    pub id: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: Option<i64>, // 待办已被彻底删除时为空
+    #[serde(rename = "todoTitle")]
+    pub todo_title: String,
+    #[serde(rename = "startTime")]
+    pub start_time: i64, // 开始时间戳（秒）
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>, // 结束时间戳（秒），为空表示正在计时
+    pub note: Option<String>,
+    pub duration: i64, // 时长（秒），正在计时的算到现在
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AddTimeEntryParams {
+    #[serde(rename = "todoId")]
+    todo_id: i64,
+    #[serde(rename = "startTime")]
+    start_time: i64,
+    #[serde(rename = "endTime")]
+    end_time: i64,
+    note: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateTimeEntryParams {
+    id: i64,
+    #[serde(rename = "todoId")]
+    todo_id: Option<i64>,
+    #[serde(rename = "startTime")]
+    start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    end_time: Option<i64>, // 正在计时的条目传入时即停止计时
+    #[serde(default, deserialize_with = "nullable")]
+    note: Option<Option<String>>,
+}
+
+// 统计的分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeReportGroup {
+    #[serde(rename = "day")]
+    Day,
+    #[serde(rename = "week")]
+    Week,
+    #[serde(rename = "todo")]
+    Todo,
+    #[serde(rename = "level")]
+    Level,
+    #[serde(rename = "project")]
+    Project,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeReportParams {
+    pub from: i64, // 统计区间开始时间戳（秒），含
+    pub to: i64,   // 统计区间结束时间戳（秒），不含
+    #[serde(rename = "groupBy")]
+    pub group_by: TimeReportGroup,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeReportRow {
+    pub key: String, // 日期、周一日期、待办/优先级/项目 id，待办已被彻底删除时为空
+    pub label: String,
+    pub seconds: i64,
+    pub entries: i64, // 涉及的计时条数
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeReport {
+    #[serde(rename = "groupBy")]
+    pub group_by: TimeReportGroup,
+    pub from: i64,
+    pub to: i64,
+    pub rows: Vec<TimeReportRow>,
+    #[serde(rename = "totalSeconds")]
+    pub total_seconds: i64,
+}
+
+#[derive(FromRow)]
+struct ReportEntry {
+    todo_id: Option<i64>,
+    title: String,
+    level: Option<i64>,
+    project_id: Option<i64>,
+    project_name: Option<String>,
+    start_time: i64,
+    end_time: i64,
+}
+
+async fn ensure_todo(pool: &SqlitePool, todo_id: i64) -> Result<(), String> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM todos WHERE id = ? AND status != 2)")
+        .bind(todo_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err("Todo not found".to_string());
+    }
+    Ok(())
+}
+
+async fn load_entry(pool: &SqlitePool, id: i64) -> Result<TimeEntry, String> {
+    sqlx::query_as::<_, TimeEntry>(&format!("{} WHERE e.id = ?", TIME_ENTRY_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Time entry not found".to_string())
+}
+
+async fn load_running(pool: &SqlitePool) -> Result<Option<TimeEntry>, String> {
+    sqlx::query_as::<_, TimeEntry>(&format!("{} WHERE e.end_time IS NULL", TIME_ENTRY_SELECT))
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// 校验时间段，并且不能与其他计时重叠（正在计时的算到现在）
+async fn validate_range(pool: &SqlitePool, exclude_id: Option<i64>, start_time: i64, end_time: Option<i64>) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    if start_time > now {
+        return Err("开始时间不能晚于现在".to_string());
+    }
+    if matches!(end_time, Some(end) if end <= start_time) {
+        return Err("结束时间必须晚于开始时间".to_string());
+    }
+
+    let overlap = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM time_entries WHERE id IS NOT ? AND start_time < ? AND COALESCE(end_time, ?) > ?)"
+    )
+    .bind(exclude_id)
+    .bind(end_time.unwrap_or(now))
+    .bind(now)
+    .bind(start_time)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if overlap {
+        return Err("与已有的计时重叠".to_string());
+    }
+    Ok(())
+}
+
+// 停止正在进行的计时，返回被停止的条目
+async fn stop_running(pool: &SqlitePool) -> Result<Option<TimeEntry>, String> {
+    let Some(running) = load_running(pool).await? else {
+        return Ok(None);
+    };
+    // 系统时间被调回时至少记为 0 秒
+    let now = chrono::Utc::now().timestamp().max(running.start_time);
+    sqlx::query("UPDATE time_entries SET end_time = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(now)
+        .bind(running.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    load_entry(pool, running.id).await.map(Some)
+}
+
+// 开始为待办计时，同一时间只有一个计时，正在为其他待办计时时先停止
+#[tauri::command]
+pub async fn start_time_tracking(pool: State<'_, SqlitePool>, todo_id: i64) -> Result<TimeEntry, String> {
+    ensure_todo(pool.inner(), todo_id).await?;
+    if let Some(running) = load_running(pool.inner()).await? {
+        if running.todo_id == Some(todo_id) {
+            return Ok(running);
+        }
+        stop_running(pool.inner()).await?;
+    }
+
+    let result = sqlx::query("INSERT INTO time_entries (todo_id, start_time) VALUES (?, ?)")
+        .bind(todo_id)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    load_entry(pool.inner(), result.last_insert_rowid()).await
+}
+
+#[tauri::command]
+pub async fn stop_time_tracking(pool: State<'_, SqlitePool>) -> Result<Option<TimeEntry>, String> {
+    stop_running(pool.inner()).await
+}
+
+// 正在进行的计时，应用重启后据此恢复计时显示
+#[tauri::command]
+pub async fn get_running_time_entry(pool: State<'_, SqlitePool>) -> Result<Option<TimeEntry>, String> {
+    load_running(pool.inner()).await
+}
+
+// 按待办和时间区间查询计时记录，区间内有交集的都会返回
+#[tauri::command]
+pub async fn get_time_entries(
+    pool: State<'_, SqlitePool>,
+    todo_id: Option<i64>,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<TimeEntry>, String> {
+    sqlx::query_as::<_, TimeEntry>(&format!(
+        "{} WHERE (? IS NULL OR e.todo_id = ?)
+           AND (? IS NULL OR COALESCE(e.end_time, CAST(strftime('%s', 'now') AS INTEGER)) > ?)
+           AND (? IS NULL OR e.start_time < ?)
+         ORDER BY e.start_time DESC",
+        TIME_ENTRY_SELECT
+    ))
+    .bind(todo_id)
+    .bind(todo_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 手动补录一段计时
+#[tauri::command]
+pub async fn add_time_entry(pool: State<'_, SqlitePool>, params: AddTimeEntryParams) -> Result<i64, String> {
+    ensure_todo(pool.inner(), params.todo_id).await?;
+    validate_range(pool.inner(), None, params.start_time, Some(params.end_time)).await?;
+
+    let result = sqlx::query("INSERT INTO time_entries (todo_id, start_time, end_time, note) VALUES (?, ?, ?, ?)")
+        .bind(params.todo_id)
+        .bind(params.start_time)
+        .bind(params.end_time)
+        .bind(params.note)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_time_entry(pool: State<'_, SqlitePool>, params: UpdateTimeEntryParams) -> Result<TimeEntry, String> {
+    let mut entry = load_entry(pool.inner(), params.id).await?;
+
+    if let Some(todo_id) = params.todo_id {
+        if Some(todo_id) != entry.todo_id {
+            ensure_todo(pool.inner(), todo_id).await?;
+            entry.todo_id = Some(todo_id);
+        }
+    }
+    if let Some(start_time) = params.start_time {
+        entry.start_time = start_time;
+    }
+    if let Some(end_time) = params.end_time {
+        entry.end_time = Some(end_time);
+    }
+    if let Some(note) = params.note {
+        entry.note = note;
+    }
+    validate_range(pool.inner(), Some(entry.id), entry.start_time, entry.end_time).await?;
+
+    sqlx::query("UPDATE time_entries SET todo_id = ?, start_time = ?, end_time = ?, note = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(entry.todo_id)
+        .bind(entry.start_time)
+        .bind(entry.end_time)
+        .bind(&entry.note)
+        .bind(entry.id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    load_entry(pool.inner(), entry.id).await
+}
+
+#[tauri::command]
+pub async fn delete_time_entry(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM time_entries WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn local_midnight(date: NaiveDate) -> Result<i64, String> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).ok_or("无效的日期")?)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or_else(|| "无效的日期".to_string())
+}
+
+// 按本地时间的天或周（周一开始）切分一段时间，跨零点的计时分别计入各自的日期
+fn split_by_period(start: i64, end: i64, group: TimeReportGroup) -> Result<Vec<(NaiveDate, i64)>, String> {
+    let days = if group == TimeReportGroup::Week { 7 } else { 1 };
+    let mut segments = Vec::new();
+    let mut t = start;
+    while t < end {
+        let date = Local.timestamp_opt(t, 0).single().ok_or("无效的时间")?.date_naive();
+        let period = if group == TimeReportGroup::Week {
+            date - Duration::days(date.weekday().num_days_from_monday() as i64)
+        } else {
+            date
+        };
+        let next = local_midnight(period + Duration::days(days))?.max(t + 1).min(end);
+        segments.push((period, next - t));
+        t = next;
+    }
+    Ok(segments)
+}
+
+async fn build_time_report(pool: &SqlitePool, params: &TimeReportParams) -> Result<TimeReport, String> {
+    if params.to <= params.from {
+        return Err("结束时间必须晚于开始时间".to_string());
+    }
+    let now = chrono::Utc::now().timestamp();
+
+    // 只统计落在区间内的部分
+    let entries = sqlx::query_as::<_, ReportEntry>(
+        "SELECT e.todo_id, COALESCE(t.title, e.todo_title, '') AS title, t.level, t.project_id, p.name AS project_name,
+                MAX(e.start_time, ?) AS start_time, MIN(COALESCE(e.end_time, ?), ?) AS end_time
+         FROM time_entries e LEFT JOIN todos t ON t.id = e.todo_id LEFT JOIN projects p ON p.id = t.project_id
+         WHERE e.start_time < ? AND COALESCE(e.end_time, ?) > ?
+         ORDER BY e.start_time"
+    )
+    .bind(params.from)
+    .bind(now)
+    .bind(params.to)
+    .bind(params.to)
+    .bind(now)
+    .bind(params.from)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut rows: Vec<TimeReportRow> = Vec::new();
+    // 已删除的待办没有 id，按标题区分
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut add = |key: String, label: String, seconds: i64| {
+        let i = *index.entry((key.clone(), label.clone())).or_insert_with(|| {
+            rows.push(TimeReportRow { key, label, seconds: 0, entries: 0 });
+            rows.len() - 1
+        });
+        rows[i].seconds += seconds;
+        rows[i].entries += 1;
+    };
+
+    for entry in &entries {
+        if entry.end_time <= entry.start_time {
+            continue;
+        }
+        let seconds = entry.end_time - entry.start_time;
+        match params.group_by {
+            TimeReportGroup::Day | TimeReportGroup::Week => {
+                for (period, seconds) in split_by_period(entry.start_time, entry.end_time, params.group_by)? {
+                    let key = period.format("%Y-%m-%d").to_string();
+                    let label = if params.group_by == TimeReportGroup::Week {
+                        format!("{} ~ {}", key, (period + Duration::days(6)).format("%Y-%m-%d"))
+                    } else {
+                        key.clone()
+                    };
+                    add(key, label, seconds);
+                }
+            }
+            TimeReportGroup::Todo => {
+                let key = entry.todo_id.map(|id| id.to_string()).unwrap_or_default();
+                add(key, entry.title.clone(), seconds);
+            }
+            TimeReportGroup::Level => match entry.level {
+                Some(level) => {
+                    let label = Priority::try_from(level).map_or("未知", Priority::label);
+                    add(level.to_string(), label.to_string(), seconds);
+                }
+                None => add(String::new(), "已删除的待办".to_string(), seconds),
+            },
+            TimeReportGroup::Project => {
+                let key = entry.project_id.map(|id| id.to_string()).unwrap_or_default();
+                let label = entry.project_name.clone().unwrap_or_else(|| "未归入项目".to_string());
+                add(key, label, seconds);
+            }
+        }
+    }
+
+    // 按日期、周的按时间先后，其余按时长从多到少
+    match params.group_by {
+        TimeReportGroup::Day | TimeReportGroup::Week => rows.sort_by(|a, b| a.key.cmp(&b.key)),
+        _ => rows.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.key.cmp(&b.key)).then_with(|| a.label.cmp(&b.label))),
+    }
+    let total_seconds = rows.iter().map(|r| r.seconds).sum();
+    Ok(TimeReport { group_by: params.group_by, from: params.from, to: params.to, rows, total_seconds })
+}
+
+#[tauri::command]
+pub async fn get_time_report(pool: State<'_, SqlitePool>, params: TimeReportParams) -> Result<TimeReport, String> {
+    build_time_report(pool.inner(), &params).await
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// 导出为 CSV 文本，由前端保存为文件
+#[tauri::command]
+pub async fn export_time_report_csv(pool: State<'_, SqlitePool>, params: TimeReportParams) -> Result<String, String> {
+    let report = build_time_report(pool.inner(), &params).await?;
+    Ok(report_csv(&report))
+}
+
+fn report_csv(report: &TimeReport) -> String {
+    let mut csv = String::from("key,label,entries,seconds,hours\n");
+    for row in &report.rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{:.2}\n",
+            csv_field(&row.key),
+            csv_field(&row.label),
+            row.entries,
+            row.seconds,
+            row.seconds as f64 / 3600.0
+        ));
+    }
+    csv.push_str(&format!(",合计,,{},{:.2}\n", report.total_seconds, report.total_seconds as f64 / 3600.0));
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_todo(pool: &SqlitePool, title: &str, parent_id: Option<i64>) -> i64 {
+        sqlx::query("INSERT INTO todos (title, start_time, parent_id) VALUES (?, 0, ?)")
+            .bind(title)
+            .bind(parent_id)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn add_entry(pool: &SqlitePool, todo_id: i64, start_time: i64, end_time: Option<i64>) -> Result<i64, sqlx::Error> {
+        sqlx::query("INSERT INTO time_entries (todo_id, start_time, end_time) VALUES (?, ?, ?)")
+            .bind(todo_id)
+            .bind(start_time)
+            .bind(end_time)
+            .execute(pool)
+            .await
+            .map(|r| r.last_insert_rowid())
+    }
+
+    fn midnight(y: i32, m: u32, d: u32) -> i64 {
+        local_midnight(NaiveDate::from_ymd_opt(y, m, d).unwrap()).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[tokio::test]
+    async fn overlapping_ranges_are_rejected() {
+        let pool = pool().await;
+        let todo = add_todo(&pool, "A", None).await;
+        let start = midnight(2024, 6, 3) + 9 * HOUR;
+        let entry = add_entry(&pool, todo, start, Some(start + HOUR)).await.unwrap();
+
+        let err = validate_range(&pool, None, start + HOUR / 2, Some(start + 2 * HOUR)).await.unwrap_err();
+        assert_eq!(err, "与已有的计时重叠");
+        assert!(validate_range(&pool, None, start - HOUR, Some(start + 2 * HOUR)).await.is_err());
+        // 首尾相接不算重叠，修改条目自身时排除自己
+        validate_range(&pool, None, start + HOUR, Some(start + 2 * HOUR)).await.unwrap();
+        validate_range(&pool, None, start - HOUR, Some(start)).await.unwrap();
+        validate_range(&pool, Some(entry), start + HOUR / 2, Some(start + 2 * HOUR)).await.unwrap();
+        assert!(validate_range(&pool, None, start, Some(start)).await.is_err());
+
+        // 正在进行的计时算到现在
+        let now = chrono::Utc::now().timestamp();
+        add_entry(&pool, todo, now - HOUR, None).await.unwrap();
+        assert!(validate_range(&pool, None, now - HOUR / 2, Some(now - HOUR / 4)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn only_one_entry_can_run() {
+        let pool = pool().await;
+        let a = add_todo(&pool, "A", None).await;
+        let b = add_todo(&pool, "B", None).await;
+        let now = chrono::Utc::now().timestamp();
+
+        add_entry(&pool, a, now - HOUR, None).await.unwrap();
+        assert!(add_entry(&pool, b, now, None).await.is_err());
+        add_entry(&pool, b, now - 3 * HOUR, Some(now - 2 * HOUR)).await.unwrap();
+
+        let stopped = stop_running(&pool).await.unwrap().unwrap();
+        assert_eq!((stopped.todo_id, stopped.todo_title.as_str()), (Some(a), "A"));
+        assert!(load_running(&pool).await.unwrap().is_none());
+        add_entry(&pool, b, now, None).await.unwrap();
+    }
+
+    #[test]
+    fn entries_are_split_at_midnight_and_monday() {
+        // 2024-06-09 是周日，跨零点进入下一周
+        let start = midnight(2024, 6, 9) + 23 * HOUR;
+        let end = midnight(2024, 6, 10) + HOUR + 30 * 60;
+        assert_eq!(
+            split_by_period(start, end, TimeReportGroup::Day).unwrap(),
+            [(date(2024, 6, 9), HOUR), (date(2024, 6, 10), HOUR + 30 * 60)]
+        );
+        assert_eq!(
+            split_by_period(start, end, TimeReportGroup::Week).unwrap(),
+            [(date(2024, 6, 3), HOUR), (date(2024, 6, 10), HOUR + 30 * 60)]
+        );
+
+        // 周内跨零点只按天切分
+        let start = midnight(2024, 6, 4) + 22 * HOUR;
+        let end = midnight(2024, 6, 5) + 2 * HOUR;
+        assert_eq!(split_by_period(start, end, TimeReportGroup::Day).unwrap().len(), 2);
+        assert_eq!(split_by_period(start, end, TimeReportGroup::Week).unwrap(), [(date(2024, 6, 3), 4 * HOUR)]);
+    }
+
+    #[test]
+    fn csv_quotes_titles() {
+        let report = TimeReport {
+            group_by: TimeReportGroup::Todo,
+            from: 0,
+            to: 1,
+            rows: vec![
+                TimeReportRow { key: "1".to_string(), label: "客户A, 方案".to_string(), seconds: 5400, entries: 2 },
+                TimeReportRow { key: "2".to_string(), label: "写\"周报\"".to_string(), seconds: 1800, entries: 1 },
+                TimeReportRow { key: "3".to_string(), label: "普通".to_string(), seconds: 0, entries: 1 },
+            ],
+            total_seconds: 7200,
+        };
+        assert_eq!(
+            report_csv(&report),
+            "key,label,entries,seconds,hours\n\
+             1,\"客户A, 方案\",2,5400,1.50\n\
+             2,\"写\"\"周报\"\"\",1,1800,0.50\n\
+             3,普通,1,0,0.00\n\
+             ,合计,,7200,2.00\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn purged_todos_keep_their_time() {
+        let pool = pool().await;
+        let parent = add_todo(&pool, "客户A, 方案", None).await;
+        let child = add_todo(&pool, "子任务", Some(parent)).await;
+        let kept = add_todo(&pool, "保留", None).await;
+        let start = midnight(2024, 6, 3) + 9 * HOUR;
+        add_entry(&pool, parent, start, Some(start + HOUR)).await.unwrap();
+        add_entry(&pool, child, start + HOUR, Some(start + HOUR + 30 * 60)).await.unwrap();
+        add_entry(&pool, kept, start + 2 * HOUR, Some(start + 2 * HOUR + 15 * 60)).await.unwrap();
+
+        sqlx::query("UPDATE todos SET status = 2, deleted_at = 0 WHERE id = ?")
+            .bind(parent)
+            .execute(&pool)
+            .await
+            .unwrap();
+        crate::trash::purge_expired_todos(&pool, start).await.unwrap();
+        assert_eq!(sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM todos").fetch_one(&pool).await.unwrap(), 1);
+
+        let params = TimeReportParams { from: midnight(2024, 6, 3), to: midnight(2024, 6, 4), group_by: TimeReportGroup::Todo };
+        let report = build_time_report(&pool, &params).await.unwrap();
+        let rows = report.rows.iter().map(|r| (r.key.as_str(), r.label.as_str(), r.seconds)).collect::<Vec<_>>();
+        let kept_key = kept.to_string();
+        assert_eq!(rows, [("", "客户A, 方案", HOUR), ("", "子任务", 30 * 60), (kept_key.as_str(), "保留", 15 * 60)]);
+        assert_eq!(report.total_seconds, HOUR + 45 * 60);
+        assert!(report_csv(&report).contains(",\"客户A, 方案\",1,3600,1.00\n"));
+
+        let params = TimeReportParams { group_by: TimeReportGroup::Level, ..params };
+        let report = build_time_report(&pool, &params).await.unwrap();
+        assert_eq!(report.rows[0].label, "已删除的待办");
+        assert_eq!(report.rows[0].seconds, HOUR + 30 * 60);
+
+        let entry = sqlx::query_as::<_, TimeEntry>(&format!("{} ORDER BY e.id LIMIT 1", TIME_ENTRY_SELECT))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!((entry.todo_id, entry.todo_title.as_str()), (None, "客户A, 方案"));
+    }
+}
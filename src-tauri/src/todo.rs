@@ -1,89 +1,368 @@
+use chrono::{Local, TimeZone};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
-use tauri::State;
-use chrono::{DateTime, Utc, Duration, Datelike};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+use crate::dependency::{attach_blocked, blocked_todo_ids, emit_todos_unblocked, ensure_not_blocking, unblocked_since};
+use crate::history::{diff_advanced_series, diff_todos, record_changes, tag_change, ChangeSource};
+use crate::holiday::{load_workday_calendar, WorkdayCalendar};
+use crate::priority::{Priority, LEVEL_ORDER};
+use crate::project::project_filter_clause;
+use crate::query::local_timestamp;
+use crate::recurrence::{normalize_rule, RecurrenceRule};
+use crate::subtask::{attach_subtasks, load_parent, next_sort_order, sync_parent_completion, TodoProgress};
+use crate::tag::{attach_tags, replace_todo_tags, tag_filter_clause, Tag};
+use crate::undo::ChangeRecorder;
+
+// 查询todo时统一使用的字段列表
+pub(crate) const TODO_SELECT: &str = "SELECT id, title, start_time, end_time, notes, level, rrule, series_start, occurrence_start, project_id, parent_id, sort_order, auto_complete, status, deleted_at, completed_at, all_day, created_at, updated_at FROM todos";
+
+// 全天待办按日期处理：开始时间和截止时间都是当天零点，截止到截止日期当天结束
+// 开始时间所在的最后一刻，按开始时间筛选时以此与范围的起点比较，全天待办在当天任何时候都算在范围内
+pub(crate) const START_LATEST: &str = "CASE WHEN all_day THEN CAST(strftime('%s', start_time, 'unixepoch', 'localtime', '+1 day', 'utc') AS INTEGER) - 1 ELSE start_time END";
+// 实际的截止时刻，全天待办为截止日期的次日零点
+pub(crate) const DEADLINE: &str = "CASE WHEN all_day THEN CAST(strftime('%s', end_time, 'unixepoch', 'localtime', '+1 day', 'utc') AS INTEGER) ELSE end_time END";
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Todo {
     pub id: i64,
     pub title: String,
     #[serde(rename = "startTime")]
-    pub start_time: i64, // 开始时间戳（秒）
+    pub start_time: Option<i64>, // 开始时间戳（秒），为空表示收集箱中尚未安排的待办
     #[serde(rename = "endTime")]
     pub end_time: Option<i64>, // 结束时间戳（秒），可为空
     pub notes: Option<String>,
     pub level: i64, // 0: 重要不紧急, 1: 重要且紧急, 2: 不重要不紧急, 3: 不重要但紧急
-    pub cycle: String, // one, day, week, month, year
+    pub rrule: Option<String>, // RFC 5545 RRULE，如 FREQ=WEEKLY;BYDAY=TU,TH，为空表示不重复
+    #[serde(rename = "seriesStart")]
+    pub series_start: Option<i64>, // 循环系列第一次的开始时间戳（秒）
+    #[serde(rename = "occurrenceStart")]
+    pub occurrence_start: Option<i64>, // 当前这一次按规则计划的开始时间戳（秒），推迟后可能与start_time不同
+    #[serde(rename = "projectId")]
+    pub project_id: Option<i64>, // 所属项目，为空表示未归入项目
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<i64>, // 父任务，为空表示顶层待办
+    #[serde(rename = "sortOrder")]
+    pub sort_order: i64, // 子任务在父任务中的顺序
+    #[serde(rename = "autoComplete")]
+    pub auto_complete: bool, // 子任务全部完成时自动完成
     pub status: i64, // 0: pending, 1: completed, 2: deleted
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<i64>, // 移入回收站的时间戳（秒）
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<i64>, // 完成的时间戳（秒），未完成为空
+    #[serde(rename = "allDay", default)]
+    pub all_day: bool, // 全天待办，只有日期没有时刻
     pub created_at: String,
     pub updated_at: String,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub children: Vec<Todo>, // 仅 get_todo_by_id 返回
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub progress: Option<TodoProgress>, // 没有子任务时为空
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub blocked: bool, // 还有未完成的前置任务
 }
 
-#[derive(serde::Deserialize)]
+impl Todo {
+    // 实际的截止时刻，全天待办截止到截止日期当天结束
+    pub(crate) fn deadline(&self) -> Option<i64> {
+        let end = self.end_time?;
+        Some(if self.all_day { local_day_start(end, 1) } else { end })
+    }
+}
+
+// 时间戳所在日期之后第 days 天的本地零点
+pub(crate) fn local_day_start(timestamp: i64, days: i64) -> i64 {
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(time) => local_timestamp(time.date_naive() + chrono::Duration::days(days)),
+        None => timestamp,
+    }
+}
+
+// 开始时间移到 start 后的截止时间：保持原来的时长，全天待办保持相差的天数
+pub(crate) fn shifted_end(todo: &Todo, start: i64) -> Option<i64> {
+    let end = todo.end_time?;
+    let Some(old_start) = todo.start_time else {
+        return Some(end);
+    };
+    if !todo.all_day {
+        return Some(start + (end - old_start));
+    }
+    let date = |timestamp: i64| Local.timestamp_opt(timestamp, 0).single().map(|t| t.date_naive());
+    match (date(old_start), date(end)) {
+        (Some(old_date), Some(end_date)) => Some(local_day_start(start, (end_date - old_date).num_days())),
+        _ => Some(start + (end - old_start)),
+    }
+}
+
+// 全天待办的时间统一为当天零点
+fn normalize_all_day(todo: &mut Todo) {
+    if todo.all_day {
+        let midnight = |t: Option<i64>| t.map(|t| local_day_start(t, 0));
+        todo.start_time = midnight(todo.start_time);
+        todo.end_time = midnight(todo.end_time);
+        todo.series_start = midnight(todo.series_start);
+        todo.occurrence_start = midnight(todo.occurrence_start);
+    }
+}
+
+// 循环任务每一次发生的记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TodoOccurrence {
+    pub id: i64,
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    #[serde(rename = "scheduledTime")]
+    pub scheduled_time: i64, // 按规则计划的开始时间戳（秒）
+    #[serde(rename = "actualStart")]
+    pub actual_start: i64, // 推迟后实际的开始时间戳（秒）
+    pub status: String, // done, skipped, missed
+    pub recorded_at: String,
+}
+
+// 区分字段缺省（不更新）与显式传 null（设置为空），需配合 #[serde(default)] 使用
+pub(crate) fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct AddTodoParams {
     title: String,
-    #[serde(rename = "startTime")]
-    start_time: i64, // 开始时间戳（秒）
+    #[serde(rename = "startTime", default)]
+    start_time: Option<i64>, // 开始时间戳（秒），不传则放入收集箱
     #[serde(rename = "endTime")]
     end_time: Option<i64>, // 结束时间戳（秒），可为空
     notes: Option<String>,
-    level: i64,
-    cycle: String,
+    level: Priority,
+    #[serde(alias = "cycle")]
+    rrule: Option<String>, // 兼容旧版 one/day/week/month/year
+    #[serde(rename = "tagIds")]
+    tag_ids: Option<Vec<i64>>,
+    #[serde(rename = "projectId")]
+    project_id: Option<i64>,
+    #[serde(rename = "parentId")]
+    parent_id: Option<i64>,
+    #[serde(rename = "autoComplete", default)]
+    auto_complete: bool,
+    #[serde(rename = "allDay", default)]
+    all_day: bool,
 }
 
-#[derive(serde::Deserialize)]
+impl AddTodoParams {
+    // 快速添加或 AI 提取出的待办，其余字段留给用户在界面上补充
+    pub(crate) fn parsed(
+        title: String,
+        start_time: Option<i64>,
+        end_time: Option<i64>,
+        notes: Option<String>,
+        level: Priority,
+        rrule: Option<String>,
+        all_day: bool,
+    ) -> Self {
+        AddTodoParams {
+            title,
+            start_time,
+            end_time,
+            notes,
+            level,
+            rrule,
+            tag_ids: None,
+            project_id: None,
+            parent_id: None,
+            auto_complete: false,
+            all_day,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
 pub struct UpdateTodoParams {
     id: i64,
     title: Option<String>,
-    #[serde(rename = "startTime")]
-    start_time: Option<i64>,
-    #[serde(rename = "endTime")]
+    #[serde(rename = "startTime", default, deserialize_with = "nullable")]
+    start_time: Option<Option<i64>>, // 传 null 表示移回收集箱
+    #[serde(rename = "endTime", default, deserialize_with = "nullable")]
     end_time: Option<Option<i64>>, // Option<Option<i64>> 用于区分不更新和设置为null
+    #[serde(default, deserialize_with = "nullable")]
     notes: Option<Option<String>>,
-    level: Option<i64>,
-    cycle: Option<String>,
+    level: Option<Priority>,
+    #[serde(alias = "cycle")]
+    rrule: Option<String>, // 传 one 或空字符串表示取消重复
     completed: Option<bool>,
+    #[serde(rename = "tagIds")]
+    tag_ids: Option<Vec<i64>>, // 传入时替换全部标签
+    #[serde(rename = "projectId", default, deserialize_with = "nullable")]
+    project_id: Option<Option<i64>>, // 传 null 表示移出项目
+    #[serde(rename = "autoComplete")]
+    auto_complete: Option<bool>,
+    #[serde(rename = "allDay")]
+    all_day: Option<bool>,
+    #[serde(default)]
+    source: ChangeSource, // 修改来源，记录到修改历史中
+}
+
+impl UpdateTodoParams {
+    // 只修改优先级
+    pub(crate) fn with_level(id: i64, level: Priority) -> Self {
+        UpdateTodoParams { id, level: Some(level), ..Default::default() }
+    }
+
+    // 安排时间，收集箱中的待办由此移出收集箱
+    fn schedule(params: ScheduleTodoParams) -> Self {
+        UpdateTodoParams {
+            id: params.id,
+            start_time: Some(Some(params.start_time)),
+            end_time: Some(params.end_time),
+            all_day: Some(params.all_day),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct ScheduleTodoParams {
+    id: i64,
+    #[serde(rename = "startTime")]
+    start_time: i64,
+    #[serde(rename = "endTime", default)]
+    end_time: Option<i64>,
+    #[serde(rename = "allDay", default)]
+    all_day: bool,
+}
+
+// todos-changed 事件的内容，所有窗口据此刷新列表
+#[derive(Debug, Clone, Serialize)]
+pub struct TodosChangedEvent {
+    pub action: String, // add, update, delete, undo, redo, bulk, escalate
+    pub ids: Vec<i64>,
+}
+
+pub(crate) fn emit_todos_changed(app_handle: &AppHandle, action: &str, ids: &[i64]) {
+    let event = TodosChangedEvent { action: action.to_string(), ids: ids.to_vec() };
+    if let Err(e) = app_handle.emit("todos-changed", event) {
+        eprintln!("Failed to emit todos-changed event: {}", e);
+    }
 }
 
 #[tauri::command]
-pub async fn add_todo(pool: State<'_, SqlitePool>, params: AddTodoParams) -> Result<i64, String> {
+pub async fn add_todo(app_handle: AppHandle, pool: State<'_, SqlitePool>, params: AddTodoParams) -> Result<i64, String> {
+    let rrule = normalize_rule(params.rrule.as_deref())?;
+    if rrule.is_some() && params.start_time.is_none() {
+        return Err("循环任务必须设置开始时间".to_string());
+    }
+    let (start_time, end_time) = if params.all_day {
+        (params.start_time.map(|t| local_day_start(t, 0)), params.end_time.map(|t| local_day_start(t, 0)))
+    } else {
+        (params.start_time, params.end_time)
+    };
+    let series_start = rrule.as_ref().and(start_time);
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let recorder = ChangeRecorder::begin(&mut tx, "add", params.parent_id).await?;
+    
+    // 子任务默认跟随父任务所在的项目
+    let mut project_id = params.project_id;
+    let mut sort_order = 0;
+    if let Some(parent_id) = params.parent_id {
+        if rrule.is_some() {
+            return Err("子任务不支持重复".to_string());
+        }
+        let parent = load_parent(&mut tx, parent_id).await?;
+        project_id = project_id.or(parent.project_id);
+        sort_order = next_sort_order(&mut tx, parent_id).await?;
+    }
+    
     let result = sqlx::query(
-        "INSERT INTO todos (title, start_time, end_time, notes, level, cycle) VALUES (?, ?, ?, ?, ?, ?)"
+        "INSERT INTO todos (title, start_time, end_time, notes, level, rrule, series_start, occurrence_start, project_id, parent_id, sort_order, auto_complete, all_day) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(params.title)
-    .bind(params.start_time)
-    .bind(params.end_time)
+    .bind(start_time)
+    .bind(end_time)
     .bind(params.notes)
-    .bind(params.level)
-    .bind(params.cycle)
-    .execute(pool.inner())
+    .bind(params.level.level())
+    .bind(rrule)
+    .bind(series_start)
+    .bind(series_start)
+    .bind(project_id)
+    .bind(params.parent_id)
+    .bind(sort_order)
+    .bind(params.auto_complete)
+    .bind(params.all_day)
+    .execute(&mut *tx)
     .await
     .map_err(|e| e.to_string())?;
+    let id = result.last_insert_rowid();
 
-    Ok(result.last_insert_rowid())
+    if let Some(tag_ids) = params.tag_ids {
+        replace_todo_tags(&mut tx, id, &tag_ids).await?;
+    }
+    // 已自动完成的父任务添加了新的子任务后重新打开
+    if let Some(parent_id) = params.parent_id {
+        sync_parent_completion(pool.inner(), &mut tx, parent_id).await?;
+    }
+    recorder.finish(&mut tx, id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    emit_todos_changed(&app_handle, "add", &[id]);
+    Ok(id)
 }
 
 #[tauri::command]
-pub async fn get_all_todos(pool: State<'_, SqlitePool>) -> Result<Vec<Todo>, String> {
-    sqlx::query_as::<_, Todo>("SELECT id, title, start_time, end_time, notes, level, cycle, status, created_at, updated_at FROM todos WHERE status != 2 ORDER BY created_at DESC")
-        .fetch_all(pool.inner())
-        .await
-        .map_err(|e| e.to_string())
+pub async fn get_all_todos(
+    pool: State<'_, SqlitePool>,
+    tag_ids: Option<Vec<i64>>,
+    match_all: Option<bool>,
+    project_id: Option<i64>,
+) -> Result<Vec<Todo>, String> {
+    let mut todos = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE status != 2 AND parent_id IS NULL{}{} ORDER BY created_at DESC",
+        TODO_SELECT,
+        tag_filter_clause(tag_ids.as_deref(), match_all.unwrap_or(false)),
+        project_filter_clause(project_id)
+    ))
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(pool.inner(), &mut todos).await?;
+    attach_subtasks(pool.inner(), &mut todos, false).await?;
+    attach_blocked(pool.inner(), &mut todos).await?;
+    Ok(todos)
 }
 
 #[tauri::command]
 pub async fn get_todo_by_id(pool: State<'_, SqlitePool>, id: i64) -> Result<Option<Todo>, String> {
-    let todo = sqlx::query_as::<_, Todo>("SELECT id, title, start_time, end_time, notes, level, cycle, status, created_at, updated_at FROM todos WHERE id = ? AND status != 2")
+    let todo = sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ? AND status != 2", TODO_SELECT))
         .bind(id)
         .fetch_optional(pool.inner())
         .await
         .map_err(|e| e.to_string())?;
     
-    Ok(todo)
+    let mut todos = Vec::from_iter(todo);
+    attach_tags(pool.inner(), &mut todos).await?;
+    attach_subtasks(pool.inner(), &mut todos, true).await?;
+    attach_blocked(pool.inner(), &mut todos).await?;
+    Ok(todos.pop())
 }
 
 #[tauri::command]
-pub async fn get_recent_todos(pool: State<'_, SqlitePool>, days: i64) -> Result<Vec<Todo>, String> {
+pub async fn get_recent_todos(
+    pool: State<'_, SqlitePool>,
+    days: i64,
+    tag_ids: Option<Vec<i64>>,
+    match_all: Option<bool>,
+    project_id: Option<i64>,
+    blocked_last: Option<bool>, // 被阻塞的待办排在可以开始的待办之后
+) -> Result<Vec<Todo>, String> {
     let now = chrono::Utc::now().timestamp();
     let cutoff_timestamp = now - (days * 24 * 60 * 60);
     let future_timestamp = now + (days * 24 * 60 * 60);
@@ -91,105 +370,286 @@ pub async fn get_recent_todos(pool: State<'_, SqlitePool>, days: i64) -> Result<
     // 首先处理到期的循环任务
     process_expired_todos(pool.inner()).await?;
     
-    // 获取最近x天内的todos（包括过去x天和未来x天的任务），按优先级排序
-    let todos = sqlx::query_as::<_, Todo>(
-        "SELECT id, title, start_time, end_time, notes, level, cycle, status, created_at, updated_at FROM todos WHERE status = 0 AND start_time >= ? AND start_time <= ? ORDER BY CASE level WHEN 1 THEN 1 WHEN 0 THEN 2 WHEN 3 THEN 3 WHEN 2 THEN 4 END, start_time ASC"
-    )
+    // 获取最近x天内的todos（包括过去x天和未来x天的任务），按优先级排序；收集箱中的待办不在其中
+    let mut todos = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE status = 0 AND parent_id IS NULL AND {} >= ? AND start_time <= ?{}{} ORDER BY {}, start_time ASC",
+        TODO_SELECT,
+        START_LATEST,
+        tag_filter_clause(tag_ids.as_deref(), match_all.unwrap_or(false)),
+        project_filter_clause(project_id),
+        LEVEL_ORDER
+    ))
     .bind(cutoff_timestamp)
     .bind(future_timestamp)
     .fetch_all(pool.inner())
     .await
     .map_err(|e| e.to_string())?;
     
+    attach_tags(pool.inner(), &mut todos).await?;
+    attach_subtasks(pool.inner(), &mut todos, false).await?;
+    attach_blocked(pool.inner(), &mut todos).await?;
+    if blocked_last.unwrap_or(false) {
+        // 稳定排序，同一组内保持优先级顺序
+        todos.sort_by_key(|t| t.blocked);
+    }
     Ok(todos)
 }
 
-// 处理到期的循环任务
-async fn process_expired_todos(pool: &SqlitePool) -> Result<(), String> {
+// 收集箱：尚未安排开始时间的待办，新添加的在前
+#[tauri::command]
+pub async fn get_inbox_todos(
+    pool: State<'_, SqlitePool>,
+    tag_ids: Option<Vec<i64>>,
+    match_all: Option<bool>,
+    project_id: Option<i64>,
+) -> Result<Vec<Todo>, String> {
+    let mut todos = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE status = 0 AND parent_id IS NULL AND start_time IS NULL{}{} ORDER BY created_at DESC, id DESC",
+        TODO_SELECT,
+        tag_filter_clause(tag_ids.as_deref(), match_all.unwrap_or(false)),
+        project_filter_clause(project_id)
+    ))
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(pool.inner(), &mut todos).await?;
+    attach_subtasks(pool.inner(), &mut todos, false).await?;
+    attach_blocked(pool.inner(), &mut todos).await?;
+    Ok(todos)
+}
+
+// 为待办安排时间，与修改开始和截止时间相同，可撤销并记录修改历史
+#[tauri::command]
+pub async fn schedule_todo(app_handle: AppHandle, pool: State<'_, SqlitePool>, params: ScheduleTodoParams) -> Result<(), String> {
+    update_todo(app_handle, pool, UpdateTodoParams::schedule(params)).await
+}
+
+// 处理到期的循环任务：下一次已经到来但当前这一次仍未处理的，记为错过并推进到下一次
+pub(crate) async fn process_expired_todos(pool: &SqlitePool) -> Result<(), String> {
     let now = chrono::Utc::now().timestamp();
     
-    // 查找所有到期的循环任务
-    let expired_todos = sqlx::query_as::<_, Todo>(
-        "SELECT id, title, start_time, end_time, notes, level, cycle, status, created_at, updated_at FROM todos WHERE status = 0 AND start_time < ? AND cycle != 'one'"
-    )
-    .bind(now)
+    let series = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE status = 0 AND rrule IS NOT NULL",
+        TODO_SELECT
+    ))
     .fetch_all(pool)
     .await
     .map_err(|e| e.to_string())?;
     
-    for todo in expired_todos {
-        // 归档旧的todo（设置为已完成）
-        sqlx::query("UPDATE todos SET status = 1, updated_at = datetime('now') WHERE id = ?")
-            .bind(todo.id)
-            .execute(pool)
-            .await
-            .map_err(|e| e.to_string())?;
-        
-        // 根据cycle生成新的todo
-        let new_start_time = calculate_next_cycle_time(todo.start_time, &todo.cycle);
-        let new_end_time = todo.end_time.map(|end| {
-            let duration = end - todo.start_time;
-            new_start_time + duration
-        });
-        
-        sqlx::query(
-            "INSERT INTO todos (title, start_time, end_time, notes, level, cycle) VALUES (?, ?, ?, ?, ?, ?)"
-        )
-        .bind(todo.title)
-        .bind(new_start_time)
-        .bind(new_end_time)
-        .bind(todo.notes)
-        .bind(todo.level)
-        .bind(todo.cycle)
-        .execute(pool)
+    if series.is_empty() {
+        return Ok(());
+    }
+    let calendar = load_workday_calendar(pool).await?;
+    
+    for mut todo in series {
+        let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+        while matches!(next_occurrence(&todo, &calendar), Some(next) if next <= now) {
+            advance_series(&mut tx, &mut todo, "missed", &calendar).await?;
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+    }
+    
+    Ok(())
+}
+
+fn parse_rule(todo: &Todo) -> Option<RecurrenceRule> {
+    match todo.rrule.as_deref()?.parse::<RecurrenceRule>() {
+        Ok(rule) => Some(rule),
+        Err(e) => {
+            eprintln!("Invalid rrule for todo {}: {}", todo.id, e);
+            None
+        }
+    }
+}
+
+// 当前这一次之后的下一次计划开始时间，系列已结束（COUNT/UNTIL）时返回 None
+pub(crate) fn next_occurrence(todo: &Todo, calendar: &WorkdayCalendar) -> Option<i64> {
+    let rule = parse_rule(todo)?;
+    let series_start = todo.series_start.or(todo.start_time)?;
+    let scheduled = todo.occurrence_start.or(todo.start_time)?;
+    rule.next_after(calendar, series_start, scheduled)
+}
+
+// 记录当前这一次的结果，并把系列推进到下一次；没有下一次时整个系列结束
+pub(crate) async fn advance_series(
+    conn: &mut SqliteConnection,
+    todo: &mut Todo,
+    status: &str,
+    calendar: &WorkdayCalendar,
+) -> Result<(), String> {
+    let scheduled = todo.occurrence_start.or(todo.start_time).ok_or("循环任务必须设置开始时间")?;
+    
+    sqlx::query(
+        "INSERT INTO todo_occurrences (todo_id, scheduled_time, actual_start, status) VALUES (?, ?, ?, ?)
+         ON CONFLICT(todo_id, scheduled_time) DO UPDATE SET actual_start = excluded.actual_start, status = excluded.status, recorded_at = CURRENT_TIMESTAMP"
+    )
+    .bind(todo.id)
+    .bind(scheduled)
+    .bind(todo.start_time)
+    .bind(status)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    
+    match next_occurrence(todo, calendar) {
+        Some(next) => {
+            // 保持与本次相同的持续时长
+            todo.end_time = shifted_end(todo, next);
+            todo.start_time = Some(next);
+            todo.occurrence_start = Some(next);
+            
+            // 下一次重新从未完成的子任务开始
+            sqlx::query("UPDATE todos SET status = 0, updated_at = datetime('now') WHERE parent_id = ? AND status = 1")
+                .bind(todo.id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        None => todo.status = 1,
+    }
+    
+    sqlx::query("UPDATE todos SET start_time = ?, end_time = ?, occurrence_start = ?, status = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(todo.start_time)
+        .bind(todo.end_time)
+        .bind(todo.occurrence_start)
+        .bind(todo.status)
+        .bind(todo.id)
+        .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
-    }
     
     Ok(())
 }
 
-// 计算下一个循环时间
-fn calculate_next_cycle_time(current_time: i64, cycle: &str) -> i64 {
-    let dt = DateTime::<Utc>::from_timestamp(current_time, 0).unwrap_or_else(|| Utc::now());
+async fn get_recurring_todo(pool: &SqlitePool, id: i64) -> Result<Todo, String> {
+    let todo = sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ? AND status = 0", TODO_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Todo not found")?;
     
-    match cycle {
-        "day" => (dt + Duration::days(1)).timestamp(),
-        "week" => (dt + Duration::weeks(1)).timestamp(),
-        "month" => {
-            // 添加一个月，处理月份边界
-            let mut year = dt.year();
-            let mut month = dt.month() + 1;
-            if month > 12 {
-                month = 1;
-                year += 1;
-            }
-            dt.with_year(year).unwrap_or(dt)
-                .with_month(month).unwrap_or(dt)
-                .timestamp()
-        },
-        "year" => (dt + Duration::days(365)).timestamp(),
-        _ => current_time, // 默认不变
+    if todo.rrule.is_none() {
+        return Err("该待办不是循环任务".to_string());
     }
+    Ok(todo)
+}
+
+// 记录循环任务当前这一次的结果并推进到下一次，与 update_todo 一样可撤销并记录修改历史
+async fn finish_occurrence(pool: &SqlitePool, id: i64, status: &str) -> Result<Todo, String> {
+    let mut todo = get_recurring_todo(pool, id).await?;
+    let original = todo.clone();
+    let calendar = load_workday_calendar(pool).await?;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let recorder = ChangeRecorder::begin(&mut tx, "update", Some(id)).await?;
+    advance_series(&mut tx, &mut todo, status, &calendar).await?;
+    record_changes(&mut tx, id, &diff_advanced_series(&original, &todo), ChangeSource::Ui).await?;
+    recorder.finish(&mut tx, id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(todo)
+}
+
+// 完成循环任务的当前这一次
+#[tauri::command]
+pub async fn complete_todo_occurrence(app_handle: AppHandle, pool: State<'_, SqlitePool>, id: i64) -> Result<Todo, String> {
+    let todo = finish_occurrence(pool.inner(), id, "done").await?;
+    emit_todos_changed(&app_handle, "update", &[id]);
+    Ok(todo)
+}
+
+// 跳过循环任务的当前这一次
+#[tauri::command]
+pub async fn skip_todo_occurrence(app_handle: AppHandle, pool: State<'_, SqlitePool>, id: i64) -> Result<Todo, String> {
+    let todo = finish_occurrence(pool.inner(), id, "skipped").await?;
+    emit_todos_changed(&app_handle, "update", &[id]);
+    Ok(todo)
+}
+
+// 推迟循环任务的当前这一次，不影响系列后续的计划时间；source 为修改来源，记录到修改历史中
+#[tauri::command]
+pub async fn postpone_todo_occurrence(
+    app_handle: AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    start_time: i64,
+    source: Option<ChangeSource>,
+) -> Result<Todo, String> {
+    let todo = postpone_occurrence(pool.inner(), id, start_time, source.unwrap_or_default()).await?;
+    emit_todos_changed(&app_handle, "update", &[id]);
+    Ok(todo)
+}
+
+// 开始和截止时间的推后记录到修改历史中，计入推迟次数的统计
+async fn postpone_occurrence(pool: &SqlitePool, id: i64, start_time: i64, source: ChangeSource) -> Result<Todo, String> {
+    let mut todo = get_recurring_todo(pool, id).await?;
+    let original = todo.clone();
+    let calendar = load_workday_calendar(pool).await?;
+    
+    if let Some(next) = next_occurrence(&todo, &calendar) {
+        if start_time >= next {
+            return Err("推迟时间不能晚于下一次的开始时间".to_string());
+        }
+    }
+    
+    let start_time = if todo.all_day { local_day_start(start_time, 0) } else { start_time };
+    todo.occurrence_start = todo.occurrence_start.or(todo.start_time);
+    todo.end_time = shifted_end(&todo, start_time);
+    todo.start_time = Some(start_time);
+    
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let recorder = ChangeRecorder::begin(&mut tx, "update", Some(id)).await?;
+    sqlx::query("UPDATE todos SET start_time = ?, end_time = ?, occurrence_start = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(todo.start_time)
+        .bind(todo.end_time)
+        .bind(todo.occurrence_start)
+        .bind(todo.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    record_changes(&mut tx, id, &diff_todos(&original, &todo), source).await?;
+    recorder.finish(&mut tx, id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(todo)
 }
 
 #[tauri::command]
-pub async fn update_todo(pool: State<'_, SqlitePool>, params: UpdateTodoParams) -> Result<(), String> {
+pub async fn get_todo_occurrences(pool: State<'_, SqlitePool>, id: i64) -> Result<Vec<TodoOccurrence>, String> {
+    sqlx::query_as::<_, TodoOccurrence>(
+        "SELECT id, todo_id, scheduled_time, actual_start, status, recorded_at FROM todo_occurrences WHERE todo_id = ? ORDER BY scheduled_time DESC"
+    )
+    .bind(id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_todo(app_handle: AppHandle, pool: State<'_, SqlitePool>, params: UpdateTodoParams) -> Result<(), String> {
     // 首先获取现有的todo
-    let existing_todo = sqlx::query_as::<_, Todo>("SELECT id, title, start_time, end_time, notes, level, cycle, status, created_at, updated_at FROM todos WHERE id = ?")
+    let existing_todo = sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ?", TODO_SELECT))
         .bind(params.id)
         .fetch_optional(pool.inner())
         .await
         .map_err(|e| e.to_string())?;
     
     let mut todo = existing_todo.ok_or("Todo not found")?;
+    let original = todo.clone();
     
     // 更新字段
     if let Some(title) = params.title {
         todo.title = title;
     }
     if let Some(start_time) = params.start_time {
-        todo.start_time = start_time;
+        // 只有真正修改了开始时间才重新锚定循环系列
+        if start_time != todo.start_time {
+            todo.start_time = start_time;
+            todo.series_start = todo.rrule.as_ref().and(start_time);
+            todo.occurrence_start = todo.series_start;
+        }
+    }
+    if let Some(all_day) = params.all_day {
+        todo.all_day = all_day;
     }
     if let Some(end_time) = params.end_time {
         todo.end_time = end_time;
@@ -198,38 +658,177 @@ pub async fn update_todo(pool: State<'_, SqlitePool>, params: UpdateTodoParams)
         todo.notes = notes;
     }
     if let Some(level) = params.level {
-        todo.level = level;
+        todo.level = level.level();
+    }
+    if let Some(project_id) = params.project_id {
+        todo.project_id = project_id;
+    }
+    if let Some(auto_complete) = params.auto_complete {
+        todo.auto_complete = auto_complete;
     }
-    if let Some(cycle) = params.cycle {
-        todo.cycle = cycle;
+    if let Some(rrule) = params.rrule {
+        let rrule = normalize_rule(Some(&rrule))?;
+        if rrule.is_some() && todo.parent_id.is_some() {
+            return Err("子任务不支持重复".to_string());
+        }
+        if rrule != todo.rrule {
+            todo.rrule = rrule;
+            todo.series_start = todo.rrule.as_ref().and(todo.start_time);
+            todo.occurrence_start = todo.series_start;
+        }
     }
+    if todo.rrule.is_some() && todo.start_time.is_none() {
+        return Err("循环任务必须设置开始时间".to_string());
+    }
+    normalize_all_day(&mut todo);
+    // 循环任务的"完成"只完成当前这一次，系列继续
+    let complete_occurrence = params.completed == Some(true) && todo.status == 0 && todo.rrule.is_some();
     if let Some(completed) = params.completed {
-        todo.status = if completed { 1 } else { 0 };
+        if !complete_occurrence {
+            todo.status = if completed { 1 } else { 0 };
+        }
+    }
+    let mut changes = diff_todos(&original, &todo);
+    
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    if original.rrule.is_none() && todo.rrule.is_some() {
+        ensure_not_blocking(&mut tx, todo.id).await?;
     }
+    let recorder = ChangeRecorder::begin(&mut tx, "update", Some(todo.id)).await?;
+    // 完成待办（包括由此自动完成的父任务）可能解除其他待办的阻塞
+    let blocked_before = if params.completed == Some(true) {
+        Some(blocked_todo_ids(&mut tx).await?)
+    } else {
+        None
+    };
     
     // 执行更新
-    sqlx::query("UPDATE todos SET title = ?, start_time = ?, end_time = ?, notes = ?, level = ?, cycle = ?, status = ?, updated_at = datetime('now') WHERE id = ?")
-        .bind(todo.title)
+    sqlx::query("UPDATE todos SET title = ?, start_time = ?, end_time = ?, notes = ?, level = ?, rrule = ?, series_start = ?, occurrence_start = ?, project_id = ?, auto_complete = ?, all_day = ?, status = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(&todo.title)
         .bind(todo.start_time)
         .bind(todo.end_time)
-        .bind(todo.notes)
+        .bind(&todo.notes)
         .bind(todo.level)
-        .bind(todo.cycle)
+        .bind(&todo.rrule)
+        .bind(todo.series_start)
+        .bind(todo.occurrence_start)
+        .bind(todo.project_id)
+        .bind(todo.auto_complete)
+        .bind(todo.all_day)
         .bind(todo.status)
         .bind(todo.id)
-        .execute(pool.inner())
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
+    
+    if complete_occurrence {
+        let calendar = load_workday_calendar(pool.inner()).await?;
+        let current = todo.clone();
+        advance_series(&mut tx, &mut todo, "done", &calendar).await?;
+        changes.extend(diff_advanced_series(&current, &todo));
+    }
+    
+    if let Some(tag_ids) = params.tag_ids {
+        changes.extend(tag_change(&mut tx, todo.id, &tag_ids).await?);
+        replace_todo_tags(&mut tx, todo.id, &tag_ids).await?;
+    }
+    record_changes(&mut tx, todo.id, &changes, params.source).await?;
+    
+    // 子任务完成状态变化或父任务开启自动完成时，同步父任务
+    if let Some(parent_id) = todo.parent_id {
+        if params.completed.is_some() {
+            sync_parent_completion(pool.inner(), &mut tx, parent_id).await?;
+        }
+    } else if params.auto_complete == Some(true) {
+        sync_parent_completion(pool.inner(), &mut tx, todo.id).await?;
+    }
+    
+    let unblocked = match blocked_before {
+        Some(before) => unblocked_since(&mut tx, &before).await?,
+        None => Vec::new(),
+    };
+    
+    recorder.finish(&mut tx, todo.id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    emit_todos_changed(&app_handle, "update", &[todo.id]);
+    emit_todos_unblocked(&app_handle, &[todo.id], unblocked);
+    Ok(())
+}
+
+// 移入回收站，子任务一并删除；记录删除前的状态以便恢复
+pub(crate) async fn move_to_trash(
+    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<(), String> {
+    sqlx::query(
+        "UPDATE todos SET deleted_status = status, status = 2, deleted_at = ?, updated_at = datetime('now')
+         WHERE (id = ? OR parent_id = ?) AND status != 2"
+    )
+    .bind(chrono::Utc::now().timestamp())
+    .bind(id)
+    .bind(id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    // 删除子任务后剩下的可能已全部完成
+    if let Some(parent_id) = parent_id {
+        sync_parent_completion(pool, conn, parent_id).await?;
+    }
     Ok(())
 }
 
 #[tauri::command]
-pub async fn delete_todo(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
-    // 逻辑删除
-    sqlx::query("UPDATE todos SET status = 2, updated_at = datetime('now') WHERE id = ?")
+pub async fn delete_todo(app_handle: AppHandle, pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    let parent_id = sqlx::query_scalar::<_, Option<i64>>("SELECT parent_id FROM todos WHERE id = ?")
         .bind(id)
-        .execute(pool.inner())
+        .fetch_optional(pool.inner())
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .flatten();
+    
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let recorder = ChangeRecorder::begin(&mut tx, "delete", Some(id)).await?;
+    move_to_trash(pool.inner(), &mut tx, id, parent_id).await?;
+    recorder.finish(&mut tx, id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    emit_todos_changed(&app_handle, "delete", &[id]);
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn postpone_is_recorded_with_its_source() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        let id = sqlx::query(
+            "INSERT INTO todos (title, start_time, end_time, rrule, series_start, occurrence_start) VALUES ('晨跑', 1000, 2800, 'FREQ=DAILY', 1000, 1000)"
+        )
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+        // 不能推迟到下一次之后
+        assert!(postpone_occurrence(&pool, id, 87_400, ChangeSource::Ui).await.is_err());
+
+        let todo = postpone_occurrence(&pool, id, 4_600, ChangeSource::Shortcut).await.unwrap();
+        assert_eq!((todo.start_time, todo.end_time, todo.occurrence_start), (Some(4_600), Some(6_400), Some(1_000)));
+
+        let history = sqlx::query_as::<_, (String, String, String, String)>(
+            "SELECT field, old_value, new_value, source FROM todo_history WHERE todo_id = ? ORDER BY id"
+        )
+        .bind(id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let expected = [("startTime", "1000", "4600", "shortcut"), ("endTime", "2800", "6400", "shortcut")];
+        assert_eq!(history.len(), expected.len());
+        for (row, expected) in history.iter().zip(expected) {
+            assert_eq!((row.0.as_str(), row.1.as_str(), row.2.as_str(), row.3.as_str()), expected);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::State;
+use tokio::time::{interval, Duration};
+use crate::subtask::sync_parent_completion;
+use crate::tag::attach_tags;
+use crate::todo::{Todo, TODO_SELECT};
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+// 自动清理的检查间隔
+const PURGE_INTERVAL_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TrashSettings {
+    #[serde(rename = "retentionDays")]
+    pub retention_days: i64, // 回收站保留天数，0 表示不自动清理
+}
+
+async fn load_trash_settings(pool: &SqlitePool) -> Result<TrashSettings, String> {
+    let settings = sqlx::query_as::<_, TrashSettings>("SELECT retention_days FROM trash_settings WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or(TrashSettings { retention_days: DEFAULT_RETENTION_DAYS }))
+}
+
+#[tauri::command]
+pub async fn get_trash_settings(pool: State<'_, SqlitePool>) -> Result<TrashSettings, String> {
+    load_trash_settings(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn save_trash_settings(pool: State<'_, SqlitePool>, settings: TrashSettings) -> Result<(), String> {
+    if settings.retention_days < 0 {
+        return Err("保留天数不能为负数".to_string());
+    }
+    sqlx::query("INSERT OR REPLACE INTO trash_settings (id, retention_days, updated_at) VALUES (1, ?, datetime('now'))")
+        .bind(settings.retention_days)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 回收站中的待办，随父任务一起删除的子任务不单独列出
+#[tauri::command]
+pub async fn list_deleted_todos(pool: State<'_, SqlitePool>) -> Result<Vec<Todo>, String> {
+    let mut todos = sqlx::query_as::<_, Todo>(&format!(
+        "{} WHERE status = 2 AND (parent_id IS NULL OR parent_id NOT IN (SELECT id FROM todos WHERE status = 2))
+         ORDER BY deleted_at DESC, id DESC",
+        TODO_SELECT
+    ))
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(pool.inner(), &mut todos).await?;
+    Ok(todos)
+}
+
+// 从回收站恢复待办，恢复到删除前的状态；随它一起删除的子任务一并恢复
+pub(crate) async fn restore_from_trash(pool: &SqlitePool, conn: &mut SqliteConnection, id: i64) -> Result<(), String> {
+    let todo = sqlx::query_as::<_, Todo>(&format!("{} WHERE id = ? AND status = 2", TODO_SELECT))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("待办不在回收站中")?;
+
+    if let Some(parent_id) = todo.parent_id {
+        let parent_status = sqlx::query_scalar::<_, i64>("SELECT status FROM todos WHERE id = ?")
+            .bind(parent_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        if parent_status == 2 {
+            return Err("请先恢复父任务".to_string());
+        }
+    }
+
+    sqlx::query(
+        "UPDATE todos SET status = COALESCE(deleted_status, 0), deleted_status = NULL, deleted_at = NULL, updated_at = datetime('now')
+         WHERE id = ? OR (parent_id = ? AND status = 2 AND deleted_at IS ?)"
+    )
+    .bind(id)
+    .bind(id)
+    .bind(todo.deleted_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // 恢复的子任务可能改变父任务的完成状态
+    if let Some(parent_id) = todo.parent_id {
+        sync_parent_completion(pool, conn, parent_id).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn restore_todo(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    restore_from_trash(pool.inner(), &mut tx, id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 彻底删除回收站中的待办，子任务、提醒和标签关联随之删除
+#[tauri::command]
+pub async fn purge_todo(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
+    let result = sqlx::query("DELETE FROM todos WHERE id = ? AND status = 2")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("待办不在回收站中".to_string());
+    }
+    Ok(())
+}
+
+// 彻底删除超过保留期限的待办，返回删除的数量
+pub async fn purge_expired_todos(pool: &SqlitePool, now: i64) -> Result<u64, String> {
+    let settings = load_trash_settings(pool).await?;
+    if settings.retention_days == 0 {
+        return Ok(0);
+    }
+
+    let cutoff = now - settings.retention_days * 24 * 60 * 60;
+    let result = sqlx::query("DELETE FROM todos WHERE status = 2 AND deleted_at < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.rows_affected())
+}
+
+// 启动回收站自动清理，启动时先清理一次，之后每小时检查一次
+pub fn start_trash_purger(pool: SqlitePool) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(PURGE_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            match purge_expired_todos(&pool, Utc::now().timestamp()).await {
+                Ok(0) => {}
+                Ok(count) => println!("回收站自动清理了 {} 个待办", count),
+                Err(e) => eprintln!("Failed to purge trash: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::move_to_trash;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn add_todo(pool: &SqlitePool, status: i64, parent_id: Option<i64>) -> i64 {
+        sqlx::query("INSERT INTO todos (title, start_time, status, parent_id) VALUES ('整理桌面', 0, ?, ?)")
+            .bind(status)
+            .bind(parent_id)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn trash(pool: &SqlitePool, id: i64, deleted_at: i64) {
+        sqlx::query("UPDATE todos SET deleted_status = status, status = 2, deleted_at = ? WHERE id = ?")
+            .bind(deleted_at)
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn status(pool: &SqlitePool, id: i64) -> Option<i64> {
+        sqlx::query_scalar::<_, i64>("SELECT status FROM todos WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn purge_only_after_retention() {
+        let pool = pool().await;
+        let now = 100 * DAY;
+        let expired = add_todo(&pool, 0, None).await;
+        let expired_child = add_todo(&pool, 0, Some(expired)).await;
+        let recent = add_todo(&pool, 0, None).await;
+        let pending = add_todo(&pool, 0, None).await;
+        trash(&pool, expired, now - DEFAULT_RETENTION_DAYS * DAY - 1).await;
+        trash(&pool, expired_child, now - DEFAULT_RETENTION_DAYS * DAY - 1).await;
+        trash(&pool, recent, now - DEFAULT_RETENTION_DAYS * DAY).await;
+
+        assert!(purge_expired_todos(&pool, now).await.unwrap() > 0);
+        assert_eq!(status(&pool, expired).await, None);
+        assert_eq!(status(&pool, expired_child).await, None);
+        assert_eq!(status(&pool, recent).await, Some(2));
+        assert_eq!(status(&pool, pending).await, Some(0));
+
+        // 保留 0 天表示不自动清理
+        sqlx::query("INSERT INTO trash_settings (id, retention_days) VALUES (1, 0)").execute(&pool).await.unwrap();
+        assert_eq!(purge_expired_todos(&pool, now + 365 * DAY).await.unwrap(), 0);
+        assert_eq!(status(&pool, recent).await, Some(2));
+
+        sqlx::query("UPDATE trash_settings SET retention_days = 1").execute(&pool).await.unwrap();
+        assert_eq!(purge_expired_todos(&pool, now - DEFAULT_RETENTION_DAYS * DAY + DAY + 1).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn restore_returns_to_status_before_delete() {
+        let pool = pool().await;
+        let completed = add_todo(&pool, 1, None).await;
+        let parent = add_todo(&pool, 0, None).await;
+        let child = add_todo(&pool, 1, Some(parent)).await;
+        let removed_earlier = add_todo(&pool, 0, Some(parent)).await;
+
+        let mut conn = pool.acquire().await.unwrap();
+        move_to_trash(&pool, &mut conn, completed, None).await.unwrap();
+        trash(&pool, removed_earlier, 1).await;
+        move_to_trash(&pool, &mut conn, parent, None).await.unwrap();
+        assert_eq!(status(&pool, child).await, Some(2));
+
+        // 父任务还在回收站中时不能单独恢复子任务
+        assert!(restore_from_trash(&pool, &mut conn, child).await.is_err());
+
+        restore_from_trash(&pool, &mut conn, completed).await.unwrap();
+        assert_eq!(status(&pool, completed).await, Some(1));
+
+        // 随父任务一起删除的子任务恢复到删除前的状态，之前单独删除的仍留在回收站
+        restore_from_trash(&pool, &mut conn, parent).await.unwrap();
+        assert_eq!(status(&pool, parent).await, Some(0));
+        assert_eq!(status(&pool, child).await, Some(1));
+        assert_eq!(status(&pool, removed_earlier).await, Some(2));
+
+        assert!(restore_from_trash(&pool, &mut conn, parent).await.is_err());
+    }
+}
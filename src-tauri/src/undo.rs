@@ -0,0 +1,589 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, State};
+use crate::todo::{emit_todos_changed, TodoOccurrence};
+
+// 保留的可撤销操作数量
+const MAX_HISTORY: i64 = 100;
+
+const SNAPSHOT_SELECT: &str = "SELECT id, title, start_time, end_time, notes, level, rrule, series_start, occurrence_start, project_id, parent_id, sort_order, auto_complete, status, deleted_at, deleted_status, completed_at, all_day, created_at, updated_at FROM todos";
+
+// 一条待办在某一时刻的完整数据，撤销和重做时原样写回
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub(crate) struct TodoSnapshot {
+    id: i64,
+    title: String,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    notes: Option<String>,
+    level: i64,
+    rrule: Option<String>,
+    series_start: Option<i64>,
+    occurrence_start: Option<i64>,
+    project_id: Option<i64>,
+    parent_id: Option<i64>,
+    sort_order: i64,
+    auto_complete: bool,
+    status: i64,
+    deleted_at: Option<i64>,
+    deleted_status: Option<i64>,
+    #[serde(default)]
+    completed_at: Option<i64>,
+    #[serde(default)]
+    all_day: bool,
+    created_at: String,
+    updated_at: String,
+    #[sqlx(skip)]
+    #[serde(default)]
+    tag_ids: Vec<i64>,
+}
+
+// 操作前或操作后的状态，occurrences 为操作中新记录的循环任务历史
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ChangeState {
+    todos: Vec<TodoSnapshot>,
+    #[serde(default)]
+    occurrences: Vec<TodoOccurrence>,
+}
+
+#[derive(FromRow)]
+struct TodoChangeRow {
+    id: i64,
+    action: String,
+    todo_id: i64,
+    title: String,
+    before_state: String,
+    after_state: String,
+    created_at: String,
+}
+
+// 被撤销或重做的操作
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoChange {
+    pub id: i64,
+    pub action: String, // add, update, delete
+    #[serde(rename = "todoId")]
+    pub todo_id: i64,
+    pub title: String,
+    pub created_at: String,
+}
+
+impl From<&TodoChangeRow> for TodoChange {
+    fn from(row: &TodoChangeRow) -> Self {
+        TodoChange {
+            id: row.id,
+            action: row.action.clone(),
+            todo_id: row.todo_id,
+            title: row.title.clone(),
+            created_at: row.created_at.clone(),
+        }
+    }
+}
+
+fn id_list(ids: &[i64]) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+}
+
+async fn snapshot(conn: &mut SqliteConnection, ids: &[i64]) -> Result<Vec<TodoSnapshot>, String> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut todos = sqlx::query_as::<_, TodoSnapshot>(&format!("{} WHERE id IN ({}) ORDER BY id", SNAPSHOT_SELECT, id_list(ids)))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tags = sqlx::query_as::<_, (i64, i64)>(&format!(
+        "SELECT todo_id, tag_id FROM todo_tags WHERE todo_id IN ({}) ORDER BY tag_id",
+        id_list(ids)
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    for (todo_id, tag_id) in tags {
+        if let Some(todo) = todos.iter_mut().find(|t| t.id == todo_id) {
+            todo.tag_ids.push(tag_id);
+        }
+    }
+    Ok(todos)
+}
+
+// 修改一条待办可能影响到的待办：自身、父任务和子任务
+async fn related_ids(conn: &mut SqliteConnection, id: i64) -> Result<Vec<i64>, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM todos WHERE id = ? OR id = (SELECT parent_id FROM todos WHERE id = ?) OR parent_id = ?"
+    )
+    .bind(id)
+    .bind(id)
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 记录一次可撤销的待办修改：修改前调用 begin 保存快照，修改完成后在同一事务中调用 finish
+pub(crate) struct ChangeRecorder {
+    action: &'static str,
+    ids: Vec<i64>,
+    before: Vec<TodoSnapshot>,
+    occurrence_mark: i64,
+}
+
+impl ChangeRecorder {
+    // anchor 为被修改的待办，新增子任务时为父任务
+    pub(crate) async fn begin(conn: &mut SqliteConnection, action: &'static str, anchor: Option<i64>) -> Result<Self, String> {
+        let ids = match anchor {
+            Some(id) => related_ids(conn, id).await?,
+            None => Vec::new(),
+        };
+        let before = snapshot(conn, &ids).await?;
+        let occurrence_mark = sqlx::query_scalar::<_, i64>("SELECT COALESCE(MAX(id), 0) FROM todo_occurrences")
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(ChangeRecorder { action, ids, before, occurrence_mark })
+    }
+
+    pub(crate) async fn finish(self, conn: &mut SqliteConnection, todo_id: i64) -> Result<(), String> {
+        let mut ids = self.ids;
+        if !ids.contains(&todo_id) {
+            ids.push(todo_id);
+        }
+        let after = snapshot(conn, &ids).await?;
+        if after == self.before {
+            return Ok(());
+        }
+        let occurrences = sqlx::query_as::<_, TodoOccurrence>(&format!(
+            "SELECT id, todo_id, scheduled_time, actual_start, status, recorded_at FROM todo_occurrences WHERE id > ? AND todo_id IN ({})",
+            id_list(&ids)
+        ))
+        .bind(self.occurrence_mark)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let title = after
+            .iter()
+            .chain(self.before.iter())
+            .find(|t| t.id == todo_id)
+            .map(|t| t.title.clone())
+            .unwrap_or_default();
+        let before_state = serde_json::to_string(&ChangeState { todos: self.before, occurrences: Vec::new() })
+            .map_err(|e| e.to_string())?;
+        let after_state = serde_json::to_string(&ChangeState { todos: after, occurrences })
+            .map_err(|e| e.to_string())?;
+
+        // 新的修改使已撤销的记录无法再重做
+        sqlx::query("DELETE FROM todo_changes WHERE undone = 1")
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        sqlx::query("INSERT INTO todo_changes (action, todo_id, title, before_state, after_state) VALUES (?, ?, ?, ?, ?)")
+            .bind(self.action)
+            .bind(todo_id)
+            .bind(title)
+            .bind(before_state)
+            .bind(after_state)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        sqlx::query("DELETE FROM todo_changes WHERE id NOT IN (SELECT id FROM todo_changes ORDER BY id DESC LIMIT ?)")
+            .bind(MAX_HISTORY)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+fn state_ids(a: &ChangeState, b: &ChangeState) -> Vec<i64> {
+    let mut ids: Vec<i64> = a.todos.iter().chain(b.todos.iter()).map(|t| t.id).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+// 当前数据仍与 current 一致时才能切换到 target，避免覆盖之后的其他修改
+async fn can_apply(conn: &mut SqliteConnection, current: &ChangeState, target: &ChangeState) -> Result<bool, String> {
+    let ids = state_ids(current, target);
+    if snapshot(conn, &ids).await? != current.todos {
+        return Ok(false);
+    }
+
+    // 要删除的待办之后又添加了子任务
+    let removed = current
+        .todos
+        .iter()
+        .filter(|t| !target.todos.iter().any(|o| o.id == t.id))
+        .map(|t| t.id)
+        .collect::<Vec<_>>();
+    if removed.is_empty() {
+        return Ok(true);
+    }
+    let children = sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM todos WHERE parent_id IN ({}) AND id NOT IN ({})",
+        id_list(&removed),
+        id_list(&ids)
+    ))
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(children == 0)
+}
+
+// 把待办写回 target 的状态，current 中有而 target 中没有的记录（由该操作新建）被删除
+async fn apply_state(conn: &mut SqliteConnection, current: &ChangeState, target: &ChangeState) -> Result<(), String> {
+    for occurrence in &current.occurrences {
+        if !target.occurrences.iter().any(|o| o.id == occurrence.id) {
+            sqlx::query("DELETE FROM todo_occurrences WHERE id = ?")
+                .bind(occurrence.id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    for todo in &current.todos {
+        if !target.todos.iter().any(|t| t.id == todo.id) {
+            sqlx::query("DELETE FROM todos WHERE id = ?")
+                .bind(todo.id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    // 先写父任务再写子任务
+    let mut todos = target.todos.iter().collect::<Vec<_>>();
+    todos.sort_by_key(|t| t.parent_id.is_some());
+    for todo in todos {
+        sqlx::query(
+            "INSERT INTO todos (id, title, start_time, end_time, notes, level, rrule, series_start, occurrence_start, project_id, parent_id, sort_order, auto_complete, status, deleted_at, deleted_status, completed_at, all_day, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title, start_time = excluded.start_time, end_time = excluded.end_time, notes = excluded.notes,
+                level = excluded.level, rrule = excluded.rrule, series_start = excluded.series_start,
+                occurrence_start = excluded.occurrence_start, project_id = excluded.project_id, parent_id = excluded.parent_id,
+                sort_order = excluded.sort_order, auto_complete = excluded.auto_complete, status = excluded.status,
+                deleted_at = excluded.deleted_at, deleted_status = excluded.deleted_status, completed_at = excluded.completed_at,
+                all_day = excluded.all_day, created_at = excluded.created_at, updated_at = excluded.updated_at"
+        )
+        .bind(todo.id)
+        .bind(&todo.title)
+        .bind(todo.start_time)
+        .bind(todo.end_time)
+        .bind(&todo.notes)
+        .bind(todo.level)
+        .bind(&todo.rrule)
+        .bind(todo.series_start)
+        .bind(todo.occurrence_start)
+        .bind(todo.project_id)
+        .bind(todo.parent_id)
+        .bind(todo.sort_order)
+        .bind(todo.auto_complete)
+        .bind(todo.status)
+        .bind(todo.deleted_at)
+        .bind(todo.deleted_status)
+        .bind(todo.completed_at)
+        .bind(todo.all_day)
+        .bind(&todo.created_at)
+        .bind(&todo.updated_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        // 期间被删除的标签不再恢复
+        sqlx::query("DELETE FROM todo_tags WHERE todo_id = ?")
+            .bind(todo.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        for tag_id in &todo.tag_ids {
+            sqlx::query("INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) SELECT ?, id FROM tags WHERE id = ?")
+                .bind(todo.id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    for occurrence in &target.occurrences {
+        sqlx::query(
+            "INSERT OR IGNORE INTO todo_occurrences (id, todo_id, scheduled_time, actual_start, status, recorded_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(occurrence.id)
+        .bind(occurrence.todo_id)
+        .bind(occurrence.scheduled_time)
+        .bind(occurrence.actual_start)
+        .bind(&occurrence.status)
+        .bind(&occurrence.recorded_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn parse_states(row: &TodoChangeRow) -> Result<(ChangeState, ChangeState), String> {
+    let before = serde_json::from_str(&row.before_state).map_err(|e| e.to_string())?;
+    let after = serde_json::from_str(&row.after_state).map_err(|e| e.to_string())?;
+    Ok((before, after))
+}
+
+// 撤销最近一次待办修改，返回被撤销的操作和受影响的待办，没有可撤销的操作时返回空
+async fn undo_last_change(pool: &SqlitePool) -> Result<Option<(TodoChange, Vec<i64>)>, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let row = sqlx::query_as::<_, TodoChangeRow>(
+        "SELECT id, action, todo_id, title, before_state, after_state, created_at FROM todo_changes WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let (before, after) = parse_states(&row)?;
+    if !can_apply(&mut tx, &after, &before).await? {
+        // 这条记录已经过期，丢弃后仍可继续撤销更早的操作
+        sqlx::query("DELETE FROM todo_changes WHERE id = ?")
+            .bind(row.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        tx.commit().await.map_err(|e| e.to_string())?;
+        return Err(format!("「{}」之后又被修改过，无法撤销", row.title));
+    }
+
+    apply_state(&mut tx, &after, &before).await?;
+    sqlx::query("UPDATE todo_changes SET undone = 1 WHERE id = ?")
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(Some((TodoChange::from(&row), state_ids(&before, &after))))
+}
+
+// 重做最近一次撤销的修改，返回被重做的操作和受影响的待办，没有可重做的操作时返回空
+async fn redo_change(pool: &SqlitePool) -> Result<Option<(TodoChange, Vec<i64>)>, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let row = sqlx::query_as::<_, TodoChangeRow>(
+        "SELECT id, action, todo_id, title, before_state, after_state, created_at FROM todo_changes WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let (before, after) = parse_states(&row)?;
+    if !can_apply(&mut tx, &before, &after).await? {
+        // 撤销之后又有修改，所有重做记录都已失效
+        sqlx::query("DELETE FROM todo_changes WHERE undone = 1")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        tx.commit().await.map_err(|e| e.to_string())?;
+        return Err(format!("「{}」撤销后又被修改过，无法重做", row.title));
+    }
+
+    apply_state(&mut tx, &before, &after).await?;
+    sqlx::query("UPDATE todo_changes SET undone = 0 WHERE id = ?")
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(Some((TodoChange::from(&row), state_ids(&before, &after))))
+}
+
+// 撤销最近一次待办修改，没有可撤销的操作时返回空
+#[tauri::command]
+pub async fn undo_last_todo_change(app_handle: AppHandle, pool: State<'_, SqlitePool>) -> Result<Option<TodoChange>, String> {
+    let Some((change, ids)) = undo_last_change(pool.inner()).await? else {
+        return Ok(None);
+    };
+    emit_todos_changed(&app_handle, "undo", &ids);
+    Ok(Some(change))
+}
+
+// 重做最近一次撤销的修改，没有可重做的操作时返回空
+#[tauri::command]
+pub async fn redo_todo_change(app_handle: AppHandle, pool: State<'_, SqlitePool>) -> Result<Option<TodoChange>, String> {
+    let Some((change, ids)) = redo_change(pool.inner()).await? else {
+        return Ok(None);
+    };
+    emit_todos_changed(&app_handle, "redo", &ids);
+    Ok(Some(change))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::move_to_trash;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::run_migrations(&pool).await.unwrap();
+        sqlx::query("INSERT INTO projects (id, name) VALUES (1, '工作'), (2, '生活')").execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO tags (id, name) VALUES (1, '紧急'), (2, '会议'), (3, '出差')").execute(&pool).await.unwrap();
+        pool
+    }
+
+    // 与命令中的用法相同：修改前 begin，修改后在同一事务中 finish
+    async fn add(pool: &SqlitePool, title: &str) -> i64 {
+        let mut tx = pool.begin().await.unwrap();
+        let recorder = ChangeRecorder::begin(&mut tx, "add", None).await.unwrap();
+        let id = sqlx::query("INSERT INTO todos (title, start_time, level) VALUES (?, 1000, 0)")
+            .bind(title)
+            .execute(&mut *tx)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        sqlx::query("INSERT INTO todo_tags (todo_id, tag_id) VALUES (?, 1)").bind(id).execute(&mut *tx).await.unwrap();
+        recorder.finish(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+        id
+    }
+
+    async fn update(pool: &SqlitePool, id: i64, sql: &str) {
+        let mut tx = pool.begin().await.unwrap();
+        let recorder = ChangeRecorder::begin(&mut tx, "update", Some(id)).await.unwrap();
+        sqlx::query(sql).bind(id).execute(&mut *tx).await.unwrap();
+        recorder.finish(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    async fn current(pool: &SqlitePool, id: i64) -> Vec<TodoSnapshot> {
+        snapshot(&mut pool.acquire().await.unwrap(), &[id]).await.unwrap()
+    }
+
+    async fn change_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM todo_changes").fetch_one(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn undo_add_removes_the_row() {
+        let pool = pool().await;
+        assert!(undo_last_change(&pool).await.unwrap().is_none());
+        let id = add(&pool, "写周报").await;
+        let added = current(&pool, id).await;
+
+        let (change, ids) = undo_last_change(&pool).await.unwrap().unwrap();
+        assert_eq!((change.action.as_str(), change.todo_id, change.title.as_str()), ("add", id, "写周报"));
+        assert_eq!(ids, [id]);
+        assert!(current(&pool, id).await.is_empty());
+        let tags = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM todo_tags").fetch_one(&pool).await.unwrap();
+        assert_eq!(tags, 0);
+
+        // 重做后以相同的 id 和标签恢复
+        redo_change(&pool).await.unwrap().unwrap();
+        assert_eq!(current(&pool, id).await, added);
+        assert!(redo_change(&pool).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_update_round_trip_every_column() {
+        let pool = pool().await;
+        let id = add(&pool, "写周报").await;
+        update(&pool, id, "UPDATE todos SET notes = '旧备注', project_id = 1, end_time = 2000, updated_at = '2026-01-01 00:00:00' WHERE id = ?").await;
+        let before = current(&pool, id).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let recorder = ChangeRecorder::begin(&mut tx, "update", Some(id)).await.unwrap();
+        sqlx::query(
+            "UPDATE todos SET title = '写月报', start_time = 86400, end_time = 86400, notes = NULL, level = 1,
+                 rrule = 'FREQ=WEEKLY', series_start = 86400, occurrence_start = 86400, project_id = 2, sort_order = 3,
+                 auto_complete = 1, status = 1, completed_at = 90000, all_day = 1, updated_at = '2026-02-01 00:00:00'
+             WHERE id = ?"
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM todo_tags WHERE todo_id = ?").bind(id).execute(&mut *tx).await.unwrap();
+        sqlx::query("INSERT INTO todo_tags (todo_id, tag_id) VALUES (?, 2), (?, 3)").bind(id).bind(id).execute(&mut *tx).await.unwrap();
+        recorder.finish(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+        let after = current(&pool, id).await;
+        assert_ne!(before, after);
+        assert_eq!(after[0].tag_ids, [2, 3]);
+        assert!(after[0].all_day);
+
+        undo_last_change(&pool).await.unwrap().unwrap();
+        assert_eq!(current(&pool, id).await, before);
+        redo_change(&pool).await.unwrap().unwrap();
+        assert_eq!(current(&pool, id).await, after);
+        undo_last_change(&pool).await.unwrap().unwrap();
+        assert_eq!(current(&pool, id).await, before);
+    }
+
+    #[tokio::test]
+    async fn undo_delete_restores_the_todo() {
+        let pool = pool().await;
+        let id = add(&pool, "写周报").await;
+        let before = current(&pool, id).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let recorder = ChangeRecorder::begin(&mut tx, "delete", Some(id)).await.unwrap();
+        move_to_trash(&pool, &mut tx, id, None).await.unwrap();
+        recorder.finish(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(current(&pool, id).await[0].status, 2);
+
+        let (change, _) = undo_last_change(&pool).await.unwrap().unwrap();
+        assert_eq!(change.action, "delete");
+        assert_eq!(current(&pool, id).await, before);
+    }
+
+    #[tokio::test]
+    async fn stale_snapshot_is_refused() {
+        let pool = pool().await;
+        let id = add(&pool, "写周报").await;
+        update(&pool, id, "UPDATE todos SET title = '写月报' WHERE id = ?").await;
+
+        // 记录之外的修改使最近一次记录过期
+        sqlx::query("UPDATE todos SET level = 3 WHERE id = ?").bind(id).execute(&pool).await.unwrap();
+        let row = sqlx::query_as::<_, TodoChangeRow>(
+            "SELECT id, action, todo_id, title, before_state, after_state, created_at FROM todo_changes ORDER BY id DESC LIMIT 1"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let (before, after) = parse_states(&row).unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        assert!(!can_apply(&mut conn, &after, &before).await.unwrap());
+        drop(conn);
+
+        assert!(undo_last_change(&pool).await.is_err());
+        assert_eq!(current(&pool, id).await[0].title, "写月报");
+        assert_eq!(change_count(&pool).await, 1);
+
+        // 过期的记录被丢弃，更早的添加操作同样已经过期
+        assert!(undo_last_change(&pool).await.is_err());
+        assert_eq!(change_count(&pool).await, 0);
+        assert!(undo_last_change(&pool).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn history_is_capped() {
+        let pool = pool().await;
+        let id = add(&pool, "写周报").await;
+        for level in 0..(MAX_HISTORY + 5) {
+            update(&pool, id, &format!("UPDATE todos SET sort_order = {} WHERE id = ?", level + 1)).await;
+        }
+        assert_eq!(change_count(&pool).await, MAX_HISTORY);
+
+        // 最早的记录（包括添加）被移除
+        let actions = sqlx::query_scalar::<_, String>("SELECT DISTINCT action FROM todo_changes").fetch_all(&pool).await.unwrap();
+        assert_eq!(actions, ["update"]);
+        for _ in 0..MAX_HISTORY {
+            undo_last_change(&pool).await.unwrap().unwrap();
+        }
+        assert!(undo_last_change(&pool).await.unwrap().is_none());
+        assert_eq!(current(&pool, id).await[0].sort_order, 5);
+    }
+}
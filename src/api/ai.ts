@@ -1,4 +1,5 @@
-import { api } from './index';
+import { api, ApiClient } from './index';
+import type { AddTodoParams } from './todo';
 import type { 
   AIConfig, 
   ChatMessage, 
@@ -128,6 +129,14 @@ export class AIApi {
     return response.success ? response.data || null : null;
   }
 
+  /**
+   * 用保存的 AI 配置从一段话中提取待办，返回的参数已校验修正，确认后再调用 todoApi.add
+   * 失败时抛出 ApiError，code 为 AIErrorCode
+   */
+  static async extractTodo(text: string): Promise<AddTodoParams> {
+    return ApiClient.invokeOrThrow<AddTodoParams>('ai_extract_todo', { text }, { timeout: 40000 });
+  }
+
   /**
    * 简单聊天
    */
@@ -173,5 +182,6 @@ export const aiApi = {
   updateModel: AIApi.updateModel,
   testConnection: AIApi.testAIConnection,
   chatCompletion: AIApi.chatCompletion,
+  extractTodo: AIApi.extractTodo,
   chat: AIApi.chat,
 };
\ No newline at end of file
@@ -1,6 +1,7 @@
 import { api } from './index';
 import type { CountdownConfig } from '../model/countdown';
 import type { WindowSettings } from '../model/settings';
+import type { SnoozePreset } from '../model/todo';
 
 /**
  * 数据库相关 API 服务
@@ -62,6 +63,22 @@ export class DatabaseApi {
     return response.success;
   }
 
+  /**
+   * 稍后再提醒下班，返回再次提醒的时间戳（秒）
+   */
+  static async snoozeCountdownAlert(alertDate: string, preset: SnoozePreset): Promise<number | null> {
+    const response = await api.call<number>('snooze_countdown_alert', { alertDate, preset });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 确认下班提醒
+   */
+  static async acknowledgeCountdownAlert(alertDate: string): Promise<boolean> {
+    const response = await api.call('acknowledge_countdown_alert', { alertDate });
+    return response.success;
+  }
+
 
 
   /**
@@ -92,6 +109,8 @@ export const databaseApi = {
     saveRecord: DatabaseApi.saveCountdownRecord,
     startTimer: DatabaseApi.startCountdownTimer,
     resetWorkEndToNextDay: DatabaseApi.resetWorkEndToNextDay,
+    snoozeAlert: DatabaseApi.snoozeCountdownAlert,
+    acknowledgeAlert: DatabaseApi.acknowledgeCountdownAlert,
   },
   window: {
     load: DatabaseApi.loadWindowSettings,
@@ -0,0 +1,94 @@
+import { api } from './index';
+import type { PomodoroSettings, PomodoroStatus } from '../model/countdown';
+
+/**
+ * 番茄钟相关 API 服务，计时由后端推进，状态通过 pomodoro-update 事件推送
+ */
+export class PomodoroApi {
+  /**
+   * 获取番茄钟设置
+   */
+  static async getSettings(): Promise<PomodoroSettings | null> {
+    const response = await api.call<PomodoroSettings>('get_pomodoro_settings');
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 保存番茄钟设置，从下一次开始时生效
+   */
+  static async saveSettings(settings: PomodoroSettings): Promise<boolean> {
+    const response = await api.call('save_pomodoro_settings', { settings });
+    return response.success;
+  }
+
+  /**
+   * 获取当前状态，未开始时返回 null
+   */
+  static async getStatus(): Promise<PomodoroStatus | null> {
+    const response = await api.call<PomodoroStatus | null>('get_pomodoro_status');
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 开始番茄钟，可关联一个待办
+   */
+  static async start(todoId?: number | null): Promise<PomodoroStatus | null> {
+    const response = await api.call<PomodoroStatus>('start_pomodoro', { todoId });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 暂停
+   */
+  static async pause(): Promise<PomodoroStatus | null> {
+    const response = await api.call<PomodoroStatus>('pause_pomodoro');
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 继续
+   */
+  static async resume(): Promise<PomodoroStatus | null> {
+    const response = await api.call<PomodoroStatus>('resume_pomodoro');
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 跳过当前阶段
+   */
+  static async skip(): Promise<PomodoroStatus | null> {
+    const response = await api.call<PomodoroStatus>('skip_pomodoro_phase');
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 结束番茄钟
+   */
+  static async stop(): Promise<boolean> {
+    const response = await api.call('stop_pomodoro');
+    return response.success;
+  }
+
+  /**
+   * 修改关联的待办，传 null 取消关联
+   */
+  static async setTodo(todoId: number | null): Promise<PomodoroStatus | null> {
+    const response = await api.call<PomodoroStatus>('set_pomodoro_todo', { todoId });
+    return response.success ? response.data || null : null;
+  }
+}
+
+/**
+ * 番茄钟 API 的便捷导出
+ */
+export const pomodoroApi = {
+  getSettings: PomodoroApi.getSettings,
+  saveSettings: PomodoroApi.saveSettings,
+  getStatus: PomodoroApi.getStatus,
+  start: PomodoroApi.start,
+  pause: PomodoroApi.pause,
+  resume: PomodoroApi.resume,
+  skip: PomodoroApi.skip,
+  stop: PomodoroApi.stop,
+  setTodo: PomodoroApi.setTodo,
+};
@@ -0,0 +1,104 @@
+import { api } from './index';
+import type { Project, ProjectStats } from '../model/project';
+
+/**
+ * 添加项目的参数接口
+ */
+export interface AddProjectParams {
+  name: string;
+  color?: string | null;
+  icon?: string | null;
+}
+
+/**
+ * 更新项目的参数接口
+ */
+export interface UpdateProjectParams {
+  id: number;
+  name?: string;
+  color?: string | null;
+  icon?: string | null;
+}
+
+/**
+ * 项目相关 API 服务
+ */
+export class ProjectApi {
+  /**
+   * 获取所有项目（按排序），默认不包含已归档项目
+   */
+  static async getAllProjects(includeArchived: boolean = false): Promise<Project[]> {
+    const response = await api.call<Project[]>('get_all_projects', { includeArchived });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 添加项目，返回新项目ID
+   */
+  static async addProject(params: AddProjectParams): Promise<number | null> {
+    const response = await api.call<number>('add_project', { params });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 更新项目
+   */
+  static async updateProject(params: UpdateProjectParams): Promise<boolean> {
+    const response = await api.call('update_project', { params });
+    return response.success;
+  }
+
+  /**
+   * 删除项目，项目下的待办保留并移出项目
+   */
+  static async deleteProject(id: number): Promise<boolean> {
+    const response = await api.call('delete_project', { id });
+    return response.success;
+  }
+
+  /**
+   * 按传入的顺序重新排列项目
+   */
+  static async reorderProjects(ids: number[]): Promise<boolean> {
+    const response = await api.call('reorder_projects', { ids });
+    return response.success;
+  }
+
+  /**
+   * 归档或取消归档项目，项目下的待办随之隐藏或恢复
+   */
+  static async archiveProject(id: number, archived: boolean): Promise<boolean> {
+    const response = await api.call('archive_project', { id, archived });
+    return response.success;
+  }
+
+  /**
+   * 将待办移到指定项目，projectId 为 null 表示移出项目
+   */
+  static async moveTodos(todoIds: number[], projectId: number | null): Promise<boolean> {
+    const response = await api.call('move_todos_to_project', { todoIds, projectId });
+    return response.success;
+  }
+
+  /**
+   * 获取每个项目的未完成、逾期和已完成数量
+   */
+  static async getProjectStats(): Promise<ProjectStats[]> {
+    const response = await api.call<ProjectStats[]>('get_project_stats');
+    return response.success ? response.data || [] : [];
+  }
+}
+
+/**
+ * 项目 API 的便捷导出
+ */
+export const projectApi = {
+  getAll: ProjectApi.getAllProjects,
+  add: ProjectApi.addProject,
+  update: ProjectApi.updateProject,
+  delete: ProjectApi.deleteProject,
+  reorder: ProjectApi.reorderProjects,
+  archive: ProjectApi.archiveProject,
+  moveTodos: ProjectApi.moveTodos,
+  getStats: ProjectApi.getProjectStats,
+};
@@ -3,7 +3,12 @@
 import { aiApi } from './ai';
 import { databaseApi } from './database';
 import { api } from './index';
+import { pomodoroApi } from './pomodoro';
+import { projectApi } from './project';
 import { shortcutApi } from './shortcut';
+import { smartListApi } from './smartList';
+import { tagApi } from './tag';
+import { timeEntryApi } from './timeEntry';
 import { todoApi } from './todo';
 import { weatherApi } from './weather';
 import { windowApi } from './window';
@@ -17,7 +22,26 @@ export { DatabaseApi, databaseApi } from './database';
 
 // 待办事项 API
 export { TodoApi, todoApi } from './todo';
-export type { AddTodoParams, UpdateTodoParams } from './todo';
+export type { AddTodoParams, ScheduleTodoParams, UpdateTodoParams } from './todo';
+
+// 标签 API
+export { TagApi, tagApi } from './tag';
+export type { AddTagParams, UpdateTagParams } from './tag';
+
+// 项目 API
+export { ProjectApi, projectApi } from './project';
+export type { AddProjectParams, UpdateProjectParams } from './project';
+
+// 智能清单 API
+export { SmartListApi, smartListApi } from './smartList';
+export type { AddSmartListParams, UpdateSmartListParams } from './smartList';
+
+// 计时 API
+export { TimeEntryApi, timeEntryApi } from './timeEntry';
+export type { AddTimeEntryParams, UpdateTimeEntryParams } from './timeEntry';
+
+// 番茄钟 API
+export { PomodoroApi, pomodoroApi } from './pomodoro';
 
 // 天气 API
 export { WeatherApi, weatherApi } from './weather';
@@ -54,6 +78,21 @@ export const apiServices = {
   // 待办事项服务
   todo: todoApi,
 
+  // 标签服务
+  tag: tagApi,
+
+  // 项目服务
+  project: projectApi,
+
+  // 智能清单服务
+  smartList: smartListApi,
+
+  // 计时服务
+  timeEntry: timeEntryApi,
+
+  // 番茄钟服务
+  pomodoro: pomodoroApi,
+
   // 天气服务
   weather: weatherApi,
 
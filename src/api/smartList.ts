@@ -0,0 +1,99 @@
+import { api } from './index';
+import type { SmartList, SmartListCount } from '../model/smartList';
+import type { PageParams, TodoFilter, TodoPage } from '../model/todo';
+
+/**
+ * 添加智能清单的参数接口
+ */
+export interface AddSmartListParams {
+  name: string;
+  icon?: string | null;
+  color?: string | null;
+  filter: TodoFilter;
+}
+
+/**
+ * 更新智能清单的参数接口
+ */
+export interface UpdateSmartListParams {
+  id: number;
+  name?: string;
+  icon?: string | null;
+  color?: string | null;
+  // 传入时替换全部条件
+  filter?: TodoFilter;
+}
+
+/**
+ * 智能清单相关 API 服务
+ */
+export class SmartListApi {
+  /**
+   * 获取所有智能清单（按排序）及当前数量
+   */
+  static async getSmartLists(): Promise<SmartList[]> {
+    const response = await api.call<SmartList[]>('get_smart_lists');
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 获取各智能清单的数量，用于刷新角标
+   */
+  static async getCounts(): Promise<SmartListCount[]> {
+    const response = await api.call<SmartListCount[]>('get_smart_list_counts');
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 添加智能清单，返回新清单ID
+   */
+  static async addSmartList(params: AddSmartListParams): Promise<number | null> {
+    const response = await api.call<number>('add_smart_list', { params });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 更新智能清单
+   */
+  static async updateSmartList(params: UpdateSmartListParams): Promise<boolean> {
+    const response = await api.call('update_smart_list', { params });
+    return response.success;
+  }
+
+  /**
+   * 删除智能清单，不影响待办
+   */
+  static async deleteSmartList(id: number): Promise<boolean> {
+    const response = await api.call('delete_smart_list', { id });
+    return response.success;
+  }
+
+  /**
+   * 按传入的顺序重新排列智能清单
+   */
+  static async reorderSmartLists(ids: number[]): Promise<boolean> {
+    const response = await api.call('reorder_smart_lists', { ids });
+    return response.success;
+  }
+
+  /**
+   * 按清单保存的条件分页查询待办
+   */
+  static async evaluateSmartList(id: number, page: PageParams = {}): Promise<TodoPage> {
+    const response = await api.call<TodoPage>('evaluate_smart_list', { id, page });
+    return response.success && response.data ? response.data : { items: [], total: 0 };
+  }
+}
+
+/**
+ * 智能清单 API 的便捷导出
+ */
+export const smartListApi = {
+  getAll: SmartListApi.getSmartLists,
+  getCounts: SmartListApi.getCounts,
+  add: SmartListApi.addSmartList,
+  update: SmartListApi.updateSmartList,
+  delete: SmartListApi.deleteSmartList,
+  reorder: SmartListApi.reorderSmartLists,
+  evaluate: SmartListApi.evaluateSmartList,
+};
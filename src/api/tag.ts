@@ -0,0 +1,84 @@
+import { api } from './index';
+import type { Tag } from '../model/tag';
+
+/**
+ * 添加标签的参数接口
+ */
+export interface AddTagParams {
+  name: string;
+  color?: string | null;
+}
+
+/**
+ * 更新标签的参数接口
+ */
+export interface UpdateTagParams {
+  id: number;
+  name?: string;
+  color?: string | null;
+}
+
+/**
+ * 标签相关 API 服务
+ */
+export class TagApi {
+  /**
+   * 获取所有标签（按排序）
+   */
+  static async getAllTags(): Promise<Tag[]> {
+    const response = await api.call<Tag[]>('get_all_tags');
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 添加标签，返回新标签ID
+   */
+  static async addTag(params: AddTagParams): Promise<number | null> {
+    const response = await api.call<number>('add_tag', { params });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 更新标签
+   */
+  static async updateTag(params: UpdateTagParams): Promise<boolean> {
+    const response = await api.call('update_tag', { params });
+    return response.success;
+  }
+
+  /**
+   * 删除标签
+   */
+  static async deleteTag(id: number): Promise<boolean> {
+    const response = await api.call('delete_tag', { id });
+    return response.success;
+  }
+
+  /**
+   * 按传入的顺序重新排列标签
+   */
+  static async reorderTags(ids: number[]): Promise<boolean> {
+    const response = await api.call('reorder_tags', { ids });
+    return response.success;
+  }
+
+  /**
+   * 设置待办的全部标签
+   */
+  static async setTodoTags(todoId: number, tagIds: number[]): Promise<boolean> {
+    const response = await api.call('set_todo_tags', { todoId, tagIds });
+    return response.success;
+  }
+}
+
+/**
+ * 标签 API 的便捷导出
+ */
+export const tagApi = {
+  getAll: TagApi.getAllTags,
+  add: TagApi.addTag,
+  update: TagApi.updateTag,
+  delete: TagApi.deleteTag,
+  reorder: TagApi.reorderTags,
+  setTodoTags: TagApi.setTodoTags,
+};
@@ -0,0 +1,116 @@
+import { api } from './index';
+import type { TimeEntry, TimeReport, TimeReportParams } from '../model/timeEntry';
+
+/**
+ * 手动补录计时的参数接口
+ */
+export interface AddTimeEntryParams {
+  todoId: number;
+  startTime: number;
+  endTime: number;
+  note?: string | null;
+}
+
+/**
+ * 修改计时的参数接口
+ */
+export interface UpdateTimeEntryParams {
+  id: number;
+  todoId?: number;
+  startTime?: number;
+  // 正在计时的条目传入时即停止计时
+  endTime?: number;
+  note?: string | null;
+}
+
+/**
+ * 计时相关 API 服务
+ */
+export class TimeEntryApi {
+  /**
+   * 开始为待办计时，正在为其他待办计时时先停止
+   */
+  static async start(todoId: number): Promise<TimeEntry | null> {
+    const response = await api.call<TimeEntry>('start_time_tracking', { todoId });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 停止正在进行的计时，返回被停止的条目
+   */
+  static async stop(): Promise<TimeEntry | null> {
+    const response = await api.call<TimeEntry | null>('stop_time_tracking');
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 获取正在进行的计时，应用重启后用于恢复显示
+   */
+  static async getRunning(): Promise<TimeEntry | null> {
+    const response = await api.call<TimeEntry | null>('get_running_time_entry');
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 按待办和时间区间查询计时记录
+   */
+  static async getEntries(options: { todoId?: number; from?: number; to?: number } = {}): Promise<TimeEntry[]> {
+    const response = await api.call<TimeEntry[]>('get_time_entries', options);
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 手动补录计时，返回新条目的 id
+   */
+  static async addEntry(params: AddTimeEntryParams): Promise<number | null> {
+    const response = await api.call<number>('add_time_entry', { params });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 修改计时
+   */
+  static async updateEntry(params: UpdateTimeEntryParams): Promise<TimeEntry | null> {
+    const response = await api.call<TimeEntry>('update_time_entry', { params });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 删除计时
+   */
+  static async deleteEntry(id: number): Promise<boolean> {
+    const response = await api.call('delete_time_entry', { id });
+    return response.success;
+  }
+
+  /**
+   * 按天、周、待办、优先级或项目统计计时
+   */
+  static async getReport(params: TimeReportParams): Promise<TimeReport | null> {
+    const response = await api.call<TimeReport>('get_time_report', { params });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 导出统计为 CSV 文本
+   */
+  static async exportCsv(params: TimeReportParams): Promise<string | null> {
+    const response = await api.call<string>('export_time_report_csv', { params });
+    return response.success ? response.data ?? null : null;
+  }
+}
+
+/**
+ * 计时 API 的便捷导出
+ */
+export const timeEntryApi = {
+  start: TimeEntryApi.start,
+  stop: TimeEntryApi.stop,
+  getRunning: TimeEntryApi.getRunning,
+  getEntries: TimeEntryApi.getEntries,
+  addEntry: TimeEntryApi.addEntry,
+  updateEntry: TimeEntryApi.updateEntry,
+  deleteEntry: TimeEntryApi.deleteEntry,
+  getReport: TimeEntryApi.getReport,
+  exportCsv: TimeEntryApi.exportCsv,
+};
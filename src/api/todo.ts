@@ -1,16 +1,50 @@
 import { api } from './index';
-import type { Todo } from '../model/todo';
+import type {
+  BulkResult,
+  ChangeSource,
+  DateWindow,
+  EisenhowerMatrix,
+  EscalationSettings,
+  PageParams,
+  PostponedTodo,
+  Reschedule,
+  SnoozePreset,
+  StatisticsRange,
+  Todo,
+  TodoFilter,
+  TodoChange,
+  TodoDependencies,
+  TodoEscalation,
+  TodoHistoryEntry,
+  TodoOccurrence,
+  TodoPage,
+  TodoReminder,
+  TodoSearchResult,
+  TodoStatistics,
+  TrashSettings,
+} from '../model/todo';
+import type { TodoListFilter } from '../model/tag';
 
 /**
  * 添加待办事项的参数接口
  */
 export interface AddTodoParams {
   title: string;
-  startTime: number;
+  // 不传则放入收集箱
+  startTime?: number | null;
   endTime?: number | null;
   notes?: string | null;
   level: number;
-  cycle: string;
+  // RRULE 字符串，兼容 one/day/week/month/year
+  rrule?: string | null;
+  tagIds?: number[];
+  projectId?: number | null;
+  // 传入时作为该待办的子任务
+  parentId?: number | null;
+  // 子任务全部完成时自动完成
+  autoComplete?: boolean;
+  // 全天待办，开始和截止时间按日期处理
+  allDay?: boolean;
 }
 
 /**
@@ -19,12 +53,44 @@ export interface AddTodoParams {
 export interface UpdateTodoParams {
   id: number;
   title?: string;
-  startTime?: number;
+  // 传 null 表示移回收集箱
+  startTime?: number | null;
   endTime?: number | null;
   notes?: string | null;
   level?: number;
-  cycle?: string;
+  rrule?: string | null;
   completed?: boolean;
+  // 传入时替换全部标签
+  tagIds?: number[];
+  // 传 null 表示移出项目
+  projectId?: number | null;
+  autoComplete?: boolean;
+  allDay?: boolean;
+  // 修改来源，记录到修改历史，默认 ui
+  source?: ChangeSource;
+}
+
+/**
+ * 安排待办时间的参数接口
+ */
+export interface ScheduleTodoParams {
+  id: number;
+  startTime: number;
+  endTime?: number | null;
+  allDay?: boolean;
+}
+
+/**
+ * 添加提醒的参数接口
+ */
+export interface AddReminderParams {
+  todoId: number;
+  // start: 开始时间 end: 截止时间
+  anchor: 'start' | 'end';
+  // 相对分钟数，负数表示提前
+  offsetMinutes: number;
+  // 持续提醒间隔（分钟），仅对重要且紧急的待办生效
+  nagMinutes?: number | null;
 }
 
 /**
@@ -42,19 +108,62 @@ export class TodoApi {
   /**
    * 获取所有待办事项
    */
-  static async getAllTodos(): Promise<Todo[]> {
-    const response = await api.call<Todo[]>('get_all_todos');
+  static async getAllTodos(filter: TodoListFilter = {}): Promise<Todo[]> {
+    const response = await api.call<Todo[]>('get_all_todos', { ...filter });
     return response.success ? response.data || [] : [];
   }
 
   /**
    * 获取最近的待办事项
    */
-  static async getRecentTodos(days: number = 5): Promise<Todo[]> {
-    const response = await api.call<Todo[]>('get_recent_todos', { days });
+  static async getRecentTodos(days: number = 5, filter: TodoListFilter = {}, blockedLast: boolean = false): Promise<Todo[]> {
+    const response = await api.call<Todo[]>('get_recent_todos', { days, ...filter, blockedLast });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 获取收集箱中尚未安排时间的待办事项
+   */
+  static async getInboxTodos(filter: TodoListFilter = {}): Promise<Todo[]> {
+    const response = await api.call<Todo[]>('get_inbox_todos', { ...filter });
     return response.success ? response.data || [] : [];
   }
 
+  /**
+   * 为待办安排时间，收集箱中的待办由此移出收集箱
+   */
+  static async scheduleTodo(params: ScheduleTodoParams): Promise<boolean> {
+    const response = await api.call('schedule_todo', { params });
+    return response.success;
+  }
+
+  /**
+   * 离线解析一句话（如“明天下午3点交周报，每周五重复”），返回新建待办的参数，确认后再调用 addTodo
+   */
+  static async parseQuickAdd(text: string): Promise<AddTodoParams | null> {
+    const response = await api.call<AddTodoParams>('parse_quick_add', { text });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 搜索标题和备注，关键词之间用空格分隔
+   */
+  static async searchTodos(
+    query: string,
+    options: { includeCompleted?: boolean; includeDeleted?: boolean; limit?: number } = {}
+  ): Promise<TodoSearchResult[]> {
+    const response = await api.call<TodoSearchResult[]>('search_todos', { query, ...options });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 按组合条件分页查询待办事项
+   */
+  static async queryTodos(filter: TodoFilter = {}, page: PageParams = {}): Promise<TodoPage> {
+    const response = await api.call<TodoPage>('query_todos', { filter, page });
+    return response.success && response.data ? response.data : { items: [], total: 0 };
+  }
+
   /**
    * 更新待办事项
    */
@@ -72,13 +181,213 @@ export class TodoApi {
   }
 
   /**
-   * 删除待办事项（逻辑删除）
+   * 按传入的顺序重新排列子任务
+   */
+  static async reorderSubtasks(parentId: number, ids: number[]): Promise<boolean> {
+    const response = await api.call('reorder_subtasks', { parentId, ids });
+    return response.success;
+  }
+
+  /**
+   * 删除待办事项（移入回收站），子任务一并删除
    */
   static async deleteTodo(id: number): Promise<boolean> {
     const response = await api.call('delete_todo', { id });
     return response.success;
   }
 
+  /**
+   * 批量完成待办事项，循环任务完成当前这一次
+   */
+  static async bulkComplete(ids: number[]): Promise<BulkResult[]> {
+    const response = await api.call<BulkResult[]>('bulk_complete_todos', { ids });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 批量取消完成
+   */
+  static async bulkUncomplete(ids: number[]): Promise<BulkResult[]> {
+    const response = await api.call<BulkResult[]>('bulk_uncomplete_todos', { ids });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 批量移入回收站
+   */
+  static async bulkDelete(ids: number[]): Promise<BulkResult[]> {
+    const response = await api.call<BulkResult[]>('bulk_delete_todos', { ids });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 批量从回收站恢复
+   */
+  static async bulkRestore(ids: number[]): Promise<BulkResult[]> {
+    const response = await api.call<BulkResult[]>('bulk_restore_todos', { ids });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 批量改期，截止时间随开始时间一起移动
+   */
+  static async bulkReschedule(ids: number[], reschedule: Reschedule): Promise<BulkResult[]> {
+    const response = await api.call<BulkResult[]>('bulk_reschedule_todos', { ids, reschedule });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 批量设置优先级
+   */
+  static async bulkSetLevel(ids: number[], level: number): Promise<BulkResult[]> {
+    const response = await api.call<BulkResult[]>('bulk_set_todo_level', { ids, level });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 撤销最近一次新增、修改或删除，没有可撤销的操作时返回 null
+   */
+  static async undo(): Promise<TodoChange | null> {
+    const response = await api.call<TodoChange | null>('undo_last_todo_change');
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 重做最近一次撤销的操作，没有可重做的操作时返回 null
+   */
+  static async redo(): Promise<TodoChange | null> {
+    const response = await api.call<TodoChange | null>('redo_todo_change');
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 获取待办的修改时间线
+   */
+  static async getTodoTimeline(todoId: number): Promise<TodoHistoryEntry[]> {
+    const response = await api.call<TodoHistoryEntry[]>('get_todo_timeline', { todoId });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 获取开始或截止时间被推后次数最多的待办
+   */
+  static async getMostPostponedTodos(limit?: number): Promise<PostponedTodo[]> {
+    const response = await api.call<PostponedTodo[]>('get_most_postponed_todos', { limit });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 添加依赖：todoId 需要等 dependsOnId 完成后才能开始，形成循环时失败
+   */
+  static async addDependency(todoId: number, dependsOnId: number): Promise<boolean> {
+    const response = await api.call('add_todo_dependency', { todoId, dependsOnId });
+    return response.success;
+  }
+
+  /**
+   * 移除依赖
+   */
+  static async removeDependency(todoId: number, dependsOnId: number): Promise<boolean> {
+    const response = await api.call('remove_todo_dependency', { todoId, dependsOnId });
+    return response.success;
+  }
+
+  /**
+   * 获取待办的前置任务和后续任务
+   */
+  static async getDependencies(todoId: number): Promise<TodoDependencies | null> {
+    const response = await api.call<TodoDependencies>('get_todo_dependencies', { todoId });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 获取完成情况统计
+   */
+  static async getStatistics(range: StatisticsRange): Promise<TodoStatistics | null> {
+    const response = await api.call<TodoStatistics>('get_todo_statistics', { range });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 获取按四象限分组的未完成待办，不指定范围时返回全部
+   */
+  static async getEisenhowerMatrix(range?: DateWindow): Promise<EisenhowerMatrix | null> {
+    const response = await api.call<EisenhowerMatrix>('get_eisenhower_matrix', { range });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 把待办移到另一个象限
+   */
+  static async moveToQuadrant(id: number, level: number): Promise<boolean> {
+    const response = await api.call('move_todo_to_quadrant', { id, level });
+    return response.success;
+  }
+
+  /**
+   * 获取回收站中的待办事项
+   */
+  static async listDeletedTodos(): Promise<Todo[]> {
+    const response = await api.call<Todo[]>('list_deleted_todos');
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 从回收站恢复待办事项，随它一起删除的子任务一并恢复
+   */
+  static async restoreTodo(id: number): Promise<boolean> {
+    const response = await api.call('restore_todo', { id });
+    return response.success;
+  }
+
+  /**
+   * 彻底删除回收站中的待办事项
+   */
+  static async purgeTodo(id: number): Promise<boolean> {
+    const response = await api.call('purge_todo', { id });
+    return response.success;
+  }
+
+  /**
+   * 获取回收站设置
+   */
+  static async getTrashSettings(): Promise<TrashSettings | null> {
+    const response = await api.call<TrashSettings>('get_trash_settings');
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 保存回收站设置
+   */
+  static async saveTrashSettings(settings: TrashSettings): Promise<boolean> {
+    const response = await api.call('save_trash_settings', { settings });
+    return response.success;
+  }
+
+  /**
+   * 获取优先级自动升级规则
+   */
+  static async getEscalationSettings(): Promise<EscalationSettings | null> {
+    const response = await api.call<EscalationSettings>('get_escalation_settings');
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 保存优先级自动升级规则
+   */
+  static async saveEscalationSettings(settings: EscalationSettings): Promise<boolean> {
+    const response = await api.call('save_escalation_settings', { settings });
+    return response.success;
+  }
+
+  /**
+   * 获取待办的自动升级记录
+   */
+  static async getTodoEscalations(todoId: number): Promise<TodoEscalation[]> {
+    const response = await api.call<TodoEscalation[]>('get_todo_escalations', { todoId });
+    return response.success ? response.data || [] : [];
+  }
+
   /**
    * 标记待办事项为完成
    */
@@ -100,6 +409,78 @@ export class TodoApi {
     return this.updateTodo({ id, completed });
   }
 
+  /**
+   * 完成循环任务的当前这一次
+   */
+  static async completeOccurrence(id: number): Promise<Todo | null> {
+    const response = await api.call<Todo>('complete_todo_occurrence', { id });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 跳过循环任务的当前这一次
+   */
+  static async skipOccurrence(id: number): Promise<Todo | null> {
+    const response = await api.call<Todo>('skip_todo_occurrence', { id });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 推迟循环任务的当前这一次，source 为修改来源，记录到修改历史中
+   */
+  static async postponeOccurrence(id: number, startTime: number, source?: ChangeSource): Promise<Todo | null> {
+    const response = await api.call<Todo>('postpone_todo_occurrence', { id, startTime, source });
+    return response.success ? response.data || null : null;
+  }
+
+  /**
+   * 获取循环任务的历史记录
+   */
+  static async getOccurrences(id: number): Promise<TodoOccurrence[]> {
+    const response = await api.call<TodoOccurrence[]>('get_todo_occurrences', { id });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 添加待办提醒
+   */
+  static async addReminder(params: AddReminderParams): Promise<number | null> {
+    const response = await api.call<number>('add_todo_reminder', { params });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 获取待办的所有提醒
+   */
+  static async getReminders(todoId: number): Promise<TodoReminder[]> {
+    const response = await api.call<TodoReminder[]>('get_todo_reminders', { todoId });
+    return response.success ? response.data || [] : [];
+  }
+
+  /**
+   * 删除待办提醒
+   */
+  static async deleteReminder(id: number): Promise<boolean> {
+    const response = await api.call('delete_todo_reminder', { id });
+    return response.success;
+  }
+
+  /**
+   * 稍后提醒，返回再次提醒的时间戳（秒）
+   */
+  static async snoozeReminder(id: number, preset: SnoozePreset): Promise<number | null> {
+    const response = await api.call<number>('snooze_todo_reminder', { id, preset });
+    return response.success ? response.data ?? null : null;
+  }
+
+  /**
+   * 确认提醒，停止持续提醒
+   */
+  static async acknowledgeReminder(id: number): Promise<boolean> {
+    const response = await api.call('acknowledge_todo_reminder', { id });
+    return response.success;
+  }
+
   /**
    * 加载 TODO 颜色设置
    */
@@ -132,12 +513,51 @@ export const todoApi = {
   add: TodoApi.addTodo,
   getAll: TodoApi.getAllTodos,
   getRecent: TodoApi.getRecentTodos,
+  getInbox: TodoApi.getInboxTodos,
+  schedule: TodoApi.scheduleTodo,
+  parseQuickAdd: TodoApi.parseQuickAdd,
+  search: TodoApi.searchTodos,
+  query: TodoApi.queryTodos,
   getById: TodoApi.getTodoById,
   update: TodoApi.updateTodo,
   delete: TodoApi.deleteTodo,
+  bulkComplete: TodoApi.bulkComplete,
+  bulkUncomplete: TodoApi.bulkUncomplete,
+  bulkDelete: TodoApi.bulkDelete,
+  bulkRestore: TodoApi.bulkRestore,
+  bulkReschedule: TodoApi.bulkReschedule,
+  bulkSetLevel: TodoApi.bulkSetLevel,
+  undo: TodoApi.undo,
+  redo: TodoApi.redo,
+  getTimeline: TodoApi.getTodoTimeline,
+  getMostPostponed: TodoApi.getMostPostponedTodos,
+  addDependency: TodoApi.addDependency,
+  removeDependency: TodoApi.removeDependency,
+  getDependencies: TodoApi.getDependencies,
+  getStatistics: TodoApi.getStatistics,
+  getEisenhowerMatrix: TodoApi.getEisenhowerMatrix,
+  moveToQuadrant: TodoApi.moveToQuadrant,
+  listDeleted: TodoApi.listDeletedTodos,
+  restore: TodoApi.restoreTodo,
+  purge: TodoApi.purgeTodo,
+  getTrashSettings: TodoApi.getTrashSettings,
+  saveTrashSettings: TodoApi.saveTrashSettings,
+  getEscalationSettings: TodoApi.getEscalationSettings,
+  saveEscalationSettings: TodoApi.saveEscalationSettings,
+  getEscalations: TodoApi.getTodoEscalations,
+  reorderSubtasks: TodoApi.reorderSubtasks,
   complete: TodoApi.completeTodo,
   uncomplete: TodoApi.uncompleteTodo,
   toggleComplete: TodoApi.toggleTodoComplete,
+  completeOccurrence: TodoApi.completeOccurrence,
+  skipOccurrence: TodoApi.skipOccurrence,
+  postponeOccurrence: TodoApi.postponeOccurrence,
+  getOccurrences: TodoApi.getOccurrences,
+  addReminder: TodoApi.addReminder,
+  getReminders: TodoApi.getReminders,
+  deleteReminder: TodoApi.deleteReminder,
+  snoozeReminder: TodoApi.snoozeReminder,
+  acknowledgeReminder: TodoApi.acknowledgeReminder,
   loadColorSettings: TodoApi.loadColorSettings,
   saveColorSettings: TodoApi.saveColorSettings,
   applyColorsToMainWindow: TodoApi.applyColorsToMainWindow,
@@ -1,6 +1,4 @@
-import { dailyMotivationalQuotePrompt, ExtractionTaskPrompt } from "./prompt"
-import { GDate } from "./date"
-import { TodoVo } from '@/model/todo';
+import { dailyMotivationalQuotePrompt } from "./prompt"
 import { aiApi } from '@/api/services';
 import type { 
     ChatMessage, 
@@ -306,18 +304,6 @@ export class AIService {
         }
     }
 
-    async extTask(input: string): Promise<TodoVo | null> {
-        let prompt = `现在是${new GDate().toChineseString()}; 要提取的文本是：${input}`
-        let response = await this.chat(prompt, false, ExtractionTaskPrompt)
-        let res = null
-        if (response) {
-            let todoStr = response.replace('```json', '').replace('```', '').trim();
-            res = JSON.parse(todoStr) as TodoVo;
-
-        }
-        return res
-    }
-
     async dailyMotivationalQuote(): Promise<string> {
         let prompt = `请生成`
         let response = await this.chat(prompt, false, dailyMotivationalQuotePrompt)
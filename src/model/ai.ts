@@ -56,4 +56,16 @@ export interface AIServiceStatus {
   hasBaseUrl: boolean;
   hasModel: boolean;
   isFullyConfigured: boolean;
-}
\ No newline at end of file
+}
+
+/**
+ * AI 提取待办失败的原因
+ */
+export type AIErrorCode =
+  | 'NOT_CONFIGURED'
+  | 'EMPTY_INPUT'
+  | 'DATABASE_ERROR'
+  | 'NETWORK_ERROR'
+  | 'HTTP_ERROR'
+  | 'BAD_RESPONSE'
+  | 'INVALID_TODO';
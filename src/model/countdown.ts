@@ -43,4 +43,33 @@ export interface DatabaseCommands {
   saveCountdownConfig(config: CountdownConfig): Promise<void>;
   saveCountdownRecord(record: CountdownRecord): Promise<void>;
   updateCountdownConfig(config: Partial<CountdownConfig>): Promise<void>;
-}
\ No newline at end of file
+}
+// 番茄钟设置
+export interface PomodoroSettings {
+    focusMinutes: number
+    shortBreakMinutes: number
+    longBreakMinutes: number
+    rounds: number        // 每完成几个专注进行一次长休息
+    autoStart: boolean    // 阶段结束后自动开始下一阶段
+}
+
+export type PomodoroPhase = 'focus' | 'shortBreak' | 'longBreak'
+
+// pomodoro-update 事件的内容
+export interface PomodoroStatus {
+    phase: PomodoroPhase
+    round: number         // 当前是第几个专注
+    rounds: number
+    todoId?: number | null
+    duration: number      // 本阶段计划时长（秒）
+    remaining: number     // 剩余秒数
+    status: 'running' | 'paused'
+}
+
+// pomodoro-phase 事件的内容
+export interface PomodoroPhaseEvent {
+    finished: PomodoroPhase
+    result: 'finished' | 'skipped'
+    todoId?: number | null
+    next: PomodoroStatus
+}
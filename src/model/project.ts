@@ -0,0 +1,21 @@
+// 项目/清单，用于区分工作与个人等不同来源的待办
+export interface Project {
+    id: number;
+    name: string;
+    color?: string | null;
+    icon?: string | null;
+    // 归档后项目下的待办不再出现在列表中
+    archived: boolean;
+    sortOrder: number;
+    created_at: string;
+    updated_at: string;
+}
+
+// 每个项目的待办统计，projectId 为空表示未归入项目的待办
+export interface ProjectStats {
+    projectId: number | null;
+    pending: number;
+    // 未完成且已过截止时间
+    overdue: number;
+    completed: number;
+}
@@ -0,0 +1,23 @@
+import type { TodoFilter } from './todo';
+
+// 智能清单：保存的查询条件，如“过期的重要任务”
+export interface SmartList {
+    id: number;
+    name: string;
+    icon?: string | null;
+    color?: string | null;
+    filter: TodoFilter;
+    sortOrder: number;
+    // 当前符合条件的待办数，用于角标
+    count: number;
+    // 保存的条件无法解析时的原因，此时数量为 0
+    error?: string | null;
+    created_at: string;
+    updated_at: string;
+}
+
+// 智能清单的角标数量
+export interface SmartListCount {
+    id: number;
+    count: number;
+}
@@ -0,0 +1,20 @@
+// 标签，如 @office、@home 或项目标签
+export interface Tag {
+    id: number;
+    name: string;
+    color?: string | null;
+    sortOrder: number;
+    created_at: string;
+    updated_at: string;
+}
+
+// 列表的标签过滤条件，matchAll 为 true 时需要同时带有所有标签
+export interface TagFilter {
+    tagIds?: number[];
+    matchAll?: boolean;
+}
+
+// 列表过滤条件：标签与项目
+export interface TodoListFilter extends TagFilter {
+    projectId?: number;
+}
@@ -0,0 +1,43 @@
+// 一段计时，endTime 为空表示正在计时
+export interface TimeEntry {
+    id: number;
+    // 待办已被彻底删除时为空，todoTitle 为删除时的标题
+    todoId: number | null;
+    todoTitle: string;
+    // 开始时间戳（秒）
+    startTime: number;
+    // 结束时间戳（秒）
+    endTime?: number | null;
+    note?: string | null;
+    // 时长（秒），正在计时的算到现在
+    duration: number;
+    created_at: string;
+    updated_at: string;
+}
+
+// 计时统计的分组方式
+export type TimeReportGroup = 'day' | 'week' | 'todo' | 'level' | 'project';
+
+export interface TimeReportParams {
+    // 统计区间 [from, to)，时间戳（秒）
+    from: number;
+    to: number;
+    groupBy: TimeReportGroup;
+}
+
+export interface TimeReportRow {
+    // 日期、周一日期、待办/优先级/项目 id，未归入项目或待办已被彻底删除时为空字符串
+    key: string;
+    label: string;
+    seconds: number;
+    // 涉及的计时条数
+    entries: number;
+}
+
+export interface TimeReport {
+    groupBy: TimeReportGroup;
+    from: number;
+    to: number;
+    rows: TimeReportRow[];
+    totalSeconds: number;
+}
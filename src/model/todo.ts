@@ -1,3 +1,5 @@
+import type { Tag } from './tag';
+
 //   { id: 1, title: '完成项目报告', startTime: 1690732800, endTime: 1690819200, notes: '包含Q2数据', expanded: false }
 export interface Todo {
     id: number;
@@ -6,13 +8,39 @@ export interface Todo {
     category?: string;
     // 0、重要不紧急 1、重要且紧急 2、不重要不紧急 3、不重要但紧急
     level: number;
-    cycle: 'one' | 'day' | 'week' | 'month' | 'year';
-    // 开始时间戳（秒）
-    startTime: number;
+    // RFC 5545 RRULE，如 FREQ=WEEKLY;BYDAY=TU,TH，为空表示不重复
+    rrule?: string | null;
+    // 循环系列第一次的开始时间戳（秒）
+    seriesStart?: number | null;
+    // 当前这一次按规则计划的开始时间戳（秒）
+    occurrenceStart?: number | null;
+    // 开始时间戳（秒），为空表示收集箱中尚未安排的待办
+    startTime: number | null;
     // 结束时间戳（秒），可为空
     endTime?: number;
+    // 全天待办，开始和截止时间均为当天零点
+    allDay?: boolean;
     // 0: 未开始 1: 已完成 2: 已删除
     status: number;
+    // 移入回收站的时间戳（秒）
+    deletedAt?: number | null;
+    // 完成的时间戳（秒），未完成为空
+    completedAt?: number | null;
+    tags?: Tag[];
+    // 所属项目，为空表示未归入项目
+    projectId?: number | null;
+    // 父任务，为空表示顶层待办
+    parentId?: number | null;
+    // 子任务在父任务中的顺序
+    sortOrder?: number;
+    // 子任务全部完成时自动完成
+    autoComplete?: boolean;
+    // 子任务，仅 getTodoById 返回
+    children?: Todo[];
+    // 由子任务推算的进度，没有子任务时为空
+    progress?: TodoProgress | null;
+    // 还有未完成的前置任务
+    blocked?: boolean;
     // Ui 用的字段，无需存，默认false
     expanded: boolean;
 }
@@ -23,7 +51,270 @@ export type TodoVo = {
     category?: string;
     // 0、重要不紧急 1、重要且紧急 2、不重要不紧急 3、不重要但紧急
     level: number;
-    cycle: 'one' | 'day' | 'week' | 'month' | 'year';
+    // one/day/week/month/year 或 RRULE
+    cycle: string;
     start_time: string;
     end_time?: string;
+    // 全天待办，时间只有日期
+    allDay?: boolean;
+}
+
+// 搜索结果，高亮内容为已转义的 HTML，匹配部分用 <mark> 包裹
+export interface TodoSearchResult {
+    todo: Todo;
+    titleHighlight: string;
+    notesSnippet?: string | null;
+}
+
+// 父任务的子任务完成进度
+export interface TodoProgress {
+    done: number;
+    total: number;
+}
+
+// 循环任务每一次的处理记录
+export interface TodoOccurrence {
+    id: number;
+    todoId: number;
+    scheduledTime: number;
+    actualStart: number;
+    status: 'done' | 'skipped' | 'missed';
+    recorded_at: string;
+}
+
+// 待办提醒，offsetMinutes 为负数表示提前
+export interface TodoReminder {
+    id: number;
+    todoId: number;
+    anchor: 'start' | 'end';
+    offsetMinutes: number;
+    remindAt?: number;
+    firedAt?: number;
+    snoozedUntil?: number;
+    acknowledgedAt?: number;
+    // 持续提醒间隔（分钟），仅对重要且紧急的待办生效
+    nagMinutes?: number;
+    created_at: string;
+}
+
+// 稍后提醒：5分钟、1小时、明天早上
+export type SnoozePreset = '5m' | '1h' | 'tomorrow';
+
+// 后端 todo-reminder 事件的内容
+export interface TodoReminderEvent {
+    reminderId: number;
+    todoId: number;
+    title: string;
+    anchor: 'start' | 'end';
+    offsetMinutes: number;
+    anchorTime: number;
+    remindAt: number;
+    // 应用关闭期间错过、启动后补发的提醒
+    missed: boolean;
+    // 稍后提醒或持续提醒再次弹出
+    repeat: boolean;
+}
+
+// 组合查询的排序字段，priority 按四象限排序、同级按开始时间
+export type TodoSortKey = 'startTime' | 'endTime' | 'createdAt' | 'updatedAt' | 'priority' | 'title';
+
+// 相对当天的时间窗口，thisWeek 为周一到周日，next7Days 从今天起算
+export type DateWindow = 'today' | 'tomorrow' | 'thisWeek' | 'next7Days' | 'thisMonth';
+
+// 组合查询条件，各条件之间为“且”；statuses 默认为未完成和已完成
+export interface TodoFilter {
+    statuses?: number[];
+    levels?: number[];
+    startFrom?: number;
+    startTo?: number;
+    endFrom?: number;
+    endTo?: number;
+    // 相对当天的开始时间窗口，每次查询时重新计算
+    startWindow?: DateWindow;
+    // 未完成且已过截止时间
+    overdue?: boolean;
+    // false 只查收集箱中尚未安排时间的待办
+    scheduled?: boolean;
+    hasDeadline?: boolean;
+    recurringOnly?: boolean;
+    // 标题或备注包含所有关键词，关键词用空格分隔
+    text?: string;
+    tagIds?: number[];
+    matchAllTags?: boolean;
+    projectId?: number;
+    includeSubtasks?: boolean;
+    sort?: TodoSortKey;
+    descending?: boolean;
+}
+
+// 分页参数，传入 cursor 时忽略 offset
+export interface PageParams {
+    limit?: number;
+    offset?: number;
+    cursor?: string | null;
+}
+
+// 一页查询结果，没有下一页时 nextCursor 和 nextOffset 为空
+export interface TodoPage {
+    items: Todo[];
+    total: number;
+    nextCursor?: string | null;
+    nextOffset?: number | null;
+}
+
+// 回收站设置，retentionDays 为 0 表示不自动清理
+export interface TrashSettings {
+    retentionDays: number;
+}
+
+// 临近截止自动升级优先级的规则
+export interface EscalationSettings {
+    // 截止前 promoteHours 小时把不紧急的待办升级为紧急
+    promoteEnabled: boolean;
+    promoteHours: number;
+    // 过了截止时间标记为逾期
+    overdueEnabled: boolean;
+}
+
+// 规则的一次触发，后端 todos-escalated 事件的内容为该类型的数组
+export interface TodoEscalation {
+    id: number;
+    todoId: number;
+    title: string;
+    rule: 'promote' | 'overdue';
+    endTime: number;
+    oldLevel: number;
+    newLevel: number;
+    createdAt: number;
+}
+
+// 被撤销或重做的待办修改
+export interface TodoChange {
+    id: number;
+    action: 'add' | 'update' | 'delete';
+    todoId: number;
+    title: string;
+    created_at: string;
+}
+
+// 后端 todos-changed 事件的内容
+export interface TodosChangedEvent {
+    action: string;
+    ids: number[];
+}
+
+// 批量操作中每一条待办的结果
+export interface BulkResult {
+    id: number;
+    success: boolean;
+    error?: string | null;
+}
+
+// 批量改期：整体偏移若干分钟，或保持时刻不变移到指定日期（YYYY-MM-DD）
+export type Reschedule =
+    | { type: 'offset'; minutes: number }
+    | { type: 'date'; date: string };
+
+// 修改来源
+export type ChangeSource = 'ui' | 'shortcut' | 'ai' | 'import' | 'escalation';
+
+// 待办某个字段的一次修改，字段名与 Todo 的属性名一致（标签为 tagIds）
+export interface TodoHistoryEntry {
+    id: number;
+    todoId: number;
+    field: string;
+    oldValue: unknown;
+    newValue: unknown;
+    source: ChangeSource;
+    // 修改时间戳（秒）
+    changedAt: number;
+}
+
+// 被推迟次数最多的待办
+export interface PostponedTodo {
+    todoId: number;
+    title: string;
+    // 截止时间被推后的次数
+    deadlinePostpones: number;
+    // 开始时间被推后的次数
+    startPostpones: number;
+    // 截止时间累计推后的秒数
+    deadlineDelay: number;
+    lastPostponedAt: number;
+}
+
+// 待办的前置任务和后续任务
+export interface TodoDependencies {
+    // 需要先完成的待办
+    blockedBy: Todo[];
+    // 等待该待办完成的待办
+    blocking: Todo[];
+}
+
+// 后端 todos-unblocked 事件的内容：完成 completedIds 后不再被阻塞的待办
+export interface TodosUnblockedEvent {
+    completedIds: number[];
+    ids: number[];
+}
+
+// 统计区间 [from, to)，时间戳（秒）
+export interface StatisticsRange {
+    from: number;
+    to: number;
+}
+
+export interface PeriodStatistics {
+    // 日期、周一日期或 YYYY-MM
+    period: string;
+    completed: number;
+    onTime: number;
+    late: number;
+}
+
+// 每个优先级（四象限）的统计
+export interface LevelStatistics {
+    level: number;
+    completed: number;
+    // 占区间内全部完成数的比例
+    share: number;
+    late: number;
+    // 有截止时间的完成中平均推迟的秒数，按时完成记为 0
+    averageDelay?: number | null;
+}
+
+export interface TodoStatistics {
+    from: number;
+    to: number;
+    completed: number;
+    onTime: number;
+    late: number;
+    noDeadline: number;
+    daily: PeriodStatistics[];
+    weekly: PeriodStatistics[];
+    monthly: PeriodStatistics[];
+    levels: LevelStatistics[];
+    // 截至今天（今天还没完成时截至昨天）连续有完成的天数
+    currentStreak: number;
+    longestStreak: number;
+}
+
+export interface MatrixTodo extends Todo {
+    // 已过截止时间仍未完成
+    overdue: boolean;
+}
+
+export interface Quadrant {
+    level: number;
+    label: string;
+    count: number;
+    overdueCount: number;
+    todos: MatrixTodo[];
+}
+
+// 四象限视图
+export interface EisenhowerMatrix {
+    importantUrgent: Quadrant;
+    importantNotUrgent: Quadrant;
+    notImportantUrgent: Quadrant;
+    notImportantNotUrgent: Quadrant;
 }
\ No newline at end of file